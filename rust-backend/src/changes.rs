@@ -0,0 +1,121 @@
+//! Classification of a workspace's files against the content hashes recorded
+//! by the previous indexing pass. Pure: callers read the hashes (sidecar,
+//! filesystem) and act on the result, so the rules live in one place.
+//!
+//! A rename shows up as the old path in `removed` and the new one in
+//! `changed`; an empty file is classified by its hash like any other.
+
+use std::collections::{HashMap, HashSet};
+
+/// How the current files compare with the previous pass.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChanges<F> {
+    /// Files whose hash matches the recorded one, in input order.
+    pub unchanged: Vec<F>,
+    /// New files and files whose hash differs, in input order.
+    pub changed: Vec<F>,
+    /// Keys recorded last time with no current file, sorted.
+    pub removed: Vec<String>,
+}
+
+/// Split `current` (file, content hash) pairs into unchanged and changed
+/// against `previous` (key -> hash), and list the previous keys that no
+/// longer exist. `key_of` maps a file to the key `previous` is stored under.
+pub fn classify<F: Clone>(
+    previous: &HashMap<String, String>,
+    current: &[(F, String)],
+    key_of: impl Fn(&F) -> String,
+) -> FileChanges<F> {
+    let mut unchanged = Vec::new();
+    let mut changed = Vec::new();
+    let mut seen: HashSet<String> = HashSet::with_capacity(current.len());
+
+    for (file, hash) in current {
+        let key = key_of(file);
+        if previous.get(&key) == Some(hash) {
+            unchanged.push(file.clone());
+        } else {
+            changed.push(file.clone());
+        }
+        seen.insert(key);
+    }
+
+    let mut removed: Vec<String> = previous
+        .keys()
+        .filter(|key| !seen.contains(*key))
+        .cloned()
+        .collect();
+    removed.sort_unstable();
+
+    FileChanges {
+        unchanged,
+        changed,
+        removed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn previous(entries: &[(&str, &str)]) -> HashMap<String, String> {
+        entries
+            .iter()
+            .map(|(key, hash)| (key.to_string(), hash.to_string()))
+            .collect()
+    }
+
+    fn current(entries: &[(&str, &str)]) -> Vec<(String, String)> {
+        entries
+            .iter()
+            .map(|(file, hash)| (file.to_string(), hash.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn classifies_unchanged_changed_removed_and_new() {
+        let previous = previous(&[("a.rs", "h1"), ("b.rs", "h2"), ("gone.rs", "h3")]);
+        let current = current(&[("a.rs", "h1"), ("b.rs", "h2-edited"), ("new.rs", "h4")]);
+
+        let changes = classify(&previous, &current, |file| file.clone());
+
+        assert_eq!(changes.unchanged, ["a.rs"]);
+        assert_eq!(changes.changed, ["b.rs", "new.rs"]);
+        assert_eq!(changes.removed, ["gone.rs"]);
+    }
+
+    #[test]
+    fn empty_previous_marks_everything_changed() {
+        let current = current(&[("a.rs", "h1"), ("empty.rs", "e3b0")]);
+
+        let changes = classify(&HashMap::new(), &current, |file| file.clone());
+
+        assert!(changes.unchanged.is_empty());
+        assert_eq!(changes.changed, ["a.rs", "empty.rs"]);
+        assert!(changes.removed.is_empty());
+    }
+
+    #[test]
+    fn rename_is_a_removal_plus_a_change() {
+        let previous = previous(&[("old.rs", "h1")]);
+        let current = current(&[("new.rs", "h1")]);
+
+        let changes = classify(&previous, &current, |file| file.clone());
+
+        assert_eq!(changes.changed, ["new.rs"]);
+        assert_eq!(changes.removed, ["old.rs"]);
+    }
+
+    #[test]
+    fn files_sharing_a_key_are_each_compared_with_it() {
+        let previous = previous(&[("readme.md", "h1")]);
+        let current = current(&[("README.md", "h1"), ("readme.md", "h2")]);
+
+        let changes = classify(&previous, &current, |file| file.to_lowercase());
+
+        assert_eq!(changes.unchanged, ["README.md"]);
+        assert_eq!(changes.changed, ["readme.md"]);
+        // The key is still present, so nothing counts as removed
+        assert!(changes.removed.is_empty());
+    }
+}
@@ -3,6 +3,12 @@ use serde::{Deserialize, Serialize};
 /// Maximum allowed length for search queries (characters).
 pub const MAX_SEARCH_QUERY_LENGTH: usize = 1000;
 
+/// Results returned by full-text and grep search when the request omits `limit`.
+pub const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+/// Default ceiling on a search request's `limit` (see `AppConfig::max_search_results`).
+pub const MAX_SEARCH_RESULTS: usize = 1000;
+
 /// Canonical list of file extensions considered indexable.
 /// The Tantivy full-text indexer uses this list.
 pub const SUPPORTED_EXTENSIONS: &[&str] = &[
@@ -40,6 +46,35 @@ pub fn is_supported_extension(ext: &str) -> bool {
     SUPPORTED_EXTENSIONS.contains(&ext)
 }
 
+/// Dependency lockfiles: generated, often huge, and rarely worth searching.
+/// Skipped by the indexer unless `AppConfig::index_lockfiles` is set; they
+/// still appear in directory listings.
+pub const LOCKFILE_NAMES: &[&str] = &[
+    // JavaScript
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lock",
+    // Rust
+    "cargo.lock",
+    // Python
+    "poetry.lock",
+    "pipfile.lock",
+    "pdm.lock",
+    "uv.lock",
+    // Go
+    "go.sum",
+    "go.work.sum",
+    // Ruby
+    "gemfile.lock",
+];
+
+/// Check whether a file name (any case) is a known dependency lockfile.
+pub fn is_lockfile(name: &str) -> bool {
+    LOCKFILE_NAMES.contains(&name.to_lowercase().as_str())
+}
+
 /// Canonical list of directories to exclude from indexing, file walking, and tree display.
 /// Both `IndexManager::is_build_or_output_dir` and `WorkspaceManager::should_exclude`
 /// reference this single list so they never diverge.
@@ -104,32 +139,160 @@ pub fn matches_user_exclude_patterns(name: &str, patterns: &[String]) -> bool {
             return true;
         }
         // Suffix wildcard: *.ext
-        if let Some(suffix) = p.strip_prefix('*') {
-            if name_lower.ends_with(&suffix) {
-                return true;
-            }
+        if let Some(suffix) = p.strip_prefix('*')
+            && name_lower.ends_with(&suffix)
+        {
+            return true;
         }
         // Prefix wildcard: prefix*
-        if let Some(prefix) = p.strip_suffix('*') {
-            if name_lower.starts_with(&prefix) {
-                return true;
-            }
+        if let Some(prefix) = p.strip_suffix('*')
+            && name_lower.starts_with(prefix)
+        {
+            return true;
         }
     }
     false
 }
 
+/// Backend used by the file watcher.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WatcherMode {
+    /// Native OS events, or polling when the workspace looks like a network mount.
+    #[default]
+    Auto,
+    /// Always use native OS events (inotify / FSEvents / ReadDirectoryChangesW).
+    Native,
+    /// Always poll; needed for NFS/SMB shares where native events are missed.
+    Poll,
+}
+
+impl std::str::FromStr for WatcherMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "native" => Ok(Self::Native),
+            "poll" => Ok(Self::Poll),
+            other => Err(format!("unknown watcher mode '{}'", other)),
+        }
+    }
+}
+
+/// Response compression algorithm offered to clients via `Accept-Encoding`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompressionAlgorithm {
+    Gzip,
+    Zstd,
+}
+
+impl std::str::FromStr for CompressionAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "gzip" => Ok(Self::Gzip),
+            "zstd" => Ok(Self::Zstd),
+            other => Err(format!("unknown compression algorithm '{}'", other)),
+        }
+    }
+}
+
+/// Default location of the optional TOML config file, used when `VYOTIQ_CONFIG` is unset.
+fn default_config_path() -> Option<std::path::PathBuf> {
+    dirs::config_dir().map(|d| d.join("vyotiq-backend").join("config.toml"))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppConfig {
     pub listen_addr: String,
     pub max_index_size_mb: usize,
     pub max_file_size_bytes: usize,
     pub watcher_debounce_ms: u64,
+    /// Number of distinct changed files within one debounce window above which
+    /// the watcher runs a single incremental `index_workspace` pass instead of
+    /// one `reindex_file` per file (e.g. during a large `git checkout`).
+    pub watcher_batch_threshold: usize,
+    /// File watcher backend: `auto`, `native`, or `poll`.
+    pub watcher_mode: WatcherMode,
+    /// Poll interval used when the watcher runs in polling mode.
+    pub watcher_poll_interval_ms: u64,
+    /// Top-level directories (relative to the workspace root) to watch; empty
+    /// watches the whole tree. Files directly in the root are always watched.
+    /// Changes elsewhere are not picked up until the next full indexing pass.
+    /// Listed directories created after watching starts are added as they appear.
+    pub watch_dirs: Vec<String>,
+    /// Don't watch top-level directories the indexer skips (`node_modules`,
+    /// `target`, `.git`, user exclude patterns), so notify never descends into
+    /// them. Saves most inotify watches on large repos; the same caveats as
+    /// `watch_dirs` apply, and excluded directories nested deeper are still
+    /// watched.
+    pub watch_skip_excluded_dirs: bool,
     pub index_batch_size: usize,
+    /// Threads that read, hash and parse files during indexing (0 = number of
+    /// CPUs, at most 8). Fewer threads cost a little throughput on fast SSDs
+    /// but keep spinning disks from thrashing and leave CPU for other work on
+    /// shared build machines.
+    pub index_read_threads: usize,
+    /// Files written between intermediate commits during a full indexing pass
+    /// (0 = single commit at the end). Each commit makes the progress so far
+    /// searchable and durable — an interrupted pass resumes from the last
+    /// commit instead of starting over — at the cost of extra segment merges
+    /// that slow the pass down, more so with small intervals.
+    pub index_commit_interval: usize,
+    /// Full-text indexes kept open in memory at once. Beyond this, the least
+    /// recently used idle index is unloaded and reopened from disk on its next
+    /// use (0 = keep every index loaded).
+    pub max_loaded_indexes: usize,
     pub data_dir: String,
     /// Maximum number of files to index per workspace.
-    /// Prevents unbounded memory growth for very large monorepos.
+    /// Prevents unbounded memory growth for very large monorepos. The walk
+    /// visits files in path order and stops at the cap, so the same files are
+    /// left out on every pass; the status route reports `truncated`.
     pub max_indexed_files: usize,
+    /// Upper bound applied to the `limit` of full-text and grep search
+    /// requests; larger values are clamped and the effective limit is echoed
+    /// back in the response. Lower it to bound per-request memory.
+    pub max_search_results: usize,
+    /// Store file content in the Tantivy doc store so search previews can be
+    /// built without reading files from disk (and still work for files deleted
+    /// since indexing). Off by default: it roughly doubles index size on disk.
+    pub index_store_content: bool,
+    /// Index dependency lockfiles (`package-lock.json`, `Cargo.lock`, ...; see
+    /// `LOCKFILE_NAMES`). Off by default since they bloat the index.
+    pub index_lockfiles: bool,
+    /// Full-text ranking weights for unscoped query terms, per field. Higher
+    /// boosts on the short `filename`/`symbols` fields make a file named
+    /// `auth.rs` or defining `fn auth` outrank files that merely mention
+    /// "auth" many times; set too high, they bury genuine content matches
+    /// under incidental name hits. `relative_path` is an untokenized field,
+    /// so its boost only applies when the query is an exact path.
+    pub boost_filename: f32,
+    pub boost_symbols: f32,
+    pub boost_relative_path: f32,
+    pub boost_content: f32,
+    /// Largest index bundle accepted by `POST /api/workspaces/import`.
+    pub max_import_bundle_bytes: u64,
+    /// Total size an imported bundle may unpack to; the upload limit alone
+    /// would let a small, highly compressed bundle fill the disk.
+    pub max_import_unpacked_bytes: u64,
+    /// Events buffered for WebSocket clients. A client that falls further
+    /// behind than this skips the missed events (logged as `Lagged`), so too
+    /// small a value makes progress UIs jump or stall during large indexes.
+    /// Values below 1 are treated as 1.
+    pub event_channel_capacity: usize,
+    /// Response compression algorithms; the client's `Accept-Encoding` picks
+    /// among them. Empty disables compression entirely (useful when a reverse
+    /// proxy already compresses, or to save CPU on loopback connections).
+    /// Set via `VYOTIQ_COMPRESSION` as a comma-separated list, or `none`.
+    pub compression_algorithms: Vec<CompressionAlgorithm>,
+    /// Compression quality passed to the chosen algorithm (gzip 0-9, zstd
+    /// 1-22). Unset uses each algorithm's default; higher values shrink large
+    /// search and read payloads further at the cost of CPU per response.
+    pub compression_level: Option<i32>,
     /// Additional glob patterns of files/directories to exclude from indexing.
     /// Forwarded from App settings via VYOTIQ_EXCLUDE_PATTERNS env var (comma-separated).
     pub exclude_patterns: Vec<String>,
@@ -139,56 +302,314 @@ pub struct AppConfig {
     /// Whether file watching is enabled. When false, no file watchers are started.
     /// Forwarded from App settings via VYOTIQ_ENABLE_FILE_WATCHER env var.
     pub enable_file_watcher: bool,
+    /// Remove a leftover data dir lock on startup when its owning process is gone.
+    /// Off by default so a live instance is never stolen from.
+    pub recover_stale_lock: bool,
+    /// Let several workspaces be active at once (e.g. a multi-pane editor).
+    /// Off by default: activating a workspace deactivates all others.
+    /// Set via VYOTIQ_MULTIPLE_ACTIVE_WORKSPACES.
+    pub multiple_active_workspaces: bool,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            listen_addr: "127.0.0.1:9721".to_string(),
+            max_index_size_mb: 512,
+            // MEMORY FIX: Reduced default from 10MB to 2MB for indexing.
+            // Files larger than 2MB are typically generated/minified and not useful for code search.
+            max_file_size_bytes: 2 * 1024 * 1024, // 2MB
+            watcher_debounce_ms: 500, // Increased from 300ms to 500ms for less CPU churn
+            watcher_batch_threshold: 50,
+            watcher_mode: WatcherMode::Auto,
+            watcher_poll_interval_ms: 2000,
+            watch_dirs: Vec::new(),
+            watch_skip_excluded_dirs: false,
+            index_batch_size: 50,
+            index_read_threads: 0,
+            index_commit_interval: 5_000,
+            max_loaded_indexes: 8,
+            data_dir: dirs::data_local_dir()
+                .map(|d| d.join("vyotiq-backend").to_string_lossy().to_string())
+                .unwrap_or_else(|| ".vyotiq-data".to_string()),
+            max_indexed_files: 50_000, // 50k files max per workspace
+            max_search_results: MAX_SEARCH_RESULTS,
+            index_store_content: false,
+            index_lockfiles: false,
+            boost_filename: 3.0,
+            boost_symbols: 2.0,
+            boost_relative_path: 1.0,
+            boost_content: 1.0,
+            max_import_bundle_bytes: 2 * 1024 * 1024 * 1024, // 2GB
+            max_import_unpacked_bytes: 8 * 1024 * 1024 * 1024, // 8GB
+            event_channel_capacity: 256,
+            compression_algorithms: vec![CompressionAlgorithm::Zstd, CompressionAlgorithm::Gzip],
+            compression_level: None,
+            exclude_patterns: Vec::new(),
+            include_patterns: Vec::new(),
+            enable_file_watcher: true,
+            recover_stale_lock: false,
+            multiple_active_workspaces: false,
+        }
+    }
 }
 
 impl AppConfig {
+    /// Build the effective configuration in three layers: built-in defaults,
+    /// then the optional TOML config file (`VYOTIQ_CONFIG`, or
+    /// `<config_dir>/vyotiq-backend/config.toml`), then `VYOTIQ_*` env vars.
+    /// Each layer overrides the previous one field by field.
     pub fn from_env() -> Self {
-        let port = std::env::var("VYOTIQ_PORT")
-            .unwrap_or_else(|_| "9721".to_string());
+        let mut config = Self::from_file().unwrap_or_default();
 
-        let data_dir = std::env::var("VYOTIQ_DATA_DIR").unwrap_or_else(|_| {
-            dirs::data_local_dir()
-                .map(|d| d.join("vyotiq-backend").to_string_lossy().to_string())
-                .unwrap_or_else(|| ".vyotiq-data".to_string())
-        });
+        if let Some(port) = env_string("VYOTIQ_PORT") {
+            config.listen_addr = format!("127.0.0.1:{}", port);
+        }
+        if let Some(v) = env_parse("VYOTIQ_MAX_INDEX_MB") {
+            config.max_index_size_mb = v;
+        }
+        if let Some(v) = env_parse("VYOTIQ_MAX_FILE_SIZE") {
+            config.max_file_size_bytes = v;
+        }
+        if let Some(v) = env_parse("VYOTIQ_WATCHER_DEBOUNCE_MS") {
+            config.watcher_debounce_ms = v;
+        }
+        if let Some(v) = env_parse("VYOTIQ_WATCHER_BATCH_THRESHOLD") {
+            config.watcher_batch_threshold = v;
+        }
+        if let Some(v) = env_parse("VYOTIQ_WATCHER_MODE") {
+            config.watcher_mode = v;
+        }
+        if let Some(v) = env_parse("VYOTIQ_WATCHER_POLL_INTERVAL_MS") {
+            config.watcher_poll_interval_ms = v;
+        }
+        if let Some(v) = env_list("VYOTIQ_WATCH_DIRS") {
+            config.watch_dirs = v;
+        }
+        if let Some(v) = env_bool("VYOTIQ_WATCH_SKIP_EXCLUDED_DIRS") {
+            config.watch_skip_excluded_dirs = v;
+        }
+        if let Some(v) = env_parse("VYOTIQ_INDEX_BATCH_SIZE") {
+            config.index_batch_size = v;
+        }
+        if let Some(v) = env_parse("VYOTIQ_INDEX_READ_THREADS") {
+            config.index_read_threads = v;
+        }
+        if let Some(v) = env_parse("VYOTIQ_INDEX_COMMIT_INTERVAL") {
+            config.index_commit_interval = v;
+        }
+        if let Some(v) = env_parse("VYOTIQ_MAX_LOADED_INDEXES") {
+            config.max_loaded_indexes = v;
+        }
+        if let Some(v) = env_string("VYOTIQ_DATA_DIR") {
+            config.data_dir = v;
+        }
+        if let Some(v) = env_parse("VYOTIQ_MAX_INDEXED_FILES") {
+            config.max_indexed_files = v;
+        }
+        if let Some(v) = env_parse("VYOTIQ_MAX_SEARCH_RESULTS") {
+            config.max_search_results = v;
+        }
+        if let Some(v) = env_bool("VYOTIQ_INDEX_STORE_CONTENT") {
+            config.index_store_content = v;
+        }
+        if let Some(v) = env_bool("VYOTIQ_INDEX_LOCKFILES") {
+            config.index_lockfiles = v;
+        }
+        if let Some(v) = env_parse("VYOTIQ_BOOST_FILENAME") {
+            config.boost_filename = v;
+        }
+        if let Some(v) = env_parse("VYOTIQ_BOOST_SYMBOLS") {
+            config.boost_symbols = v;
+        }
+        if let Some(v) = env_parse("VYOTIQ_BOOST_RELATIVE_PATH") {
+            config.boost_relative_path = v;
+        }
+        if let Some(v) = env_parse("VYOTIQ_BOOST_CONTENT") {
+            config.boost_content = v;
+        }
+        if let Some(v) = env_parse("VYOTIQ_MAX_IMPORT_BUNDLE_BYTES") {
+            config.max_import_bundle_bytes = v;
+        }
+        if let Some(v) = env_parse("VYOTIQ_MAX_IMPORT_UNPACKED_BYTES") {
+            config.max_import_unpacked_bytes = v;
+        }
+        if let Some(v) = env_parse("VYOTIQ_EVENT_CHANNEL_CAPACITY") {
+            config.event_channel_capacity = v;
+        }
+        if let Some(v) = env_list("VYOTIQ_COMPRESSION") {
+            config.compression_algorithms = v
+                .iter()
+                .filter(|s| !s.eq_ignore_ascii_case("none"))
+                .filter_map(|s| s.parse().ok())
+                .collect();
+        }
+        if let Some(v) = env_parse("VYOTIQ_COMPRESSION_LEVEL") {
+            config.compression_level = Some(v);
+        }
+        if let Some(v) = env_list("VYOTIQ_EXCLUDE_PATTERNS") {
+            config.exclude_patterns = v;
+        }
+        if let Some(v) = env_list("VYOTIQ_INCLUDE_PATTERNS") {
+            config.include_patterns = v;
+        }
+        if let Some(v) = env_bool("VYOTIQ_ENABLE_FILE_WATCHER") {
+            config.enable_file_watcher = v;
+        }
+        if let Some(v) = env_bool("VYOTIQ_RECOVER_STALE_LOCK") {
+            config.recover_stale_lock = v;
+        }
+        if let Some(v) = env_bool("VYOTIQ_MULTIPLE_ACTIVE_WORKSPACES") {
+            config.multiple_active_workspaces = v;
+        }
 
-        Self {
-            listen_addr: format!("127.0.0.1:{}", port),
-            max_index_size_mb: std::env::var("VYOTIQ_MAX_INDEX_MB")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(512),
-            // MEMORY FIX: Reduced default from 10MB to 2MB for indexing.
-            // Files larger than 2MB are typically generated/minified and not useful for code search.
-            max_file_size_bytes: std::env::var("VYOTIQ_MAX_FILE_SIZE")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(2 * 1024 * 1024), // 2MB
-            watcher_debounce_ms: std::env::var("VYOTIQ_WATCHER_DEBOUNCE_MS")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(500), // Increased from 300ms to 500ms for less CPU churn
-            index_batch_size: std::env::var("VYOTIQ_INDEX_BATCH_SIZE")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(50),
-            data_dir,
-            max_indexed_files: std::env::var("VYOTIQ_MAX_INDEXED_FILES")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(50_000), // 50k files max per workspace
-            exclude_patterns: std::env::var("VYOTIQ_EXCLUDE_PATTERNS")
-                .ok()
-                .map(|v| v.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
-                .unwrap_or_default(),
-            include_patterns: std::env::var("VYOTIQ_INCLUDE_PATTERNS")
-                .ok()
-                .map(|v| v.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
-                .unwrap_or_default(),
-            enable_file_watcher: std::env::var("VYOTIQ_ENABLE_FILE_WATCHER")
-                .ok()
-                .map(|v| v != "0" && v.to_lowercase() != "false")
-                .unwrap_or(true),
+        config
+    }
+
+    /// Load the TOML config file if one exists. Keys missing from the file keep
+    /// their defaults; unknown keys are reported with a warning so typos don't
+    /// silently do nothing. Returns `None` when there is no usable file.
+    fn from_file() -> Option<Self> {
+        let explicit = env_string("VYOTIQ_CONFIG").map(std::path::PathBuf::from);
+        let path = explicit.clone().or_else(default_config_path)?;
+        Self::load_file(&path, explicit.is_some())
+    }
+
+    /// Parse the config file at `path`. A missing or unreadable file is only
+    /// reported when `explicit` (the user pointed us at it).
+    fn load_file(path: &std::path::Path, explicit: bool) -> Option<Self> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) => {
+                // Only complain if the user explicitly pointed us at a file
+                if explicit {
+                    tracing::warn!("Failed to read config file {}: {}", path.display(), e);
+                }
+                return None;
+            }
+        };
+
+        let table: toml::Table = match content.parse() {
+            Ok(t) => t,
+            Err(e) => {
+                tracing::warn!("Invalid TOML in config file {}: {}", path.display(), e);
+                return None;
+            }
+        };
+
+        for key in unknown_keys(&table) {
+            tracing::warn!("Unknown key '{}' in config file {} (ignored)", key, path.display());
         }
+
+        match table.try_into::<Self>() {
+            Ok(config) => {
+                tracing::info!("Loaded config file {}", path.display());
+                Some(config)
+            }
+            Err(e) => {
+                tracing::warn!("Invalid value in config file {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+}
+
+/// Every key the config file may set, one per `AppConfig` field. Spelled out
+/// because serializing the defaults leaves out fields that are `None`.
+const CONFIG_KEYS: &[&str] = &[
+    "listen_addr",
+    "max_index_size_mb",
+    "max_file_size_bytes",
+    "watcher_debounce_ms",
+    "watcher_batch_threshold",
+    "watcher_mode",
+    "watcher_poll_interval_ms",
+    "watch_dirs",
+    "watch_skip_excluded_dirs",
+    "index_batch_size",
+    "index_read_threads",
+    "index_commit_interval",
+    "max_loaded_indexes",
+    "data_dir",
+    "max_indexed_files",
+    "max_search_results",
+    "index_store_content",
+    "index_lockfiles",
+    "boost_filename",
+    "boost_symbols",
+    "boost_relative_path",
+    "boost_content",
+    "max_import_bundle_bytes",
+    "max_import_unpacked_bytes",
+    "event_channel_capacity",
+    "compression_algorithms",
+    "compression_level",
+    "exclude_patterns",
+    "include_patterns",
+    "enable_file_watcher",
+    "recover_stale_lock",
+    "multiple_active_workspaces",
+];
+
+/// Keys of a config file table that `AppConfig` doesn't know.
+fn unknown_keys(table: &toml::Table) -> Vec<&str> {
+    table
+        .keys()
+        .map(String::as_str)
+        .filter(|k| !CONFIG_KEYS.contains(k))
+        .collect()
+}
+
+fn env_string(key: &str) -> Option<String> {
+    std::env::var(key).ok().filter(|v| !v.is_empty())
+}
+
+fn env_parse<T: std::str::FromStr>(key: &str) -> Option<T> {
+    std::env::var(key).ok().and_then(|v| v.parse().ok())
+}
+
+/// Comma-separated list; blank entries are dropped.
+fn env_list(key: &str) -> Option<Vec<String>> {
+    std::env::var(key)
+        .ok()
+        .map(|v| v.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+}
+
+/// Anything other than "0" or "false" (case-insensitive) counts as enabled.
+fn env_bool(key: &str) -> Option<bool> {
+    std::env::var(key)
+        .ok()
+        .map(|v| v != "0" && v.to_lowercase() != "false")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_keys_cover_every_field() {
+        // Set every `Option` field so it shows up in the serialized table
+        let config = AppConfig {
+            compression_level: Some(3),
+            ..AppConfig::default()
+        };
+        let table = toml::Table::try_from(config).unwrap();
+        let mut fields: Vec<&str> = table.keys().map(String::as_str).collect();
+        fields.sort_unstable();
+        let mut keys = CONFIG_KEYS.to_vec();
+        keys.sort_unstable();
+        assert_eq!(fields, keys);
+    }
+
+    #[test]
+    fn file_with_compression_level_loads_without_unknown_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "compression_level = 6\ncompresion_level = 6\n").unwrap();
+
+        let table: toml::Table = std::fs::read_to_string(&path).unwrap().parse().unwrap();
+        assert_eq!(unknown_keys(&table), ["compresion_level"]);
+        let config = AppConfig::load_file(&path, true).unwrap();
+        assert_eq!(config.compression_level, Some(6));
     }
 }
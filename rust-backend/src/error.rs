@@ -1,11 +1,36 @@
 use axum::{
-    http::StatusCode,
+    http::{header, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
 use serde_json::json;
 use thiserror::Error;
 
+/// One rejected field of a request body, reported by `AppError::Validation`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub reason: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, reason: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            reason: reason.into(),
+        }
+    }
+}
+
+/// `field: reason` pairs joined for the error message.
+fn describe_fields(errors: &[FieldError]) -> String {
+    errors
+        .iter()
+        .map(|e| format!("{}: {}", e.field, e.reason))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error("Workspace not found: {0}")]
@@ -23,6 +48,9 @@ pub enum AppError {
     #[error("Index error: {0}")]
     IndexError(String),
 
+    #[error("Index locked: {0}")]
+    IndexLocked(String),
+
     #[error("Search error: {0}")]
     SearchError(String),
 
@@ -37,8 +65,31 @@ pub enum AppError {
 
     #[error("Bad request: {0}")]
     BadRequest(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    /// Request fields that failed validation, all reported at once so forms
+    /// can mark every invalid input.
+    #[error("Validation failed: {}", describe_fields(.0))]
+    Validation(Vec<FieldError>),
+
+    /// A file or upload is over a size limit. Distinct from `BadRequest` so
+    /// clients can offer to stream or open the file another way.
+    #[error("File too large: {size} bytes exceeds the limit of {max} bytes")]
+    FileTooLarge { size: u64, max: u64 },
+
+    /// The workspace's first index pass hasn't finished; results would be partial.
+    #[error("Workspace {0} is still being indexed; retry shortly")]
+    IndexingInProgress(String),
 }
 
+/// `Retry-After` (seconds) sent with `IndexingInProgress` responses.
+const INDEXING_RETRY_AFTER_SECS: u64 = 5;
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let (status, message) = match &self {
@@ -47,6 +98,7 @@ impl IntoResponse for AppError {
             AppError::FileNotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
             AppError::PathNotAllowed(_) => (StatusCode::FORBIDDEN, self.to_string()),
             AppError::IndexError(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
+            AppError::IndexLocked(_) => (StatusCode::SERVICE_UNAVAILABLE, self.to_string()),
             AppError::SearchError(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
             AppError::Io(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
             AppError::Serde(_) => (StatusCode::BAD_REQUEST, self.to_string()),
@@ -55,13 +107,30 @@ impl IntoResponse for AppError {
                 "Internal server error".to_string(),
             ),
             AppError::BadRequest(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            AppError::Conflict(_) => (StatusCode::CONFLICT, self.to_string()),
+            AppError::Forbidden(_) => (StatusCode::FORBIDDEN, self.to_string()),
+            AppError::Validation(_) => (StatusCode::UNPROCESSABLE_ENTITY, self.to_string()),
+            AppError::FileTooLarge { .. } => (StatusCode::PAYLOAD_TOO_LARGE, self.to_string()),
+            AppError::IndexingInProgress(_) => (StatusCode::SERVICE_UNAVAILABLE, self.to_string()),
         };
 
-        let body = json!({
+        let mut body = json!({
             "error": message,
             "status": status.as_u16(),
         });
+        match &self {
+            AppError::FileTooLarge { size, max } => {
+                body["size"] = json!(size);
+                body["max"] = json!(max);
+            }
+            AppError::Validation(errors) => body["errors"] = json!(errors),
+            _ => {}
+        }
 
+        if let AppError::IndexingInProgress(_) = self {
+            let retry_after = [(header::RETRY_AFTER, INDEXING_RETRY_AFTER_SECS.to_string())];
+            return (status, retry_after, Json(body)).into_response();
+        }
         (status, Json(body)).into_response()
     }
 }
@@ -0,0 +1,413 @@
+//! Git metadata for workspace files, read from the local repository with
+//! `git2`. Everything here is best-effort: a workspace that is not inside a
+//! git work tree, or a repository that cannot be read, yields `None` rather
+//! than an error.
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use git2::{DiffOptions, ObjectType, Oid, Repository, Status, StatusOptions, TreeWalkMode, TreeWalkResult};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::debug;
+
+/// Git state of a file or directory, as shown in the explorer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GitFileStatus {
+    /// Tracked with no changes.
+    Tracked,
+    Untracked,
+    Ignored,
+    /// Changed in the index or the work tree (including newly staged and
+    /// conflicted files). Directories are modified when anything below is.
+    Modified,
+}
+
+/// Open the repository whose work tree contains `workspace_root`, together
+/// with the workspace's `/`-terminated path inside it ("" at the top level).
+pub fn open_repository(workspace_root: &Path) -> Option<(Repository, String)> {
+    let repo = match Repository::discover(workspace_root) {
+        Ok(repo) => repo,
+        Err(e) => {
+            debug!("No git repository for {}: {}", workspace_root.display(), e.message());
+            return None;
+        }
+    };
+    let workdir = dunce::canonicalize(repo.workdir()?).ok()?;
+    let root = dunce::canonicalize(workspace_root).ok()?;
+    let inner = root.strip_prefix(&workdir).ok()?.to_string_lossy().replace('\\', "/");
+    let prefix = if inner.is_empty() { inner } else { format!("{}/", inner) };
+    Some((repo, prefix))
+}
+
+/// Statuses of everything under one workspace directory, from a single
+/// `git status` over that directory.
+pub struct GitStatusMap {
+    /// Workspace root within the repository, `/`-terminated or empty.
+    prefix: String,
+    /// Repository-relative file paths with a non-clean status.
+    files: BTreeMap<String, GitFileStatus>,
+    /// Wholly untracked or ignored directories (`/`-terminated), which git
+    /// reports once instead of file by file.
+    dirs: Vec<(String, GitFileStatus)>,
+}
+
+impl GitStatusMap {
+    /// Status of `relative_dir` (workspace-relative, "" for the root) and
+    /// everything below it. None when the workspace is not in a git work tree.
+    pub fn load(workspace_root: &Path, relative_dir: &str) -> Option<Self> {
+        let (repo, prefix) = open_repository(workspace_root)?;
+        let dir = relative_dir.trim_matches('/');
+        let dir = if dir == "." { "" } else { dir };
+        let scope = format!("{}{}", prefix, dir);
+
+        let mut options = StatusOptions::new();
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(false)
+            .include_ignored(true)
+            .recurse_ignored_dirs(false)
+            .exclude_submodules(true)
+            .disable_pathspec_match(true);
+        if !scope.is_empty() {
+            options.pathspec(scope.trim_end_matches('/'));
+        }
+        let statuses = match repo.statuses(Some(&mut options)) {
+            Ok(statuses) => statuses,
+            Err(e) => {
+                debug!("git status failed for {}: {}", workspace_root.display(), e.message());
+                return None;
+            }
+        };
+
+        let mut files = BTreeMap::new();
+        let mut dirs = Vec::new();
+        for entry in statuses.iter() {
+            let Some(path) = entry.path() else { continue };
+            let status = classify(entry.status());
+            if path.ends_with('/') {
+                dirs.push((path.to_string(), status));
+            } else {
+                files.insert(path.to_string(), status);
+            }
+        }
+        Some(Self { prefix, files, dirs })
+    }
+
+    /// Status of a workspace-relative path inside the loaded directory.
+    pub fn status_of(&self, relative_path: &str, is_dir: bool) -> GitFileStatus {
+        let key = format!("{}{}", self.prefix, relative_path);
+        if let Some(status) = self.files.get(&key) {
+            return *status;
+        }
+        let dir_key = format!("{}/", key);
+        if let Some((_, status)) = self.dirs.iter().find(|(dir, _)| dir_key.starts_with(dir.as_str())) {
+            return *status;
+        }
+        let changed_below = is_dir
+            && self
+                .files
+                .range(dir_key.clone()..)
+                .take_while(|(path, _)| path.starts_with(&dir_key))
+                .any(|(_, status)| *status == GitFileStatus::Modified);
+        if changed_below {
+            GitFileStatus::Modified
+        } else {
+            GitFileStatus::Tracked
+        }
+    }
+}
+
+fn classify(status: Status) -> GitFileStatus {
+    if status.is_ignored() {
+        GitFileStatus::Ignored
+    } else if status == Status::WT_NEW {
+        GitFileStatus::Untracked
+    } else if status == Status::CURRENT {
+        GitFileStatus::Tracked
+    } else {
+        GitFileStatus::Modified
+    }
+}
+
+/// Commits examined when looking for the last change to files; files not
+/// touched within this much history fall back to their mtime.
+const MAX_HISTORY_COMMITS: usize = 20_000;
+
+/// Time (Unix seconds) of the last commit that changed each file under the
+/// workspace, keyed by workspace-relative path. Walks first-parent history
+/// from HEAD once, diffing each commit against its parent, until every file
+/// has been seen. Files that are modified in the work tree or untracked are
+/// left out, since their contents are newer than any commit.
+pub fn last_commit_times(workspace_root: &Path) -> Option<HashMap<String, u64>> {
+    let (repo, prefix) = open_repository(workspace_root)?;
+    let head = repo.head().ok()?.peel_to_commit().ok()?;
+
+    let mut pending: HashSet<String> = HashSet::new();
+    head.tree()
+        .ok()?
+        .walk(TreeWalkMode::PreOrder, |dir, entry| {
+            if entry.kind() == Some(ObjectType::Blob)
+                && let Some(name) = entry.name()
+            {
+                let path = format!("{}{}", dir, name);
+                if path.starts_with(&prefix) {
+                    pending.insert(path);
+                }
+            }
+            TreeWalkResult::Ok
+        })
+        .ok()?;
+
+    let mut revwalk = repo.revwalk().ok()?;
+    revwalk.push(head.id()).ok()?;
+    revwalk.simplify_first_parent().ok()?;
+    let mut diff_options = DiffOptions::new();
+    if !prefix.is_empty() {
+        diff_options.pathspec(prefix.trim_end_matches('/')).disable_pathspec_match(true);
+    }
+
+    let mut times = HashMap::new();
+    for id in revwalk.take(MAX_HISTORY_COMMITS) {
+        if pending.is_empty() {
+            break;
+        }
+        let Ok(commit) = id.and_then(|id| repo.find_commit(id)) else { break };
+        let tree = commit.tree().ok();
+        let parent_tree = commit.parent(0).ok().and_then(|parent| parent.tree().ok());
+        let Ok(diff) = repo.diff_tree_to_tree(parent_tree.as_ref(), tree.as_ref(), Some(&mut diff_options)) else {
+            continue;
+        };
+        let seconds = commit.time().seconds().max(0) as u64;
+        for delta in diff.deltas() {
+            let Some(path) = delta.new_file().path().and_then(|p| p.to_str()) else { continue };
+            if pending.remove(path) {
+                times.insert(path[prefix.len()..].to_string(), seconds);
+            }
+        }
+    }
+
+    for changed in changed_files(&repo, &prefix) {
+        times.remove(&changed);
+    }
+    Some(times)
+}
+
+/// Time (Unix seconds) of the last commit that changed one file, or None if
+/// the workspace is not a git work tree or the file differs from HEAD.
+pub fn last_commit_time(workspace_root: &Path, relative_path: &str) -> Option<u64> {
+    let (repo, prefix) = open_repository(workspace_root)?;
+    let repo_path = format!("{}{}", prefix, relative_path);
+    if repo.status_file(Path::new(&repo_path)).ok()? != Status::CURRENT {
+        return None;
+    }
+    let blob_in = |commit: &git2::Commit| {
+        commit
+            .tree()
+            .ok()
+            .and_then(|tree| tree.get_path(Path::new(&repo_path)).ok())
+            .map(|entry| entry.id())
+    };
+
+    let mut revwalk = repo.revwalk().ok()?;
+    revwalk.push_head().ok()?;
+    revwalk.simplify_first_parent().ok()?;
+    for id in revwalk.take(MAX_HISTORY_COMMITS) {
+        let commit = repo.find_commit(id.ok()?).ok()?;
+        let parent_blob = commit.parent(0).ok().and_then(|parent| blob_in(&parent));
+        if blob_in(&commit) != parent_blob {
+            return Some(commit.time().seconds().max(0) as u64);
+        }
+    }
+    None
+}
+
+/// Commit time (Unix seconds) of a revision such as a commit id, branch or
+/// tag. None when the workspace is not a git work tree or `rev` is unknown.
+pub fn revision_time(workspace_root: &Path, rev: &str) -> Option<u64> {
+    let (repo, _) = open_repository(workspace_root)?;
+    let commit = repo.revparse_single(rev).ok()?.peel_to_commit().ok()?;
+    Some(commit.time().seconds().max(0) as u64)
+}
+
+/// Workspace-relative paths of files under `prefix` that are modified,
+/// staged or untracked.
+fn changed_files(repo: &Repository, prefix: &str) -> Vec<String> {
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .exclude_submodules(true)
+        .disable_pathspec_match(true);
+    if !prefix.is_empty() {
+        options.pathspec(prefix.trim_end_matches('/'));
+    }
+    let Ok(statuses) = repo.statuses(Some(&mut options)) else {
+        return Vec::new();
+    };
+    statuses
+        .iter()
+        .filter_map(|entry| entry.path().and_then(|path| path.strip_prefix(prefix)).map(str::to_string))
+        .collect()
+}
+
+/// Last change to a run of lines, as reported by `git blame`. Lines that
+/// differ from HEAD (uncommitted edits) have no commit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlameHunk {
+    /// 1-based, inclusive line range in the current file.
+    pub start_line: usize,
+    pub end_line: usize,
+    pub commit: Option<String>,
+    pub author: Option<String>,
+    pub email: Option<String>,
+    pub date: Option<DateTime<Utc>>,
+    pub summary: Option<String>,
+}
+
+/// Blame of one file's working-tree contents.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileBlame {
+    /// False when the file is not in HEAD (untracked or newly added); there
+    /// are no hunks then.
+    pub tracked: bool,
+    pub hunks: Vec<BlameHunk>,
+}
+
+impl FileBlame {
+    /// Hunks overlapping `start..=end`, clipped to it.
+    pub fn lines(&self, start: usize, end: usize) -> Vec<BlameHunk> {
+        self.hunks
+            .iter()
+            .filter(|hunk| hunk.start_line <= end && hunk.end_line >= start)
+            .map(|hunk| BlameHunk {
+                start_line: hunk.start_line.max(start),
+                end_line: hunk.end_line.min(end),
+                ..hunk.clone()
+            })
+            .collect()
+    }
+
+    pub fn at(&self, line: usize) -> Option<&BlameHunk> {
+        self.hunks
+            .iter()
+            .find(|hunk| hunk.start_line <= line && line <= hunk.end_line)
+    }
+}
+
+/// Files whose blame is kept; the least recently computed is dropped first.
+const MAX_CACHED_BLAMES: usize = 256;
+
+/// Blame results per workspace file, reused while both HEAD and the file's
+/// contents (its git blob id) are unchanged.
+#[derive(Default)]
+pub struct BlameCache {
+    entries: DashMap<(String, String), CachedBlame>,
+}
+
+struct CachedBlame {
+    head: Option<Oid>,
+    blob: Oid,
+    computed_at: Instant,
+    blame: Arc<FileBlame>,
+}
+
+impl BlameCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Blame of `relative_path` in the workspace. None when the workspace is
+    /// not in a git work tree or the file can't be read.
+    pub fn blame(&self, workspace_id: &str, workspace_root: &Path, relative_path: &str) -> Option<Arc<FileBlame>> {
+        let (repo, prefix) = open_repository(workspace_root)?;
+        let content = std::fs::read(workspace_root.join(relative_path)).ok()?;
+        let blob = Oid::hash_object(ObjectType::Blob, &content).ok()?;
+        let head = repo.head().ok().and_then(|head| head.target());
+
+        let key = (workspace_id.to_string(), relative_path.to_string());
+        if let Some(cached) = self.entries.get(&key)
+            && cached.head == head
+            && cached.blob == blob
+        {
+            return Some(cached.blame.clone());
+        }
+
+        let blame = Arc::new(compute_blame(&repo, &format!("{}{}", prefix, relative_path), &content));
+        if self.entries.len() >= MAX_CACHED_BLAMES {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|entry| entry.computed_at)
+                .map(|entry| entry.key().clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(
+            key,
+            CachedBlame { head, blob, computed_at: Instant::now(), blame: blame.clone() },
+        );
+        Some(blame)
+    }
+}
+
+fn compute_blame(repo: &Repository, repo_path: &str, content: &[u8]) -> FileBlame {
+    let untracked = FileBlame { tracked: false, hunks: Vec::new() };
+    let committed = match repo.blame_file(Path::new(repo_path), None) {
+        Ok(blame) => blame,
+        Err(e) => {
+            debug!("git blame failed for {}: {}", repo_path, e.message());
+            return untracked;
+        }
+    };
+    // Re-attribute against the working-tree contents so line numbers match
+    // what the editor shows
+    let blame = match committed.blame_buffer(content) {
+        Ok(blame) => blame,
+        Err(e) => {
+            debug!("git blame of working copy failed for {}: {}", repo_path, e.message());
+            return untracked;
+        }
+    };
+
+    let mut summaries: HashMap<Oid, Option<String>> = HashMap::new();
+    let hunks = blame
+        .iter()
+        .filter(|hunk| hunk.lines_in_hunk() > 0)
+        .map(|hunk| {
+            let start_line = hunk.final_start_line();
+            let end_line = start_line + hunk.lines_in_hunk() - 1;
+            let id = hunk.final_commit_id();
+            if id.is_zero() {
+                return BlameHunk {
+                    start_line,
+                    end_line,
+                    commit: None,
+                    author: None,
+                    email: None,
+                    date: None,
+                    summary: None,
+                };
+            }
+            let signature = hunk.final_signature();
+            let summary = summaries
+                .entry(id)
+                .or_insert_with(|| repo.find_commit(id).ok().and_then(|c| c.summary().map(str::to_string)))
+                .clone();
+            BlameHunk {
+                start_line,
+                end_line,
+                commit: Some(id.to_string()),
+                author: signature.name().map(str::to_string),
+                email: signature.email().map(str::to_string),
+                date: DateTime::from_timestamp(signature.when().seconds(), 0),
+                summary,
+            }
+        })
+        .collect();
+    FileBlame { tracked: true, hunks }
+}
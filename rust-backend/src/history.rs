@@ -0,0 +1,150 @@
+use crate::error::{AppError, AppResult};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Arc;
+use parking_lot::Mutex;
+use tracing::warn;
+
+/// Maximum number of queries retained per workspace (oldest are evicted first).
+const MAX_HISTORY_ENTRIES: usize = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHistoryEntry {
+    pub query: String,
+    /// Which search endpoint produced the entry ("fulltext" or "grep").
+    pub kind: String,
+    pub result_count: usize,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Per-workspace ring buffer of recent searches, persisted under `<data_dir>/search_history`.
+/// Recording only touches the in-memory buffer; the JSON file is rewritten on a
+/// blocking worker so the search path never waits on disk I/O.
+pub struct SearchHistoryManager {
+    histories: Arc<DashMap<String, VecDeque<SearchHistoryEntry>>>,
+    base_dir: PathBuf,
+    /// Serializes background writes so an older snapshot never overwrites a newer one.
+    write_lock: Arc<Mutex<()>>,
+}
+
+impl SearchHistoryManager {
+    pub fn new(base_dir: PathBuf) -> Self {
+        Self {
+            histories: Arc::new(DashMap::new()),
+            base_dir,
+            write_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    fn history_file(&self, workspace_id: &str) -> PathBuf {
+        self.base_dir.join(format!("{}.json", workspace_id))
+    }
+
+    /// Load a workspace's history into memory on first access. The file is
+    /// read on a blocking worker, like the writes.
+    async fn ensure_loaded(&self, workspace_id: &str) {
+        if self.histories.contains_key(workspace_id) {
+            return;
+        }
+        let path = self.history_file(workspace_id);
+        let entries = tokio::task::spawn_blocking(move || {
+            std::fs::read_to_string(path)
+                .ok()
+                .and_then(|json| serde_json::from_str::<VecDeque<SearchHistoryEntry>>(&json).ok())
+        })
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+        self.histories
+            .entry(workspace_id.to_string())
+            .or_insert(entries);
+    }
+
+    /// Recent searches for a workspace, newest first.
+    pub async fn list(&self, workspace_id: &str) -> Vec<SearchHistoryEntry> {
+        self.ensure_loaded(workspace_id).await;
+        self.histories
+            .get(workspace_id)
+            .map(|h| h.iter().rev().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Append a query to the workspace's history and persist it in the background.
+    /// The first call for a workspace reads its file, so request handlers
+    /// spawn this rather than awaiting it.
+    pub async fn record(&self, workspace_id: &str, query: &str, kind: &str, result_count: usize) {
+        self.ensure_loaded(workspace_id).await;
+        {
+            let mut history = self.histories.entry(workspace_id.to_string()).or_default();
+            history.push_back(SearchHistoryEntry {
+                query: query.to_string(),
+                kind: kind.to_string(),
+                result_count,
+                timestamp: Utc::now(),
+            });
+            while history.len() > MAX_HISTORY_ENTRIES {
+                history.pop_front();
+            }
+        }
+        self.persist_in_background(workspace_id);
+    }
+
+    /// Clear a workspace's history both in memory and on disk. The file is
+    /// removed on a blocking worker, under the same lock as the writes.
+    pub async fn clear(&self, workspace_id: &str) -> AppResult<()> {
+        let histories = self.histories.clone();
+        let write_lock = self.write_lock.clone();
+        let ws_id = workspace_id.to_string();
+        let path = self.history_file(workspace_id);
+        tokio::task::spawn_blocking(move || -> AppResult<()> {
+            let _write = write_lock.lock();
+            histories.remove(&ws_id);
+            if path.exists() {
+                std::fs::remove_file(&path)?;
+            }
+            Ok(())
+        })
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Clear history task failed: {}", e)))?
+    }
+
+    fn persist_in_background(&self, workspace_id: &str) {
+        let histories = self.histories.clone();
+        let write_lock = self.write_lock.clone();
+        let ws_id = workspace_id.to_string();
+        let base_dir = self.base_dir.clone();
+        let target = self.history_file(workspace_id);
+        let write = move || -> std::io::Result<()> {
+            let _write = write_lock.lock();
+            // Snapshot under the write lock so the latest state always wins
+            let entries = match histories.get(&ws_id) {
+                Some(h) => h.clone(),
+                None => return Ok(()), // Cleared before we got here
+            };
+            let json = serde_json::to_string(&entries)?;
+            std::fs::create_dir_all(&base_dir)?;
+            // Atomic write: write to temp file then rename to prevent corruption on crash
+            let tmp = target.with_extension("json.tmp");
+            std::fs::write(&tmp, json)?;
+            std::fs::rename(&tmp, &target)
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn_blocking(move || {
+                    if let Err(e) = write() {
+                        warn!("Failed to persist search history: {}", e);
+                    }
+                });
+            }
+            Err(_) => {
+                if let Err(e) = write() {
+                    warn!("Failed to persist search history: {}", e);
+                }
+            }
+        }
+    }
+}
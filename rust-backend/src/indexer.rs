@@ -1,10 +1,12 @@
+use crate::config::AppConfig;
 use crate::error::{AppError, AppResult};
 use crate::state::ServerEvent;
+use crate::tokenizer;
 use dashmap::DashMap;
 use ignore::WalkBuilder;
 use rayon::prelude::*;
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
@@ -13,9 +15,352 @@ use tantivy::{Index, IndexReader, IndexWriter, TantivyDocument};
 use tokio::sync::broadcast;
 use tracing::{info, warn};
 
+/// Version of the Tantivy schema produced by `IndexSchema::build`.
+/// Bump this whenever fields or their options change so existing on-disk
+/// indexes are rebuilt instead of being opened with an incompatible layout.
+pub const SCHEMA_VERSION: u32 = 8;
+
+/// Memory arena for single-file IndexWriters (watcher reindex/move). This is
+/// Tantivy's per-thread minimum; smaller budgets fail writer creation.
+const INCREMENTAL_WRITER_BUDGET: usize = 15_000_000;
+
+/// Minimum gap between `IndexingProgress` events of one pass. Progress is
+/// checked every `batch_size` files, but fast passes would still flood the
+/// broadcast channel and the renderer; the final 100% event is always sent.
+const PROGRESS_EVENT_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Sidecar file (inside each index directory) holding the schema version.
+const SCHEMA_VERSION_FILE: &str = "schema_version";
+
+/// Key identifying a file's document and its sidecar entries: the path
+/// relative to the workspace root with `/` separators, lowercased on
+/// case-insensitive filesystems (macOS, Windows) so an event reporting
+/// `foo.rs` finds the document indexed as `Foo.rs`. The stored
+/// `relative_path` field keeps the original spelling. Being relative, keys
+/// stay valid when the workspace root moves.
+pub fn path_key(relative_path: &str) -> String {
+    let key = relative_path.replace('\\', "/");
+    if cfg!(any(target_os = "macos", windows)) {
+        key.to_lowercase()
+    } else {
+        key
+    }
+}
+
+/// `path_key` of an absolute `file_path` inside `workspace_path`.
+fn file_key(file_path: &Path, workspace_path: &Path) -> String {
+    path_key(&relative_path_of(file_path, workspace_path))
+}
+
+/// Key prefix shared by everything under the directory whose `path_key` is
+/// `dir_key`.
+fn descendant_prefix(dir_key: &str) -> String {
+    format!("{}/", dir_key.trim_end_matches('/'))
+}
+
+/// Upper bound on the default `index_read_threads`.
+const DEFAULT_MAX_READ_THREADS: usize = 8;
+
+/// Thread pool for the file read stages of indexing; `threads` of 0 means
+/// one per CPU, capped at `DEFAULT_MAX_READ_THREADS`.
+fn build_read_pool(threads: usize) -> rayon::ThreadPool {
+    let threads = if threads == 0 {
+        std::thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(DEFAULT_MAX_READ_THREADS)
+    } else {
+        threads
+    };
+    info!("Indexing file reads use {} thread(s)", threads);
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .thread_name(|i| format!("index-read-{}", i))
+        .build()
+        .expect("failed to start indexing thread pool")
+}
+
+/// `/`-separated path of `file_path` relative to the workspace root, as
+/// stored in the `relative_path` field.
+fn relative_path_of(file_path: &Path, workspace_path: &Path) -> String {
+    file_path
+        .strip_prefix(workspace_path)
+        .unwrap_or(file_path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Modification time in seconds since the Unix epoch (0 if unavailable).
+fn modified_secs(metadata: &std::fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Directory walk used to find indexable files: honours .gitignore, global
+/// gitignore and .git/info/exclude, includes dotfiles, and stops at depth 20.
+/// Entries are visited in file name order, so when `max_indexed_files` cuts a
+/// walk short the same files are kept on every pass.
+fn index_walker(root: &Path) -> WalkBuilder {
+    let mut builder = WalkBuilder::new(root);
+    builder
+        .hidden(false)
+        .git_ignore(true)
+        .git_global(true)
+        .git_exclude(true)
+        .max_depth(Some(20))
+        .sort_by_file_name(|a, b| a.cmp(b));
+    builder
+}
+
+/// Hex SHA-256 of a file's bytes, streamed so large files aren't held in memory.
+/// Matches the `content_hash` stored for indexed (UTF-8) files.
+pub fn hash_file(path: &Path) -> std::io::Result<String> {
+    use std::io::Read;
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Size and modification time of an indexed file, as stored in its document;
+/// the values of `IndexManager::path_index`.
+#[derive(Debug, Clone, Copy)]
+pub struct IndexedPathInfo {
+    pub size: u64,
+    /// Seconds since the Unix epoch.
+    pub modified: u64,
+}
+
+/// Relative paths of a workspace's indexed files (`/`-separated), sorted.
+pub type PathIndex = std::collections::BTreeMap<String, IndexedPathInfo>;
+
+/// Why a file is or isn't in the full-text index (`/index/explain`). Each
+/// flag mirrors one filter of `index_workspace`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct IndexExplanation {
+    pub path: String,
+    pub exists: bool,
+    /// A document for the file is in the index right now.
+    pub indexed: bool,
+    /// Every check passes, so an indexing pass would include the file.
+    pub indexable: bool,
+    /// Skipped by .gitignore, .ignore or git exclude rules, or too deep.
+    pub ignored: bool,
+    /// Inside a build/output directory or one matching user exclude patterns.
+    pub excluded_dir: bool,
+    pub size_bytes: Option<u64>,
+    pub max_file_size_bytes: usize,
+    pub over_size_limit: bool,
+    /// A dependency lockfile skipped because `index_lockfiles` is off.
+    pub lockfile: bool,
+    /// Extension, file name or content is recognised as indexable.
+    pub supported_type: bool,
+    pub language: Option<String>,
+    /// Content is valid UTF-8; None when not checked (missing or too large).
+    pub utf8: Option<bool>,
+    /// Why the file is not indexable (or not indexed yet); empty when it is.
+    pub reasons: Vec<String>,
+}
+
+/// Indexing errors kept per workspace for `/index/errors`; older ones are dropped.
+const MAX_INDEX_ERRORS: usize = 10;
+
+/// A failed indexing pass, kept so clients that missed the `index_error`
+/// event can still see it.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct IndexingErrorRecord {
+    pub error: String,
+    pub occurred_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Upper bound on `SkippedFiles::sample`.
+const SKIPPED_FILES_SAMPLE: usize = 20;
+
+/// An indexable file that a pass could not read (permissions, invalid UTF-8,
+/// removed mid-pass).
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SkippedFile {
+    /// Path relative to the workspace root.
+    pub path: String,
+    pub reason: String,
+}
+
+/// Files the last indexing pass left out because they could not be read.
+/// The pass itself still completes; they are retried on the next one.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct SkippedFiles {
+    pub count: usize,
+    /// The first `SKIPPED_FILES_SAMPLE` of them.
+    pub sample: Vec<SkippedFile>,
+}
+
+impl SkippedFiles {
+    fn record(&mut self, file: SkippedFile) {
+        self.count += 1;
+        if self.sample.len() < SKIPPED_FILES_SAMPLE {
+            self.sample.push(file);
+        }
+    }
+}
+
+/// Change to a workspace's file count and total size from one incremental
+/// update. Computed from the document that was replaced, so re-indexing an
+/// already indexed file only contributes its size difference.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatsDelta {
+    pub files: i64,
+    pub bytes: i64,
+}
+
+impl StatsDelta {
+    /// Delta from replacing a document of size `before` (None = not indexed)
+    /// with one of size `after` (None = no longer indexed).
+    fn replacing(before: Option<u64>, after: Option<u64>) -> Self {
+        Self {
+            files: after.is_some() as i64 - before.is_some() as i64,
+            bytes: after.unwrap_or(0) as i64 - before.unwrap_or(0) as i64,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.files == 0 && self.bytes == 0
+    }
+}
+
+impl std::ops::Add for StatsDelta {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            files: self.files + other.files,
+            bytes: self.bytes + other.bytes,
+        }
+    }
+}
+
+/// Stored fields that `export_ndjson` can emit, in default output order.
+pub const EXPORT_FIELDS: &[&str] = &[
+    "path",
+    "relative_path",
+    "filename",
+    "extension",
+    "language",
+    "symbols",
+    "size",
+    "modified",
+    "changed",
+    "content_hash",
+];
+
+/// Layout version of the `.tar.gz` bundles written by `export_bundle`.
+const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// Bundle entry holding the `BundleManifest`; index files live under `index/`.
+const BUNDLE_MANIFEST: &str = "manifest.json";
+const BUNDLE_INDEX_DIR: &str = "index";
+
+/// Most entries an imported bundle may hold. An index has a handful of files
+/// per segment, so real bundles stay far below this.
+const MAX_BUNDLE_ENTRIES: usize = 10_000;
+
+/// Largest manifest read from an imported bundle.
+const MAX_BUNDLE_MANIFEST_BYTES: u64 = 1024 * 1024;
+
+/// Metadata at the head of an index bundle, checked before an import is accepted.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct BundleManifest {
+    pub format_version: u32,
+    pub schema_version: u32,
+    pub store_content: bool,
+    /// Whether content was tokenized with accent folding
+    /// (`Workspace::fold_accents`).
+    #[serde(default)]
+    pub fold_accents: bool,
+    pub backend_version: String,
+    pub workspace_name: String,
+    /// Workspace root the index was built against. Informational only:
+    /// documents and sidecars are keyed by relative path, so the bundle can be
+    /// installed under any root.
+    pub source_root: String,
+    pub document_count: u64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Tech-debt comment kinds collected by `extract_markers`.
+pub const MARKER_KINDS: &[&str] = &["TODO", "FIXME", "HACK", "XXX"];
+
+/// Per-file cap on stored markers, so a generated file full of TODOs
+/// cannot bloat the doc store.
+const MAX_MARKERS_PER_FILE: usize = 500;
+
+/// Marker text is cut to this many characters.
+const MAX_MARKER_TEXT_CHARS: usize = 200;
+
+/// A TODO/FIXME/HACK/XXX comment found while indexing a file.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CodeMarker {
+    pub kind: String,
+    /// 1-based line number
+    pub line: u64,
+    pub text: String,
+}
+
+impl CodeMarker {
+    /// Stored form in the `markers` field: `KIND\tLINE\tTEXT`.
+    fn encode(&self) -> String {
+        format!("{}\t{}\t{}", self.kind, self.line, self.text)
+    }
+
+    pub fn decode(stored: &str) -> Option<Self> {
+        let mut parts = stored.splitn(3, '\t');
+        Some(Self {
+            kind: parts.next()?.to_string(),
+            line: parts.next()?.parse().ok()?,
+            text: parts.next().unwrap_or_default().to_string(),
+        })
+    }
+}
+
+/// Line totals of one indexed file.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FileLineCount {
+    pub language: String,
+    pub lines: u64,
+    /// Lines containing only whitespace
+    pub blank_lines: u64,
+}
+
+impl FileLineCount {
+    fn new(content: &str, language: &str) -> Self {
+        let (lines, blank_lines) = content.lines().fold((0, 0), |(lines, blank), line| {
+            (lines + 1, blank + u64::from(line.trim().is_empty()))
+        });
+        Self {
+            language: language.to_string(),
+            lines,
+            blank_lines,
+        }
+    }
+}
+
+fn language_of(path: &Path, content: &str) -> String {
+    crate::lang::detect_file_language(path, content).to_string()
+}
+
 #[derive(Debug, Clone)]
 pub struct IndexSchema {
-    pub path: Field,
+    /// `path_key` of the file; the term every delete and lookup goes through.
+    pub path_key: Field,
     pub relative_path: Field,
     pub filename: Field,
     pub extension: Field,
@@ -23,34 +368,70 @@ pub struct IndexSchema {
     pub language: Field,
     pub size: Field,
     pub modified: Field,
+    /// Last change to the file (Unix seconds): its last git commit, or its
+    /// mtime when uncommitted or outside a git work tree. Backs the
+    /// `modified_after`/`modified_before` search filters.
+    pub changed: Field,
     pub content_hash: Field,
     pub symbols: Field,
+    pub markers: Field,
+    pub marker_kinds: Field,
     schema: Schema,
 }
 
 impl IndexSchema {
-    pub fn build() -> Self {
+    /// `store_content` adds STORED to the content field (see
+    /// `AppConfig::index_store_content`); `fold_accents` tokenizes content
+    /// with accent folding (`Workspace::fold_accents`). Toggling either
+    /// changes the schema, so existing indexes are rebuilt on next open.
+    pub fn build(store_content: bool, fold_accents: bool) -> Self {
         let mut builder = Schema::builder();
 
-        let path = builder.add_text_field("path", STRING | STORED);
+        let path_key = builder.add_text_field("path_key", STRING);
         let relative_path = builder.add_text_field("relative_path", STRING | STORED);
         let filename = builder.add_text_field("filename", TEXT | STORED);
-        let extension = builder.add_text_field("extension", STRING | STORED);
-        // MEMORY FIX: TEXT only (indexed for search) — do NOT store full file content in Tantivy.
-        // The content lives on disk; snippets are generated by reading the file at search time.
-        // This alone saves gigabytes of RAM for large workspaces.
-        let content = builder.add_text_field("content", TEXT);
-        let language = builder.add_text_field("language", STRING | STORED);
+        // FAST columns on extension/language back the facet aggregation in search.
+        let extension = builder.add_text_field("extension", STRING | STORED | FAST);
+        // MEMORY FIX: TEXT only (indexed for search) — do NOT store full file content in Tantivy
+        // unless explicitly enabled. The content lives on disk; snippets are generated by reading
+        // the file at search time. This alone saves gigabytes of RAM for large workspaces.
+        // Content and symbols go through the code tokenizer so identifier parts
+        // (`getUserName` -> get, user, name) are searchable on their own.
+        let code_text = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer(tokenizer::CODE_TOKENIZER)
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+        );
+        // The query parser tokenizes each field's terms with that field's
+        // tokenizer, so queries are folded the same way as the content.
+        let content_text = if fold_accents {
+            TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer(tokenizer::FOLDED_CODE_TOKENIZER)
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+            )
+        } else {
+            code_text.clone()
+        };
+        let content_options = if store_content { content_text | STORED } else { content_text };
+        let content = builder.add_text_field("content", content_options);
+        let language = builder.add_text_field("language", STRING | STORED | FAST);
         let size = builder.add_u64_field("size", INDEXED | STORED);
         let modified = builder.add_u64_field("modified", INDEXED | STORED);
+        let changed = builder.add_u64_field("changed", INDEXED | STORED | FAST);
         let content_hash = builder.add_text_field("content_hash", STRING | STORED);
-        // MEMORY FIX: TEXT only — symbols are indexed for search but not stored.
-        let symbols = builder.add_text_field("symbols", TEXT);
+        // Stored (unlike content) so the NDJSON export can emit them; symbol lists are small.
+        let symbols = builder.add_text_field("symbols", code_text | STORED);
+        // TODO/FIXME comments, one stored `CodeMarker` per value; `marker_kinds`
+        // indexes each file's distinct kinds so the markers route only loads
+        // files that have some.
+        let markers = builder.add_text_field("markers", STORED);
+        let marker_kinds = builder.add_text_field("marker_kinds", STRING);
 
         let schema = builder.build();
 
         Self {
-            path,
+            path_key,
             relative_path,
             filename,
             extension,
@@ -58,8 +439,11 @@ impl IndexSchema {
             language,
             size,
             modified,
+            changed,
             content_hash,
             symbols,
+            markers,
+            marker_kinds,
             schema,
         }
     }
@@ -69,6 +453,72 @@ impl IndexSchema {
     }
 }
 
+/// Outcome of the walk + hash-compare phase of an indexing pass.
+struct IndexPlan {
+    /// Bytes across every file found.
+    total_size: u64,
+    /// Content hashes recorded by the last pass, keyed by `path_key`.
+    existing_hashes: HashMap<String, String>,
+    /// Every indexable file found, with its current content hash.
+    new_file_hashes: Vec<(PathBuf, String)>,
+    /// New files and files whose hash changed.
+    files_to_index: Vec<PathBuf>,
+    unchanged_count: usize,
+    /// `path_key`s indexed last time that no longer exist.
+    paths_to_remove: Vec<String>,
+    /// The walk stopped at `max_indexed_files`; more indexable files exist.
+    truncated: bool,
+    /// Files that could not be read to hash them.
+    skipped: SkippedFiles,
+}
+
+/// Dry-run counterpart of an indexing pass, returned by `index_plan`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct IndexPlanSummary {
+    /// Indexable files found by the walk.
+    pub total_files: usize,
+    /// New or changed files the pass would (re)index.
+    pub files_to_index: usize,
+    pub files_unchanged: usize,
+    /// Previously indexed files that no longer exist.
+    pub files_to_remove: usize,
+    pub total_size_bytes: u64,
+    pub bytes_to_index: u64,
+    /// The walk stopped at `max_indexed_files`; more files exist.
+    pub max_indexed_files_reached: bool,
+}
+
+/// Paths listed per kind in a `HashSidecarCheck`; the counts are always exact.
+const SIDECAR_CHECK_SAMPLE: usize = 20;
+
+/// Comparison of the documents in a workspace's index with its
+/// `content_hashes.json` sidecar, returned by `check_content_hashes`.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct HashSidecarCheck {
+    pub indexed_documents: usize,
+    pub sidecar_entries: usize,
+    /// Indexed files with no sidecar entry; the next pass would treat them as
+    /// new and add a second document.
+    pub missing_from_sidecar: usize,
+    /// Sidecar entries with no document; the next pass would skip them as
+    /// unchanged and leave them unsearchable.
+    pub missing_from_index: usize,
+    /// Files whose sidecar hash differs from the hash stored in the index.
+    pub hash_mismatches: usize,
+    /// Up to `SIDECAR_CHECK_SAMPLE` `path_key`s of each kind above.
+    pub sample_missing_from_sidecar: Vec<String>,
+    pub sample_missing_from_index: Vec<String>,
+    pub sample_hash_mismatches: Vec<String>,
+    /// The sidecar was rebuilt from the index.
+    pub repaired: bool,
+}
+
+impl HashSidecarCheck {
+    pub fn is_consistent(&self) -> bool {
+        self.missing_from_sidecar == 0 && self.missing_from_index == 0 && self.hash_mismatches == 0
+    }
+}
+
 /// RAII guard that resets `is_indexing` when dropped (including on panic).
 /// Prevents the indexing flag from getting stuck if the indexing task panics.
 struct IndexingGuard {
@@ -90,6 +540,14 @@ pub struct IndexState {
     pub indexed_count: AtomicUsize,
     pub total_count: AtomicUsize,
     pub total_size_bytes: std::sync::atomic::AtomicU64,
+    /// Wall time of the last completed `index_workspace` pass (0 = none yet).
+    pub last_index_duration_ms: std::sync::atomic::AtomicU64,
+    /// The last pass hit `max_indexed_files` and left files out.
+    pub truncated: AtomicBool,
+    /// Files the last pass could not read.
+    pub skipped: parking_lot::Mutex<SkippedFiles>,
+    /// `IndexManager::access_clock` value at the last access, for LRU unloading.
+    last_used: std::sync::atomic::AtomicU64,
 }
 
 pub struct IndexManager {
@@ -97,8 +555,21 @@ pub struct IndexManager {
     base_dir: PathBuf,
     max_file_size: usize,
     batch_size: usize,
+    /// Runs the parallel read/hash/parse stages of indexing, bounded by
+    /// `AppConfig::index_read_threads` instead of rayon's global pool.
+    read_pool: rayon::ThreadPool,
     /// Maximum number of files to index per workspace (memory cap)
     max_indexed_files: usize,
+    /// Whether the content field is STORED (`AppConfig::index_store_content`).
+    store_content: bool,
+    /// Files between intermediate commits of a full pass (0 = commit once at
+    /// the end); see `AppConfig::index_commit_interval`.
+    commit_interval: usize,
+    /// Loaded indexes kept in memory before idle ones are unloaded
+    /// (0 = never unload); see `AppConfig::max_loaded_indexes`.
+    max_loaded_indexes: usize,
+    /// Monotonic counter stamped into `IndexState::last_used` on each access.
+    access_clock: std::sync::atomic::AtomicU64,
     /// Serializes all Tantivy IndexWriter operations.
     /// Tantivy only allows one writer at a time per index; concurrent
     /// `reindex_file` calls from the file watcher would otherwise contend.
@@ -106,68 +577,143 @@ pub struct IndexManager {
     /// Per-workspace content hashes stored as a sidecar file.
     /// Much faster than scanning the entire Tantivy index via AllQuery.
     content_hashes: DashMap<String, HashMap<String, String>>,
+    /// Per-workspace line counts by `path_key`, persisted as a sidecar
+    /// (`line_counts.json`). Kept per file so per-language totals can be
+    /// updated incrementally when single files change.
+    line_counts: DashMap<String, HashMap<String, FileLineCount>>,
     /// Tracks whether a workspace has completed at least one full indexing pass.
     /// Prevents false `indexed: true` for workspaces that only loaded an index from disk
     /// but haven't verified its completeness.
     indexed_workspaces: DashMap<String, bool>,
     /// User-provided exclude patterns forwarded from app settings.
     user_exclude_patterns: Vec<String>,
+    /// Whether dependency lockfiles are indexed (`AppConfig::index_lockfiles`).
+    index_lockfiles: bool,
+    /// Per-workspace file size limits replacing `max_file_size`
+    /// (`Workspace::max_file_size_bytes`).
+    max_file_size_overrides: DashMap<String, usize>,
+    /// Workspaces whose content is indexed with accent folding
+    /// (`Workspace::fold_accents`).
+    fold_accents: DashMap<String, bool>,
+    /// Recent indexing failures per workspace, oldest first. In memory only;
+    /// cleared when a pass completes.
+    index_errors: DashMap<String, VecDeque<IndexingErrorRecord>>,
+    /// Per-workspace in-memory list of indexed files, so file listing and
+    /// quick open never walk the filesystem. Rebuilt from stored documents
+    /// after a full pass or on first use, kept current by `reindex_file` and
+    /// `move_file`.
+    path_indexes: DashMap<String, Arc<PathIndex>>,
 }
 
 impl IndexManager {
-    pub fn new(
-        base_dir: PathBuf,
-        max_file_size: usize,
-        batch_size: usize,
-        max_indexed_files: usize,
-        user_exclude_patterns: Vec<String>,
-    ) -> Self {
+    /// Indexes live under `base_dir`; the limits and patterns come from `config`.
+    pub fn new(base_dir: PathBuf, config: &AppConfig) -> Self {
         Self {
             indexes: DashMap::new(),
             base_dir,
-            max_file_size,
-            batch_size,
-            max_indexed_files,
+            max_file_size: config.max_file_size_bytes,
+            batch_size: config.index_batch_size,
+            read_pool: build_read_pool(config.index_read_threads),
+            max_indexed_files: config.max_indexed_files,
+            store_content: config.index_store_content,
+            commit_interval: config.index_commit_interval,
+            max_loaded_indexes: config.max_loaded_indexes,
+            access_clock: std::sync::atomic::AtomicU64::new(0),
             writer_lock: tokio::sync::Mutex::new(()),
             content_hashes: DashMap::new(),
+            line_counts: DashMap::new(),
             indexed_workspaces: DashMap::new(),
-            user_exclude_patterns,
+            user_exclude_patterns: config.exclude_patterns.clone(),
+            index_lockfiles: config.index_lockfiles,
+            max_file_size_overrides: DashMap::new(),
+            fold_accents: DashMap::new(),
+            index_errors: DashMap::new(),
+            path_indexes: DashMap::new(),
         }
     }
 
+    /// Set or clear (None) a workspace's file size limit. Takes effect on the
+    /// next indexing pass or file change.
+    pub fn set_max_file_size_override(&self, workspace_id: &str, max_file_size: Option<usize>) {
+        match max_file_size {
+            Some(limit) => {
+                self.max_file_size_overrides.insert(workspace_id.to_string(), limit);
+            }
+            None => {
+                self.max_file_size_overrides.remove(workspace_id);
+            }
+        }
+    }
+
+    /// Turn accent folding on or off for a workspace. Changing it changes the
+    /// schema, so a loaded index is dropped here and rebuilt empty on its next
+    /// open; the caller is expected to start a full indexing pass.
+    pub fn set_fold_accents(&self, workspace_id: &str, enabled: bool) {
+        let previous = if enabled {
+            self.fold_accents.insert(workspace_id.to_string(), true)
+        } else {
+            self.fold_accents.remove(workspace_id).map(|(_, v)| v)
+        };
+        if previous.unwrap_or(false) != enabled {
+            self.indexes.remove(workspace_id);
+            self.path_indexes.remove(workspace_id);
+        }
+    }
+
+    fn fold_accents_for(&self, workspace_id: &str) -> bool {
+        self.fold_accents.contains_key(workspace_id)
+    }
+
+    fn max_file_size_for(&self, workspace_id: &str) -> usize {
+        self.max_file_size_overrides
+            .get(workspace_id)
+            .map_or(self.max_file_size, |limit| *limit)
+    }
+
     fn index_dir(&self, workspace_id: &str) -> PathBuf {
         self.base_dir.join(workspace_id)
     }
 
     pub fn get_or_create_index(&self, workspace_id: &str) -> AppResult<Arc<IndexState>> {
         if let Some(state) = self.indexes.get(workspace_id) {
+            self.touch(state.value());
             return Ok(state.value().clone());
         }
 
-        let schema_def = IndexSchema::build();
+        let schema_def = IndexSchema::build(self.store_content, self.fold_accents_for(workspace_id));
         let index_path = self.index_dir(workspace_id);
         std::fs::create_dir_all(&index_path)?;
 
         let index = if index_path.join("meta.json").exists() {
-            // Try to open existing index. If schema is incompatible (e.g., after
-            // removing STORED from fields), delete the old index and recreate.
-            match Index::open_in_dir(&index_path) {
+            // Try to open existing index. If it was written by a different schema
+            // version or is otherwise incompatible (e.g., after removing STORED from
+            // fields), delete the old index and recreate so the next pass rebuilds it.
+            match Self::open_existing_index(&index_path, &schema_def)? {
                 Ok(idx) => idx,
-                Err(e) => {
-                    warn!("Existing index incompatible or corrupted, recreating: {}", e);
+                Err(reason) => {
+                    warn!("Existing index for {} incompatible or corrupted, recreating: {}", workspace_id, reason);
                     // Remove the old index directory entirely
                     let _ = std::fs::remove_dir_all(&index_path);
                     std::fs::create_dir_all(&index_path)?;
                     // Also clear stale content hashes so a full re-index is triggered
                     self.content_hashes.remove(workspace_id);
-                    Index::create_in_dir(&index_path, schema_def.schema().clone())
-                        .map_err(|e2| AppError::IndexError(format!("Failed to recreate index: {}", e2)))?
+                    self.line_counts.remove(workspace_id);
+                    self.path_indexes.remove(workspace_id);
+                    self.indexed_workspaces.remove(workspace_id);
+                    let idx = Index::create_in_dir(&index_path, schema_def.schema().clone())
+                        .map_err(|e2| AppError::IndexError(format!("Failed to recreate index: {}", e2)))?;
+                    Self::write_schema_version(&index_path)?;
+                    idx
                 }
             }
         } else {
-            Index::create_in_dir(&index_path, schema_def.schema().clone())
-                .map_err(|e| AppError::IndexError(format!("Failed to create index: {}", e)))?
+            let idx = Index::create_in_dir(&index_path, schema_def.schema().clone())
+                .map_err(|e| AppError::IndexError(format!("Failed to create index: {}", e)))?;
+            Self::write_schema_version(&index_path)?;
+            idx
         };
+        // Tokenizers are not persisted with the index; register on every open.
+        tokenizer::register(&index);
 
         // MEMORY FIX: Use manual reload policy — only reload when we explicitly ask.
         // Default policy uses warming threads that cache index segments in memory.
@@ -186,12 +732,113 @@ impl IndexManager {
             indexed_count: AtomicUsize::new(0),
             total_count: AtomicUsize::new(0),
             total_size_bytes: std::sync::atomic::AtomicU64::new(0),
+            last_index_duration_ms: std::sync::atomic::AtomicU64::new(0),
+            truncated: AtomicBool::new(false),
+            skipped: parking_lot::Mutex::new(SkippedFiles::default()),
+            last_used: std::sync::atomic::AtomicU64::new(0),
         });
+        self.touch(&state);
 
         self.indexes.insert(workspace_id.to_string(), state.clone());
+        self.unload_idle_indexes(workspace_id);
         Ok(state)
     }
 
+    /// The workspace's index if it is loaded, or loaded from disk if it exists
+    /// there (e.g. after being unloaded as idle). None if it was never created.
+    fn existing_index(&self, workspace_id: &str) -> AppResult<Option<Arc<IndexState>>> {
+        if self.indexes.contains_key(workspace_id)
+            || self.index_dir(workspace_id).join("meta.json").exists()
+        {
+            return self.get_or_create_index(workspace_id).map(Some);
+        }
+        Ok(None)
+    }
+
+    fn touch(&self, state: &IndexState) {
+        let now = self.access_clock.fetch_add(1, Ordering::Relaxed);
+        state.last_used.store(now, Ordering::Relaxed);
+    }
+
+    /// Unload least-recently-used indexes while more than `max_loaded_indexes`
+    /// are in memory. An index is only unloaded when nothing else holds its
+    /// `IndexState` — no search, writer or indexing pass in flight — and is
+    /// reopened from disk on next access. Every write path commits and saves
+    /// its sidecars before returning, so there is nothing left to flush.
+    fn unload_idle_indexes(&self, keep: &str) {
+        if self.max_loaded_indexes == 0 || self.indexes.len() <= self.max_loaded_indexes {
+            return;
+        }
+
+        let mut candidates: Vec<(u64, String)> = self
+            .indexes
+            .iter()
+            .filter(|entry| entry.key() != keep)
+            .map(|entry| (entry.value().last_used.load(Ordering::Relaxed), entry.key().clone()))
+            .collect();
+        candidates.sort_unstable();
+
+        for (_, workspace_id) in candidates {
+            if self.indexes.len() <= self.max_loaded_indexes {
+                break;
+            }
+            // remove_if runs under the shard lock, so no caller can clone the
+            // Arc between the idle check and the removal.
+            let unloaded = self
+                .indexes
+                .remove_if(&workspace_id, |_, state| {
+                    Arc::strong_count(state) == 1 && !state.is_indexing.load(Ordering::Acquire)
+                })
+                .is_some();
+            if unloaded {
+                self.content_hashes.remove(&workspace_id);
+                self.line_counts.remove(&workspace_id);
+                self.path_indexes.remove(&workspace_id);
+                info!("Unloaded idle index for workspace {}", workspace_id);
+            }
+        }
+    }
+
+    /// Open an index from disk, refusing it if the schema version sidecar is missing
+    /// or stale, or if the stored schema differs from the one we would build.
+    /// The inner `Err` carries a human-readable reason the index should be rebuilt;
+    /// lock contention is returned as an outer error so we never wipe an index
+    /// another process is using.
+    fn open_existing_index(index_path: &Path, schema_def: &IndexSchema) -> AppResult<Result<Index, String>> {
+        let version_path = index_path.join(SCHEMA_VERSION_FILE);
+        let on_disk_version = std::fs::read_to_string(&version_path)
+            .ok()
+            .and_then(|v| v.trim().parse::<u32>().ok());
+        match on_disk_version {
+            Some(v) if v == SCHEMA_VERSION => {}
+            Some(v) => {
+                return Ok(Err(format!(
+                    "schema version {} on disk, current is {}",
+                    v, SCHEMA_VERSION
+                )))
+            }
+            None => return Ok(Err("missing schema version sidecar".to_string())),
+        }
+
+        let idx = match Index::open_in_dir(index_path) {
+            Ok(idx) => idx,
+            Err(e @ tantivy::TantivyError::LockFailure(..)) => {
+                return Err(index_error("Failed to open index", e));
+            }
+            Err(e) => return Ok(Err(e.to_string())),
+        };
+        if idx.schema() != *schema_def.schema() {
+            return Ok(Err("on-disk schema differs from current schema".to_string()));
+        }
+        Ok(Ok(idx))
+    }
+
+    /// Record the current schema version next to a freshly created index.
+    fn write_schema_version(index_path: &Path) -> AppResult<()> {
+        std::fs::write(index_path.join(SCHEMA_VERSION_FILE), SCHEMA_VERSION.to_string())?;
+        Ok(())
+    }
+
     /// Index an entire workspace with smart incremental deduplication.
     /// Compares content hashes to skip re-indexing unchanged files.
     pub async fn index_workspace(
@@ -200,6 +847,27 @@ impl IndexManager {
         workspace_path: &str,
         event_tx: broadcast::Sender<ServerEvent>,
     ) -> AppResult<()> {
+        self.sync_workspace(workspace_id, workspace_path, None, event_tx).await
+    }
+
+    /// Indexing pass that trusts the recorded hash of every file last modified
+    /// before `modified_since` (Unix seconds) instead of hashing it again.
+    /// The walk still runs, so deleted and new files are picked up; with
+    /// `None` this is a full `index_workspace` pass.
+    pub async fn sync_workspace(
+        &self,
+        workspace_id: &str,
+        workspace_path: &str,
+        modified_since: Option<u64>,
+        event_tx: broadcast::Sender<ServerEvent>,
+    ) -> AppResult<()> {
+        // An empty walk of a missing root would remove every document
+        if !Path::new(workspace_path).is_dir() {
+            return Err(AppError::FileNotFound(format!(
+                "Workspace root no longer exists: {}",
+                workspace_path
+            )));
+        }
         let index_state = self.get_or_create_index(workspace_id)?;
 
         // Atomically check and set is_indexing to prevent concurrent indexing.
@@ -217,105 +885,35 @@ impl IndexManager {
 
         let ws_id = workspace_id.to_string();
         let ws_path = workspace_path.to_string();
-        let max_file_size = self.max_file_size;
+        let ws_path_buf = PathBuf::from(&ws_path);
         let batch_size = self.batch_size;
         let state = index_state.clone();
 
         let start = std::time::Instant::now();
 
-        // Collect files to index
-        let files: Vec<PathBuf> = WalkBuilder::new(&ws_path)
-            .hidden(false)
-            .git_ignore(true)
-            .git_global(true)
-            .git_exclude(true)
-            .max_depth(Some(20))
-            .build()
-            .filter_map(|entry| entry.ok())
-            .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
-            .filter(|entry| !Self::is_build_or_output_dir_with_patterns(entry.path(), &self.user_exclude_patterns))
-            .filter(|entry| {
-                entry
-                    .metadata()
-                    .map(|m| m.len() <= max_file_size as u64)
-                    .unwrap_or(false)
-            })
-            .filter(|entry| Self::is_indexable(entry.path()))
-            .map(|entry| entry.into_path())
-            // MEMORY FIX: Cap total files to prevent unbounded memory growth in large monorepos
-            .take(self.max_indexed_files)
-            .collect();
-
-        if files.len() >= self.max_indexed_files {
-            warn!(
-                "Workspace {} hit max_indexed_files cap ({}). Some files will not be indexed.",
-                ws_id, self.max_indexed_files
-            );
-        }
-
-        state.total_count.store(files.len(), Ordering::Relaxed);
-        let total = files.len();
-
-        // Compute total size of all files for stats
-        let total_size: u64 = files
-            .iter()
-            .filter_map(|f| f.metadata().ok())
-            .map(|m| m.len())
-            .sum();
+        let IndexPlan {
+            total_size,
+            existing_hashes,
+            new_file_hashes,
+            files_to_index,
+            unchanged_count,
+            paths_to_remove,
+            truncated,
+            mut skipped,
+        } = self.plan_index(&ws_id, &ws_path, modified_since)?;
+        let total = new_file_hashes.len();
+        state.total_count.store(total, Ordering::Relaxed);
         state
             .total_size_bytes
             .store(total_size, Ordering::Relaxed);
-
-        // Read existing content hashes from the sidecar file for deduplication
-        let existing_hashes = self.read_existing_hashes(&ws_id)?;
-
-        // MEMORY FIX: Compute hashes using streaming I/O — don't load full file content for hashing.
-        // This avoids holding all file contents in memory simultaneously during the hash phase.
-        let new_file_hashes: Vec<(PathBuf, String)> = files
-            .par_iter()
-            .filter_map(|file_path| {
-                use std::io::Read;
-                let mut file = std::fs::File::open(file_path).ok()?;
-                let mut hasher = Sha256::new();
-                let mut buf = [0u8; 8192];
-                loop {
-                    let n = file.read(&mut buf).ok()?;
-                    if n == 0 { break; }
-                    hasher.update(&buf[..n]);
-                }
-                let hash = format!("{:x}", hasher.finalize());
-                Some((file_path.clone(), hash))
-            })
-            .collect();
-
-        // Determine which files need re-indexing
-        let new_file_set: std::collections::HashSet<String> = new_file_hashes
-            .iter()
-            .map(|(p, _)| p.to_string_lossy().to_string())
-            .collect();
-
-        let mut files_to_index: Vec<PathBuf> = Vec::new();
-        let mut unchanged_count = 0usize;
-
-        for (file_path, new_hash) in &new_file_hashes {
-            let path_key = file_path.to_string_lossy().to_string();
-            match existing_hashes.get(&path_key) {
-                Some(existing_hash) if existing_hash == new_hash => {
-                    unchanged_count += 1;
-                }
-                _ => {
-                    files_to_index.push(file_path.clone());
-                }
-            }
+        state.truncated.store(truncated, Ordering::Relaxed);
+        if truncated {
+            let _ = event_tx.send(ServerEvent::IndexTruncated {
+                workspace_id: ws_id.clone(),
+                max_indexed_files: self.max_indexed_files,
+            });
         }
 
-        // Find paths to remove (files deleted since last index)
-        let paths_to_remove: Vec<String> = existing_hashes
-            .keys()
-            .filter(|p| !new_file_set.contains(*p))
-            .cloned()
-            .collect();
-
         info!(
             "Smart indexing workspace {}: {} unchanged (skipped), {} to index, {} to remove, total {} files ({:.1} MB)",
             ws_id, unchanged_count, files_to_index.len(), paths_to_remove.len(), total, total_size as f64 / 1_048_576.0
@@ -328,7 +926,14 @@ impl IndexManager {
             // is_indexing reset handled by _indexing_guard Drop
             // Still mark workspace as indexed — it completed successfully with zero changes
             self.indexed_workspaces.insert(ws_id.clone(), true);
+            self.index_errors.remove(&ws_id);
             let duration = start.elapsed();
+            state
+                .last_index_duration_ms
+                .store(duration.as_millis() as u64, Ordering::Relaxed);
+            self.update_line_counts(&ws_id, &ws_path_buf, &new_file_hashes, &[], &[], Vec::new());
+            Self::report_skipped(&ws_id, &skipped);
+            *state.skipped.lock() = skipped.clone();
 
             // Always emit IndexingCompleted so the renderer resets isIndexing.
             // Without this, the UI gets stuck showing "indexing" forever when
@@ -337,6 +942,8 @@ impl IndexManager {
                 workspace_id: ws_id.clone(),
                 total_files: total,
                 duration_ms: duration.as_millis() as u64,
+                skipped_files: skipped.count,
+                skipped_sample: skipped.sample,
             });
             let _ = event_tx.send(ServerEvent::SearchReady {
                 workspace_id: ws_id.clone(),
@@ -368,63 +975,145 @@ impl IndexManager {
         let mut writer: IndexWriter = state
             .index
             .writer(15_000_000)
-            .map_err(|e| AppError::IndexError(format!("Failed to create writer: {}", e)))?;
+            .map_err(|e| index_error("Failed to create writer", e))?;
 
-        // Remove documents for changed/deleted files (not all documents)
+        // Remove documents for deleted files. Changed files are deleted batch by
+        // batch right before being re-added, so an intermediate commit never
+        // drops a document whose replacement has not been written yet.
         for path_to_remove in &paths_to_remove {
-            let path_term = tantivy::Term::from_field_text(state.schema.path, path_to_remove);
-            writer.delete_term(path_term);
-        }
-        for file_path in &files_to_index {
-            let path_term = tantivy::Term::from_field_text(
-                state.schema.path,
-                &file_path.to_string_lossy(),
-            );
+            let path_term = tantivy::Term::from_field_text(state.schema.path_key, path_to_remove);
             writer.delete_term(path_term);
         }
 
-        let ws_path_buf = PathBuf::from(&ws_path);
 
         // MEMORY FIX: Process files in batches instead of loading ALL into memory at once.
         // Each batch reads, prepares docs, writes, and drops — keeping peak memory bounded.
         let batch_chunk_size = batch_size.max(100); // at least 100 files per batch
         let total_to_index = files_to_index.len();
-        for chunk in files_to_index.chunks(batch_chunk_size) {
+        let mut fresh_line_counts: Vec<(String, FileLineCount)> = Vec::with_capacity(total_to_index);
+
+        // Hashes as of the last commit: removals and committed files are applied,
+        // files not yet committed keep their old hash so an interrupted run
+        // re-indexes them next time.
+        let mut committed_hashes = existing_hashes;
+        for path_to_remove in &paths_to_remove {
+            committed_hashes.remove(path_to_remove);
+        }
+        let new_hash_of: HashMap<&PathBuf, &String> =
+            new_file_hashes.iter().map(|(path, hash)| (path, hash)).collect();
+        let mut uncommitted: Vec<&PathBuf> = Vec::new();
+        // Files that failed to read: no hash is recorded, so the next pass retries them
+        let mut unreadable: std::collections::HashSet<&PathBuf> = std::collections::HashSet::new();
+
+        // Git commit times for the `changed` field; files without one use their mtime
+        let commit_times = crate::git::last_commit_times(&ws_path_buf).unwrap_or_default();
+
+        let chunk_count = total_to_index.div_ceil(batch_chunk_size);
+        let mut last_progress_event: Option<std::time::Instant> = None;
+        for (chunk_index, chunk) in files_to_index.chunks(batch_chunk_size).enumerate() {
+            for file_path in chunk {
+                writer.delete_term(tantivy::Term::from_field_text(
+                    state.schema.path_key,
+                    &file_key(file_path, &ws_path_buf),
+                ));
+            }
+
             let schema = state.schema.clone();
             let ws_path_ref = &ws_path_buf;
-            let file_data: Vec<_> = chunk
-                .par_iter()
-                .filter_map(|file_path| {
-                    match Self::prepare_file_document(&schema, file_path, ws_path_ref) {
-                        Ok(doc) => Some(doc),
-                        Err(e) => {
-                            tracing::debug!("Skipped {}: {}", file_path.display(), e);
-                            None
-                        }
+            let commit_times = &commit_times;
+            let prepared: Vec<_> = self.read_pool.install(|| {
+                chunk
+                    .par_iter()
+                    .map(|file_path| {
+                        let commit_time = commit_times.get(&relative_path_of(file_path, ws_path_ref)).copied();
+                        Self::prepare_file_document(&schema, file_path, ws_path_ref, commit_time)
+                            .map(|(doc, lines)| (file_path, doc, lines))
+                            .map_err(|e| (file_path, e))
+                    })
+                    .collect()
+            });
+            let mut file_data = Vec::with_capacity(prepared.len());
+            for entry in prepared {
+                match entry {
+                    Ok(prepared) => file_data.push(prepared),
+                    Err((file_path, e)) => {
+                        tracing::debug!("Skipped {}: {}", file_path.display(), e);
+                        skipped.record(SkippedFile {
+                            path: relative_path_of(file_path, &ws_path_buf),
+                            reason: e.to_string(),
+                        });
+                        unreadable.insert(file_path);
                     }
-                })
-                .collect();
+                }
+            }
 
             // Sequential write to Tantivy (writer is single-threaded)
-            for doc in file_data {
+            for (file_path, doc, lines) in file_data {
                 if let Err(e) = writer.add_document(doc) {
                     warn!("Failed to add document: {}", e);
+                } else {
+                    fresh_line_counts.push((file_key(file_path, &ws_path_buf), lines));
                 }
                 state.indexed_count.fetch_add(1, Ordering::Relaxed);
 
-                // Emit progress every batch_size files
+                // Emit progress every batch_size files, at most once per PROGRESS_EVENT_INTERVAL
                 let indexed = state.indexed_count.load(Ordering::Relaxed);
-                if indexed % batch_size == 0 {
+                let throttled = last_progress_event
+                    .is_some_and(|sent| sent.elapsed() < PROGRESS_EVENT_INTERVAL);
+                if indexed % batch_size == 0 && !throttled {
                     let _ = event_tx.send(ServerEvent::IndexingProgress {
                         workspace_id: ws_id.clone(),
                         indexed,
                         total: total_to_index,
                     });
+                    last_progress_event = Some(std::time::Instant::now());
                 }
             }
             // Each batch's file_data Vec is dropped here, releasing memory
+
+            uncommitted.extend(chunk);
+            let more_to_come = chunk_index + 1 < chunk_count;
+            if self.commit_interval > 0 && uncommitted.len() >= self.commit_interval && more_to_come {
+                writer.commit().map_err(|e| {
+                    AppError::IndexError(format!("Failed to commit index: {}", e))
+                })?;
+                state.reader.reload().map_err(|e| {
+                    AppError::IndexError(format!("Failed to reload reader: {}", e))
+                })?;
+                // Rebuilt from the committed documents on next use
+                self.path_indexes.remove(&ws_id);
+                for file_path in uncommitted.drain(..) {
+                    let key = file_key(file_path, &ws_path_buf);
+                    match new_hash_of.get(file_path) {
+                        Some(hash) if !unreadable.contains(file_path) => {
+                            committed_hashes.insert(key, (*hash).clone());
+                        }
+                        _ => {
+                            committed_hashes.remove(&key);
+                        }
+                    }
+                }
+                self.content_hashes.insert(ws_id.clone(), committed_hashes.clone());
+                if let Err(e) = self.save_content_hashes(&ws_id) {
+                    warn!("Failed to save content hashes sidecar for {}: {}", ws_id, e);
+                }
+                info!(
+                    "Intermediate commit for {}: {}/{} files",
+                    ws_id,
+                    state.indexed_count.load(Ordering::Relaxed),
+                    total_to_index
+                );
+            }
         }
 
+        // Final progress event, even if throttling swallowed the last batches
+        // or files that failed to prepare kept the count short of the total
+        let _ = event_tx.send(ServerEvent::IndexingProgress {
+            workspace_id: ws_id.clone(),
+            indexed: total_to_index,
+            total: total_to_index,
+        });
+
         // Commit
         writer.commit().map_err(|e| {
             AppError::IndexError(format!("Failed to commit index: {}", e))
@@ -436,32 +1125,52 @@ impl IndexManager {
         state.reader.reload().map_err(|e| {
             AppError::IndexError(format!("Failed to reload reader: {}", e))
         })?;
+        self.path_indexes
+            .insert(ws_id.clone(), Arc::new(Self::load_path_index(&state)?));
 
         // Update content hashes sidecar: merge new hashes, remove deleted paths
         {
-            let mut updated_hashes = existing_hashes;
-            for path_to_remove in &paths_to_remove {
-                updated_hashes.remove(path_to_remove);
-            }
+            let mut updated_hashes = committed_hashes;
             for (file_path, new_hash) in &new_file_hashes {
-                updated_hashes.insert(file_path.to_string_lossy().to_string(), new_hash.clone());
+                let key = file_key(file_path, &ws_path_buf);
+                if unreadable.contains(file_path) {
+                    updated_hashes.remove(&key);
+                } else {
+                    updated_hashes.insert(key, new_hash.clone());
+                }
             }
             self.content_hashes.insert(ws_id.clone(), updated_hashes);
             if let Err(e) = self.save_content_hashes(&ws_id) {
                 warn!("Failed to save content hashes sidecar for {}: {}", ws_id, e);
             }
         }
+        self.update_line_counts(
+            &ws_id,
+            &ws_path_buf,
+            &new_file_hashes,
+            &paths_to_remove,
+            &files_to_index,
+            fresh_line_counts,
+        );
 
         let duration = start.elapsed();
         state.indexed_count.store(total, Ordering::Relaxed);
+        state
+            .last_index_duration_ms
+            .store(duration.as_millis() as u64, Ordering::Relaxed);
         // is_indexing reset handled by _indexing_guard Drop
         // Mark workspace as having completed indexing
         self.indexed_workspaces.insert(ws_id.clone(), true);
+        self.index_errors.remove(&ws_id);
+        Self::report_skipped(&ws_id, &skipped);
+        *state.skipped.lock() = skipped.clone();
 
         let _ = event_tx.send(ServerEvent::IndexingCompleted {
             workspace_id: ws_id.clone(),
             total_files: total,
             duration_ms: duration.as_millis() as u64,
+            skipped_files: skipped.count,
+            skipped_sample: skipped.sample,
         });
 
         info!(
@@ -476,10 +1185,140 @@ impl IndexManager {
         Ok(())
     }
 
+    /// Warn once per pass about files that could not be read.
+    fn report_skipped(workspace_id: &str, skipped: &SkippedFiles) {
+        if let Some(first) = skipped.sample.first() {
+            warn!(
+                "Indexing {} skipped {} unreadable file(s), e.g. {}: {}",
+                workspace_id, skipped.count, first.path, first.reason
+            );
+        }
+    }
+
+    /// Walk, filter and hash a workspace's files and compare them against the
+    /// last pass's content hashes. Shared by `index_workspace` and the dry-run
+    /// `index_plan`; reads the filesystem and sidecars only. Files with a
+    /// recorded hash and an mtime before `modified_since` keep that hash
+    /// without being read.
+    fn plan_index(
+        &self,
+        workspace_id: &str,
+        workspace_path: &str,
+        modified_since: Option<u64>,
+    ) -> AppResult<IndexPlan> {
+        let max_file_size = self.max_file_size_for(workspace_id);
+        let root = Path::new(workspace_path);
+
+        // Collect files to index, one past the cap to tell whether it was hit
+        let mut files: Vec<PathBuf> = index_walker(root)
+            .build()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
+            .filter(|entry| !Self::is_build_or_output_dir_with_patterns(entry.path(), &self.user_exclude_patterns))
+            .filter(|entry| {
+                entry
+                    .metadata()
+                    .map(|m| m.len() <= max_file_size as u64)
+                    .unwrap_or(false)
+            })
+            .filter(|entry| self.is_indexable(entry.path()))
+            .map(|entry| entry.into_path())
+            // MEMORY FIX: Cap total files to prevent unbounded memory growth in large monorepos.
+            // The walk is sorted, so the first `max_indexed_files` in path order are kept.
+            .take(self.max_indexed_files.saturating_add(1))
+            .collect();
+
+        let truncated = files.len() > self.max_indexed_files;
+        if truncated {
+            files.truncate(self.max_indexed_files);
+            warn!(
+                "Workspace {} hit max_indexed_files cap ({}). Some files will not be indexed.",
+                workspace_id, self.max_indexed_files
+            );
+        }
+
+        // Compute total size of all files for stats
+        let total_size: u64 = files
+            .iter()
+            .filter_map(|f| f.metadata().ok())
+            .map(|m| m.len())
+            .sum();
+
+        // Read existing content hashes from the sidecar file for deduplication
+        let existing_hashes = self.read_existing_hashes(workspace_id)?;
+
+        // MEMORY FIX: Compute hashes using streaming I/O — don't load full file content for hashing.
+        // This avoids holding all file contents in memory simultaneously during the hash phase.
+        let hashed: Vec<Result<(PathBuf, String), SkippedFile>> = self.read_pool.install(|| {
+            files
+                .par_iter()
+                .map(|file_path| {
+                    let recorded = modified_since.and_then(|cutoff| {
+                        let modified = file_path.metadata().map(|m| modified_secs(&m)).ok()?;
+                        if modified == 0 || modified >= cutoff {
+                            return None;
+                        }
+                        existing_hashes.get(&file_key(file_path, root)).cloned()
+                    });
+                    let hash = match recorded {
+                        Some(hash) => hash,
+                        None => hash_file(file_path).map_err(|e| SkippedFile {
+                            path: relative_path_of(file_path, root),
+                            reason: e.to_string(),
+                        })?,
+                    };
+                    Ok((file_path.clone(), hash))
+                })
+                .collect()
+        });
+        let mut skipped = SkippedFiles::default();
+        let mut new_file_hashes = Vec::with_capacity(hashed.len());
+        for entry in hashed {
+            match entry {
+                Ok(hashed) => new_file_hashes.push(hashed),
+                Err(file) => skipped.record(file),
+            }
+        }
+
+        // Determine which files need re-indexing and which were deleted
+        let changes = crate::changes::classify(&existing_hashes, &new_file_hashes, |p| file_key(p, root));
+
+        Ok(IndexPlan {
+            total_size,
+            existing_hashes,
+            new_file_hashes,
+            files_to_index: changes.changed,
+            unchanged_count: changes.unchanged.len(),
+            paths_to_remove: changes.removed,
+            truncated,
+            skipped,
+        })
+    }
+
+    /// What an indexing pass would do right now, without writing anything.
+    pub fn index_plan(&self, workspace_id: &str, workspace_path: &str) -> AppResult<IndexPlanSummary> {
+        let plan = self.plan_index(workspace_id, workspace_path, None)?;
+        let bytes_to_index = plan
+            .files_to_index
+            .iter()
+            .filter_map(|f| f.metadata().ok())
+            .map(|m| m.len())
+            .sum();
+        Ok(IndexPlanSummary {
+            total_files: plan.new_file_hashes.len(),
+            files_to_index: plan.files_to_index.len(),
+            files_unchanged: plan.unchanged_count,
+            files_to_remove: plan.paths_to_remove.len(),
+            total_size_bytes: plan.total_size,
+            bytes_to_index,
+            max_indexed_files_reached: plan.truncated,
+        })
+    }
+
     /// Read existing content hashes from the sidecar file for deduplication.
     /// Uses a fast JSON sidecar file (`content_hashes.json`) instead of scanning
     /// the entire Tantivy index via AllQuery, which is O(n) over all documents.
-    /// Returns a map of absolute file path -> content hash.
+    /// Returns a map of `path_key` -> content hash.
     fn read_existing_hashes(&self, workspace_id: &str) -> AppResult<HashMap<String, String>> {
         // Check in-memory cache first
         if let Some(hashes) = self.content_hashes.get(workspace_id) {
@@ -502,49 +1341,226 @@ impl IndexManager {
         Ok(hashes)
     }
 
-    /// Save content hashes to the sidecar file for persistence across restarts.
-    fn save_content_hashes(&self, workspace_id: &str) -> AppResult<()> {
-        if let Some(hashes) = self.content_hashes.get(workspace_id) {
-            let hash_path = self.index_dir(workspace_id).join("content_hashes.json");
-            let json = serde_json::to_string(hashes.value())
-                .map_err(|e| AppError::IndexError(format!("Failed to serialize content hashes: {}", e)))?;
-            // Atomic write: write to tmp file then rename
-            let tmp_path = hash_path.with_extension("json.tmp");
-            std::fs::write(&tmp_path, &json)?;
-            std::fs::rename(&tmp_path, &hash_path)?;
+    /// Line counts for a workspace, from memory or the `line_counts.json` sidecar.
+    fn read_line_counts(&self, workspace_id: &str) -> HashMap<String, FileLineCount> {
+        if let Some(counts) = self.line_counts.get(workspace_id) {
+            return counts.value().clone();
+        }
+
+        let counts = std::fs::read_to_string(self.index_dir(workspace_id).join("line_counts.json"))
+            .ok()
+            .and_then(|json| serde_json::from_str::<HashMap<String, FileLineCount>>(&json).ok())
+            .unwrap_or_default();
+        self.line_counts.insert(workspace_id.to_string(), counts.clone());
+        counts
+    }
+
+    /// Persist line counts to the sidecar file (best-effort, like content hashes).
+    fn save_line_counts(&self, workspace_id: &str) {
+        let Some(counts) = self.line_counts.get(workspace_id) else {
+            return;
+        };
+        let result = serde_json::to_string(counts.value())
+            .map_err(AppError::from)
+            .and_then(|json| {
+                let path = self.index_dir(workspace_id).join("line_counts.json");
+                let tmp_path = path.with_extension("json.tmp");
+                std::fs::write(&tmp_path, &json)?;
+                std::fs::rename(&tmp_path, &path)?;
+                Ok(())
+            });
+        if let Err(e) = result {
+            warn!("Failed to save line counts sidecar for {}: {}", workspace_id, e);
+        }
+    }
+
+    /// Apply one `index_workspace` pass to the line counts: drop removed and
+    /// re-indexed paths, record the fresh counts, and backfill current files
+    /// that have no entry yet (e.g. indexed before line counts were tracked).
+    fn update_line_counts(
+        &self,
+        workspace_id: &str,
+        workspace_path: &Path,
+        current: &[(PathBuf, String)],
+        removed: &[String],
+        reindexed: &[PathBuf],
+        fresh: Vec<(String, FileLineCount)>,
+    ) {
+        let mut counts = self.read_line_counts(workspace_id);
+        for path in removed {
+            counts.remove(path);
+        }
+        for path in reindexed {
+            counts.remove(&file_key(path, workspace_path));
+        }
+        counts.extend(fresh);
+
+        // Re-indexed files without a fresh count failed to parse; don't retry them
+        let reindexed: std::collections::HashSet<&PathBuf> = reindexed.iter().collect();
+        let backfilled: Vec<(String, FileLineCount)> = self.read_pool.install(|| {
+            current
+                .par_iter()
+                .map(|(path, _)| (path, file_key(path, workspace_path)))
+                .filter(|(path, key)| !counts.contains_key(key) && !reindexed.contains(path))
+                .filter_map(|(path, key)| {
+                    let content = std::fs::read_to_string(path).ok()?;
+                    Some((key, FileLineCount::new(&content, &language_of(path, &content))))
+                })
+                .collect()
+        });
+        counts.extend(backfilled);
+
+        self.line_counts.insert(workspace_id.to_string(), counts);
+        self.save_line_counts(workspace_id);
+    }
+
+    /// Total lines per language, largest first. With `exclude_blank`,
+    /// whitespace-only lines are not counted.
+    pub fn lines_by_language(&self, workspace_id: &str, exclude_blank: bool) -> Vec<(String, u64)> {
+        let mut totals: HashMap<String, u64> = HashMap::new();
+        for count in self.read_line_counts(workspace_id).values() {
+            let lines = if exclude_blank {
+                count.lines - count.blank_lines
+            } else {
+                count.lines
+            };
+            *totals.entry(count.language.clone()).or_default() += lines;
+        }
+        let mut totals: Vec<(String, u64)> = totals.into_iter().collect();
+        totals.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        totals
+    }
+
+    /// Save content hashes to the sidecar file for persistence across restarts.
+    fn save_content_hashes(&self, workspace_id: &str) -> AppResult<()> {
+        if let Some(hashes) = self.content_hashes.get(workspace_id) {
+            let hash_path = self.index_dir(workspace_id).join("content_hashes.json");
+            let json = serde_json::to_string(hashes.value())
+                .map_err(|e| AppError::IndexError(format!("Failed to serialize content hashes: {}", e)))?;
+            // Atomic write: write to tmp file then rename
+            let tmp_path = hash_path.with_extension("json.tmp");
+            std::fs::write(&tmp_path, &json)?;
+            std::fs::rename(&tmp_path, &hash_path)?;
+        }
+        Ok(())
+    }
+
+    /// Compare the indexed documents with the `content_hashes.json` sidecar.
+    /// With `repair`, a divergent sidecar is rebuilt from the index (each
+    /// document's stored `content_hash`), so the next pass neither duplicates
+    /// indexed files nor skips files that were never indexed.
+    pub async fn check_content_hashes(&self, workspace_id: &str, repair: bool) -> AppResult<HashSidecarCheck> {
+        let Some(state) = self.existing_index(workspace_id)? else {
+            return Ok(HashSidecarCheck::default());
+        };
+
+        // Hold the writer lock so no pass or incremental update changes the
+        // index or the sidecar between reading and rebuilding them
+        let _guard = self.writer_lock.lock().await;
+        if state.is_indexing.load(Ordering::Acquire) {
+            return Err(AppError::IndexingInProgress(workspace_id.to_string()));
+        }
+
+        let indexed = Self::indexed_hashes(&state)?;
+        let sidecar = self.read_existing_hashes(workspace_id)?;
+
+        let mut check = HashSidecarCheck {
+            indexed_documents: indexed.len(),
+            sidecar_entries: sidecar.len(),
+            ..HashSidecarCheck::default()
+        };
+        let mut keys: Vec<&String> = indexed.keys().collect();
+        keys.sort_unstable();
+        for key in keys {
+            match sidecar.get(key) {
+                None => {
+                    check.missing_from_sidecar += 1;
+                    if check.sample_missing_from_sidecar.len() < SIDECAR_CHECK_SAMPLE {
+                        check.sample_missing_from_sidecar.push(key.clone());
+                    }
+                }
+                Some(hash) if *hash != indexed[key] => {
+                    check.hash_mismatches += 1;
+                    if check.sample_hash_mismatches.len() < SIDECAR_CHECK_SAMPLE {
+                        check.sample_hash_mismatches.push(key.clone());
+                    }
+                }
+                Some(_) => {}
+            }
+        }
+        let mut sidecar_only: Vec<&String> = sidecar.keys().filter(|key| !indexed.contains_key(*key)).collect();
+        sidecar_only.sort_unstable();
+        check.missing_from_index = sidecar_only.len();
+        check.sample_missing_from_index = sidecar_only
+            .into_iter()
+            .take(SIDECAR_CHECK_SAMPLE)
+            .cloned()
+            .collect();
+
+        if !check.is_consistent() {
+            warn!(
+                "Content hash sidecar for {} diverges from its index: {} indexed files missing, {} stale entries, {} hash mismatches",
+                workspace_id, check.missing_from_sidecar, check.missing_from_index, check.hash_mismatches
+            );
+            if repair {
+                self.content_hashes.insert(workspace_id.to_string(), indexed);
+                self.save_content_hashes(workspace_id)?;
+                check.repaired = true;
+                info!("Rebuilt content hash sidecar for {} from the index", workspace_id);
+            }
+        }
+        Ok(check)
+    }
+
+    /// `path_key` -> stored `content_hash` for every document in the index.
+    fn indexed_hashes(state: &IndexState) -> AppResult<HashMap<String, String>> {
+        let searcher = state.reader.searcher();
+        let schema = &state.schema;
+        let mut hashes = HashMap::new();
+        for segment in searcher.segment_readers() {
+            let store = segment
+                .get_store_reader(1)
+                .map_err(|e| AppError::IndexError(format!("Failed to open document store: {}", e)))?;
+            for doc in store.iter::<TantivyDocument>(segment.alive_bitset()) {
+                let doc = doc.map_err(|e| AppError::IndexError(format!("Failed to read document: {}", e)))?;
+                let path = doc.get_first(schema.relative_path).and_then(|v| v.as_str());
+                let hash = doc.get_first(schema.content_hash).and_then(|v| v.as_str());
+                if let (Some(path), Some(hash)) = (path, hash) {
+                    hashes.insert(path_key(path), hash.to_string());
+                }
+            }
         }
-        Ok(())
+        Ok(hashes)
     }
 
+    /// Index one file outside a full pass, looking up its last commit time.
     fn index_file(
         schema: &IndexSchema,
         writer: &mut IndexWriter,
         file_path: &Path,
         workspace_path: &Path,
-    ) -> AppResult<()> {
-        let doc = Self::prepare_file_document(schema, file_path, workspace_path)?;
+    ) -> AppResult<FileLineCount> {
+        let commit_time =
+            crate::git::last_commit_time(workspace_path, &relative_path_of(file_path, workspace_path));
+        let (doc, lines) = Self::prepare_file_document(schema, file_path, workspace_path, commit_time)?;
         writer.add_document(doc).map_err(|e| {
             AppError::IndexError(format!("Failed to add document: {}", e))
         })?;
-        Ok(())
+        Ok(lines)
     }
 
-    /// Prepare a TantivyDocument from a file without writing it.
+    /// Prepare a TantivyDocument (and the file's line counts) without writing it.
     /// This is safe to call from rayon's parallel iterator (no &mut writer needed).
+    /// `commit_time` fills the `changed` field; the mtime is used without one.
     fn prepare_file_document(
         schema: &IndexSchema,
         file_path: &Path,
         workspace_path: &Path,
-    ) -> AppResult<TantivyDocument> {
-        let content = std::fs::read_to_string(file_path).map_err(|_| {
-            AppError::FileNotFound(file_path.to_string_lossy().to_string())
-        })?;
+        commit_time: Option<u64>,
+    ) -> AppResult<(TantivyDocument, FileLineCount)> {
+        let content = std::fs::read_to_string(file_path)?;
 
-        let relative = file_path
-            .strip_prefix(workspace_path)
-            .unwrap_or(file_path)
-            .to_string_lossy()
-            .replace('\\', "/");
+        let relative = relative_path_of(file_path, workspace_path);
 
         let filename = file_path
             .file_name()
@@ -558,23 +1574,19 @@ impl IndexManager {
             .to_string_lossy()
             .to_string();
 
-        let language = crate::lang::detect_language(&extension).to_string();
+        let language = language_of(file_path, &content);
+        let lines = FileLineCount::new(&content, &language);
 
         let metadata = std::fs::metadata(file_path)?;
         let size = metadata.len();
-        let modified = metadata
-            .modified()
-            .ok()
-            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-            .map(|d| d.as_secs())
-            .unwrap_or(0);
+        let modified = modified_secs(&metadata);
 
         let mut hasher = Sha256::new();
         hasher.update(content.as_bytes());
         let hash = format!("{:x}", hasher.finalize());
 
         let mut doc = TantivyDocument::new();
-        doc.add_text(schema.path, &file_path.to_string_lossy());
+        doc.add_text(schema.path_key, path_key(&relative));
         doc.add_text(schema.relative_path, &relative);
         doc.add_text(schema.filename, &filename);
         doc.add_text(schema.extension, &extension);
@@ -582,13 +1594,29 @@ impl IndexManager {
         doc.add_text(schema.language, &language);
         doc.add_u64(schema.size, size);
         doc.add_u64(schema.modified, modified);
+        doc.add_u64(schema.changed, commit_time.unwrap_or(modified));
         doc.add_text(schema.content_hash, &hash);
-        doc.add_text(schema.symbols, &extract_symbols(&content, &language));
+        doc.add_text(schema.symbols, extract_symbols(&content, &language));
+
+        let markers = extract_markers(&content, &language);
+        let mut kinds: Vec<&str> = markers.iter().map(|m| m.kind.as_str()).collect();
+        kinds.sort_unstable();
+        kinds.dedup();
+        for kind in kinds {
+            doc.add_text(schema.marker_kinds, kind);
+        }
+        for marker in &markers {
+            doc.add_text(schema.markers, marker.encode());
+        }
 
-        Ok(doc)
+        Ok((doc, lines))
     }
 
-    fn is_indexable(path: &Path) -> bool {
+    fn is_indexable(&self, path: &Path) -> bool {
+        if path.file_name().is_some_and(|n| crate::config::is_lockfile(&n.to_string_lossy())) {
+            return self.index_lockfiles;
+        }
+
         let ext = path
             .extension()
             .unwrap_or_default()
@@ -606,6 +1634,9 @@ impl IndexManager {
                     | "readme" | "license" | "changelog" | "contributing"
             )
         })
+        // Unknown extension: extensionless scripts, `Makefile.inc`, `*.in` templates
+        || (crate::lang::language_for_extension(&ext).is_none()
+            && crate::lang::sniff_language(path).is_some())
     }
 
     /// Skip files inside build/output directories that should never be indexed.
@@ -672,6 +1703,10 @@ impl IndexManager {
                 indexed_count: state.indexed_count.load(Ordering::Relaxed),
                 total_count: state.total_count.load(Ordering::Relaxed),
                 total_size_bytes: state.total_size_bytes.load(Ordering::Relaxed),
+                last_index_duration_ms: state.last_index_duration_ms.load(Ordering::Relaxed),
+                truncated: state.truncated.load(Ordering::Relaxed),
+                skipped: state.skipped.lock().clone(),
+                last_error: self.last_index_error(workspace_id),
             })
         } else {
             Ok(IndexStatusResponse {
@@ -680,23 +1715,285 @@ impl IndexManager {
                 indexed_count: 0,
                 total_count: 0,
                 total_size_bytes: 0,
+                last_index_duration_ms: 0,
+                truncated: false,
+                skipped: SkippedFiles::default(),
+                last_error: self.last_index_error(workspace_id),
             })
         }
     }
 
+    /// Remember a failed indexing pass, keeping the last `MAX_INDEX_ERRORS`.
+    pub fn record_index_error(&self, workspace_id: &str, error: &str) {
+        let mut errors = self.index_errors.entry(workspace_id.to_string()).or_default();
+        if errors.len() >= MAX_INDEX_ERRORS {
+            errors.pop_front();
+        }
+        errors.push_back(IndexingErrorRecord {
+            error: error.to_string(),
+            occurred_at: chrono::Utc::now(),
+        });
+    }
+
+    /// Indexing errors since the last completed pass, oldest first.
+    pub fn index_errors(&self, workspace_id: &str) -> Vec<IndexingErrorRecord> {
+        self.index_errors
+            .get(workspace_id)
+            .map(|errors| errors.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn last_index_error(&self, workspace_id: &str) -> Option<IndexingErrorRecord> {
+        self.index_errors.get(workspace_id)?.back().cloned()
+    }
+
+    /// Size stored for the file at `relative_path` (from the workspace root)
+    /// without touching the file. Returns None if the workspace index isn't
+    /// loaded or doesn't contain the file.
+    pub fn indexed_file_size(&self, workspace_id: &str, relative_path: &str) -> Option<u64> {
+        let state = self.indexes.get(workspace_id)?.value().clone();
+        let searcher = state.reader.searcher();
+        let term = tantivy::Term::from_field_text(state.schema.path_key, &path_key(relative_path));
+        let query = tantivy::query::TermQuery::new(term, IndexRecordOption::Basic);
+        let (_, address) = searcher
+            .search(&query, &tantivy::collector::TopDocs::with_limit(1))
+            .ok()?
+            .into_iter()
+            .next()?;
+        let doc: TantivyDocument = searcher.doc(address).ok()?;
+
+        Some(doc.get_first(state.schema.size).and_then(|v| v.as_u64()).unwrap_or(0))
+    }
+
+    /// The workspace's indexed files, loaded from the index's stored documents
+    /// on first use. Never touches the workspace directory; a workspace that
+    /// has not been indexed yet has an empty path index.
+    pub fn path_index(&self, workspace_id: &str) -> AppResult<Arc<PathIndex>> {
+        if let Some(paths) = self.path_indexes.get(workspace_id) {
+            return Ok(paths.clone());
+        }
+        let state = self.get_or_create_index(workspace_id)?;
+        let paths = Arc::new(Self::load_path_index(&state)?);
+        self.path_indexes.insert(workspace_id.to_string(), paths.clone());
+        Ok(paths)
+    }
+
+    /// Whether the workspace's index and path index are both in memory, so
+    /// the first search or listing doesn't pay for opening them.
+    pub fn is_warm(&self, workspace_id: &str) -> bool {
+        self.indexes.contains_key(workspace_id) && self.path_indexes.contains_key(workspace_id)
+    }
+
+    /// Open the workspace's index from disk and build its path index ahead of
+    /// the first request. Cheap when both are already loaded.
+    pub fn warm_up(&self, workspace_id: &str) -> AppResult<()> {
+        self.get_or_create_index(workspace_id)?;
+        self.path_index(workspace_id)?;
+        Ok(())
+    }
+
+    fn load_path_index(state: &IndexState) -> AppResult<PathIndex> {
+        let searcher = state.reader.searcher();
+        let schema = &state.schema;
+        let mut paths = PathIndex::new();
+        for segment in searcher.segment_readers() {
+            let store = segment
+                .get_store_reader(1)
+                .map_err(|e| AppError::IndexError(format!("Failed to open document store: {}", e)))?;
+            for doc in store.iter::<TantivyDocument>(segment.alive_bitset()) {
+                let doc = doc.map_err(|e| AppError::IndexError(format!("Failed to read document: {}", e)))?;
+                let Some(relative) = doc.get_first(schema.relative_path).and_then(|v| v.as_str()) else {
+                    continue;
+                };
+                let u64_of = |field| doc.get_first(field).and_then(|v| v.as_u64()).unwrap_or(0);
+                paths.insert(
+                    relative.to_string(),
+                    IndexedPathInfo { size: u64_of(schema.size), modified: u64_of(schema.modified) },
+                );
+            }
+        }
+        Ok(paths)
+    }
+
+    /// Apply an incremental change to a loaded path index; unloaded ones are
+    /// built from the committed index when next needed.
+    fn update_path_index(&self, workspace_id: &str, update: impl FnOnce(&mut PathIndex)) {
+        if let Some(mut paths) = self.path_indexes.get_mut(workspace_id) {
+            update(Arc::make_mut(&mut paths));
+        }
+    }
+
+    /// Run the checks `index_workspace` applies to decide whether to index
+    /// `relative_path`, and report each outcome.
+    pub fn explain_path(
+        &self,
+        workspace_id: &str,
+        workspace_path: &str,
+        relative_path: &str,
+    ) -> IndexExplanation {
+        let root = PathBuf::from(workspace_path);
+        let abs_path = root.join(relative_path);
+        let metadata = std::fs::metadata(&abs_path).ok();
+        let exists = metadata.is_some();
+        let is_file = metadata.as_ref().is_some_and(|m| m.is_file());
+        let size_bytes = metadata.as_ref().filter(|m| m.is_file()).map(|m| m.len());
+        let max_file_size_bytes = self.max_file_size_for(workspace_id);
+        let over_size_limit = size_bytes.is_some_and(|size| size > max_file_size_bytes as u64);
+
+        // Walk only the chain of directories leading to the file: the walker
+        // reaches it exactly when no ignore rule or depth limit drops it
+        let ignored = is_file && {
+            let target = abs_path.clone();
+            !index_walker(&root)
+                .filter_entry(move |entry| target.starts_with(entry.path()))
+                .build()
+                .filter_map(|entry| entry.ok())
+                .any(|entry| entry.path() == abs_path)
+        };
+        let excluded_dir = Self::is_build_or_output_dir_with_patterns(&abs_path, &self.user_exclude_patterns);
+        let lockfile = !self.index_lockfiles
+            && abs_path.file_name().is_some_and(|n| crate::config::is_lockfile(&n.to_string_lossy()));
+        let supported_type = is_file && self.is_indexable(&abs_path);
+        let utf8 = (is_file && !over_size_limit).then(|| std::fs::read_to_string(&abs_path).ok());
+        let language = utf8
+            .as_ref()
+            .and_then(|content| content.as_deref())
+            .map(|content| language_of(&abs_path, content));
+        let utf8 = utf8.map(|content| content.is_some());
+
+        let indexable = is_file
+            && !ignored
+            && !excluded_dir
+            && !over_size_limit
+            && supported_type
+            && utf8 == Some(true);
+        let indexed = self
+            .indexed_file_size(workspace_id, &relative_path_of(&abs_path, &root))
+            .is_some();
+
+        let mut reasons = Vec::new();
+        if !exists {
+            reasons.push("file does not exist".to_string());
+        } else if !is_file {
+            reasons.push("not a regular file".to_string());
+        } else {
+            if ignored {
+                reasons.push("ignored by .gitignore/.ignore/git exclude rules, or nested deeper than 20 directories".to_string());
+            }
+            if excluded_dir {
+                reasons.push("inside an excluded build/output directory or user exclude pattern".to_string());
+            }
+            if over_size_limit {
+                reasons.push(format!(
+                    "larger than the {} byte file size limit",
+                    max_file_size_bytes
+                ));
+            }
+            if lockfile {
+                reasons.push("dependency lockfile (enable index_lockfiles to include)".to_string());
+            } else if !supported_type {
+                reasons.push("unsupported file type".to_string());
+            }
+            if utf8 == Some(false) {
+                reasons.push("not valid UTF-8 (binary or other encoding)".to_string());
+            }
+            if indexable && !indexed {
+                reasons.push(format!(
+                    "passes every check but is not indexed yet: indexing may be pending, or the workspace hit max_indexed_files ({})",
+                    self.max_indexed_files
+                ));
+            }
+        }
+
+        IndexExplanation {
+            path: relative_path.to_string(),
+            exists,
+            indexed,
+            indexable,
+            ignored,
+            excluded_dir,
+            size_bytes,
+            max_file_size_bytes,
+            over_size_limit,
+            lockfile,
+            supported_type,
+            language,
+            utf8,
+            reasons,
+        }
+    }
+
+    /// Remove a file or a whole directory tree from the index in one commit.
+    /// Same as `reindex_file` with `"remove"`; the watcher reaches it through
+    /// directory-remove events.
+    pub async fn remove_path_prefix(
+        &self,
+        workspace_id: &str,
+        relative_path: &str,
+        workspace_path: &str,
+    ) -> AppResult<StatsDelta> {
+        self.reindex_file(workspace_id, relative_path, workspace_path, "remove").await
+    }
+
+    /// Queue deletion of every document whose `path_key` starts with `prefix`
+    /// (see `descendant_prefix`) on `writer`, returning the stats change once
+    /// committed. The reader must be current, i.e. the caller holds the
+    /// writer lock.
+    fn delete_descendants(
+        &self,
+        state: &IndexState,
+        writer: &IndexWriter,
+        prefix: &str,
+    ) -> AppResult<StatsDelta> {
+        use std::ops::Bound;
+        use tantivy::collector::DocSetCollector;
+        use tantivy::query::RangeQuery;
+
+        // '0' is the byte after '/', so the range holds exactly the keys under `prefix`
+        let upper = format!("{}0", prefix.trim_end_matches('/'));
+        let field = state.schema.path_key;
+        let query = RangeQuery::new(
+            Bound::Included(tantivy::Term::from_field_text(field, prefix)),
+            Bound::Excluded(tantivy::Term::from_field_text(field, &upper)),
+        );
+
+        let searcher = state.reader.searcher();
+        let addresses = searcher
+            .search(&query, &DocSetCollector)
+            .map_err(|e| index_error("Failed to find documents under directory", e))?;
+        if addresses.is_empty() {
+            return Ok(StatsDelta::default());
+        }
+
+        let mut delta = StatsDelta::default();
+        for address in &addresses {
+            let doc: TantivyDocument = searcher
+                .doc(*address)
+                .map_err(|e| index_error("Failed to read document", e))?;
+            let size = doc.get_first(state.schema.size).and_then(|v| v.as_u64()).unwrap_or(0);
+            delta = delta + StatsDelta::replacing(Some(size), None);
+        }
+        writer
+            .delete_query(Box::new(query))
+            .map_err(|e| index_error("Failed to delete documents under directory", e))?;
+        info!("Removing {} indexed files under {}", addresses.len(), prefix);
+        Ok(delta)
+    }
+
     /// Incrementally re-index a single file (used by file watcher).
     /// Acquires `writer_lock` to serialize Tantivy writer access across
-    /// concurrent file-change events.
+    /// concurrent file-change events. Returns the change to the workspace's
+    /// file count and total size, already applied to the index status.
     pub async fn reindex_file(
         &self,
         workspace_id: &str,
         file_path: &str,
         workspace_path: &str,
         change_type: &str,
-    ) -> AppResult<()> {
-        let index_state = match self.indexes.get(workspace_id) {
-            Some(state) => state.value().clone(),
-            None => return Ok(()), // No index yet, skip
+    ) -> AppResult<StatsDelta> {
+        let index_state = match self.existing_index(workspace_id)? {
+            Some(state) => state,
+            None => return Ok(StatsDelta::default()), // No index yet, skip
         };
 
         // Serialize writer access — Tantivy allows only one IndexWriter at a time
@@ -704,71 +2001,93 @@ impl IndexManager {
 
         // Skip if a full indexing is in progress
         if index_state.is_indexing.load(Ordering::Acquire) {
-            return Ok(());
+            return Ok(StatsDelta::default());
         }
 
         let abs_path = PathBuf::from(workspace_path).join(file_path);
         let ws_path_buf = PathBuf::from(workspace_path);
-
-        // MEMORY FIX: 3MB buffer for single-file operations (was 10MB — way too much)
+        let relative = relative_path_of(&abs_path, &ws_path_buf);
+        // The reader is current under the writer lock, so this is the size the
+        // totals already include for this file
+        let size_before = self.indexed_file_size(workspace_id, &relative);
+        let mut size_after = None;
+        let mut descendants = StatsDelta::default();
+
+        // MEMORY FIX: single-threaded writer at Tantivy's minimum arena size for
+        // single-file operations (anything smaller is rejected by Tantivy)
         let mut writer: IndexWriter = index_state
             .index
-            .writer(3_000_000)
-            .map_err(|e| AppError::IndexError(format!("Failed to create writer: {}", e)))?;
+            .writer_with_num_threads(1, INCREMENTAL_WRITER_BUDGET)
+            .map_err(|e| index_error("Failed to create writer", e))?;
 
         // Delete existing document for this file path
-        let path_term = tantivy::Term::from_field_text(
-            index_state.schema.path,
-            &abs_path.to_string_lossy(),
-        );
+        let key = path_key(&relative);
+        let path_term = tantivy::Term::from_field_text(index_state.schema.path_key, &key);
         writer.delete_term(path_term);
 
+        // Drop the old line count; re-added below if the file is re-indexed
+        let mut line_counts = self.read_line_counts(workspace_id);
+        line_counts.remove(&key);
+
         // For create/modify, re-index the file
         if change_type != "remove" {
-            if abs_path.exists() && Self::is_indexable(&abs_path) {
+            if abs_path.exists() && self.is_indexable(&abs_path) {
                 let metadata = std::fs::metadata(&abs_path).ok();
                 let file_size = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
 
-                if file_size <= self.max_file_size as u64 {
-                    if let Err(e) = Self::index_file(
+                if file_size <= self.max_file_size_for(workspace_id) as u64 {
+                    let hash = match Self::index_file(
                         &index_state.schema,
                         &mut writer,
                         &abs_path,
                         &ws_path_buf,
                     ) {
-                        warn!("Failed to re-index {}: {}", file_path, e);
-                    }
-
-                    // Update content hash sidecar so next full index_workspace() skips this file
-                    if let Ok(content) = std::fs::read(&abs_path) {
-                        let mut hasher = Sha256::new();
-                        hasher.update(&content);
-                        let hash = format!("{:x}", hasher.finalize());
-                        let abs_key = abs_path.to_string_lossy().to_string();
-                        let ws_id = workspace_id.to_string();
-                        // Update in-memory hash map
-                        self.content_hashes
-                            .entry(ws_id.clone())
-                            .or_insert_with(HashMap::new)
-                            .insert(abs_key, hash);
-                        // Persist to disk (best-effort)
-                        if let Err(e) = self.save_content_hashes(&ws_id) {
-                            warn!("Failed to persist content hash after reindex: {}", e);
+                        Ok(lines) => {
+                            line_counts.insert(key.clone(), lines);
+                            size_after = Some(file_size);
+                            hash_file(&abs_path).ok()
+                        }
+                        Err(e) => {
+                            warn!("Failed to re-index {}: {}", file_path, e);
+                            None
                         }
+                    };
+
+                    // Update content hash sidecar so next full index_workspace() skips
+                    // this file; one that failed loses its hash so that pass retries
+                    // it (and reports it in `skipped_files` if it fails again)
+                    {
+                        let mut hashes = self.content_hashes.entry(workspace_id.to_string()).or_default();
+                        match hash {
+                            Some(hash) => hashes.insert(key.clone(), hash),
+                            None => hashes.remove(&key),
+                        };
+                    }
+                    // Persist to disk (best-effort)
+                    if let Err(e) = self.save_content_hashes(workspace_id) {
+                        warn!("Failed to persist content hash after reindex: {}", e);
                     }
                 }
             }
         } else {
+            // The path may have been a directory: drop everything under it in
+            // the same commit, in case per-file events were missed
+            let prefix = descendant_prefix(&key);
+            descendants = self.delete_descendants(&index_state, &writer, &prefix)?;
+            line_counts.retain(|key, _| !key.starts_with(&prefix));
+
             // File removed — remove from content hashes too
-            let abs_key = abs_path.to_string_lossy().to_string();
             if let Some(mut hashes) = self.content_hashes.get_mut(workspace_id) {
-                hashes.remove(&abs_key);
+                hashes.remove(&key);
+                hashes.retain(|key, _| !key.starts_with(&prefix));
             }
             // Persist removal (best-effort)
             if let Err(e) = self.save_content_hashes(workspace_id) {
                 warn!("Failed to persist content hash after removal: {}", e);
             }
         }
+        self.line_counts.insert(workspace_id.to_string(), line_counts);
+        self.save_line_counts(workspace_id);
 
         writer.commit().map_err(|e| {
             AppError::IndexError(format!("Failed to commit incremental index: {}", e))
@@ -781,14 +2100,387 @@ impl IndexManager {
             AppError::IndexError(format!("Failed to reload reader: {}", e))
         })?;
 
+        let relative = relative_path_of(&abs_path, &ws_path_buf);
+        self.update_path_index(workspace_id, |paths| {
+            paths.remove(&relative);
+            if change_type == "remove" {
+                let prefix = format!("{}/", relative);
+                paths.retain(|path, _| !path.starts_with(&prefix));
+            } else if let Some(size) = size_after {
+                let modified = std::fs::metadata(&abs_path).map(|m| modified_secs(&m)).unwrap_or(0);
+                paths.insert(relative.clone(), IndexedPathInfo { size, modified });
+            }
+        });
+
+        let delta = StatsDelta::replacing(size_before, size_after) + descendants;
+        Self::apply_stats_delta(&index_state, delta);
+
         info!("Incrementally re-indexed file: {} ({})", file_path, change_type);
-        Ok(())
+        Ok(delta)
+    }
+
+    /// Move a file's document to a new path after a rename (used by file watcher).
+    /// Tantivy cannot rewrite a document in place and `content` is not stored, so
+    /// the file is re-read for the new document, but the content hash is carried
+    /// over from the old path instead of being recomputed.
+    pub async fn move_file(
+        &self,
+        workspace_id: &str,
+        from_path: &str,
+        to_path: &str,
+        workspace_path: &str,
+    ) -> AppResult<StatsDelta> {
+        let index_state = match self.existing_index(workspace_id)? {
+            Some(state) => state,
+            None => return Ok(StatsDelta::default()), // No index yet, skip
+        };
+
+        let _guard = self.writer_lock.lock().await;
+
+        // Skip if a full indexing is in progress
+        if index_state.is_indexing.load(Ordering::Acquire) {
+            return Ok(StatsDelta::default());
+        }
+
+        let ws_path_buf = PathBuf::from(workspace_path);
+        let from_abs = ws_path_buf.join(from_path);
+        let to_abs = ws_path_buf.join(to_path);
+        let from_relative = relative_path_of(&from_abs, &ws_path_buf);
+        let to_relative = relative_path_of(&to_abs, &ws_path_buf);
+        let from_key = path_key(&from_relative);
+        let to_key = path_key(&to_relative);
+        let from_size = self.indexed_file_size(workspace_id, &from_relative);
+        let to_size = self.indexed_file_size(workspace_id, &to_relative);
+        let mut size_after = None;
+
+        let mut writer: IndexWriter = index_state
+            .index
+            .writer_with_num_threads(1, INCREMENTAL_WRITER_BUDGET)
+            .map_err(|e| index_error("Failed to create writer", e))?;
+
+        writer.delete_term(tantivy::Term::from_field_text(index_state.schema.path_key, &from_key));
+        writer.delete_term(tantivy::Term::from_field_text(index_state.schema.path_key, &to_key));
+
+        let moved_hash = self
+            .content_hashes
+            .get_mut(workspace_id)
+            .and_then(|mut hashes| hashes.remove(&from_key));
+
+        let new_size = std::fs::metadata(&to_abs)
+            .map(|m| m.len())
+            .ok()
+            .filter(|&len| len <= self.max_file_size_for(workspace_id) as u64);
+        let indexable = to_abs.is_file() && self.is_indexable(&to_abs) && new_size.is_some();
+        let mut line_counts = self.read_line_counts(workspace_id);
+        line_counts.remove(&from_key);
+        line_counts.remove(&to_key);
+        if indexable {
+            let lines = Self::index_file(&index_state.schema, &mut writer, &to_abs, &ws_path_buf)?;
+            line_counts.insert(to_key.clone(), lines);
+            size_after = new_size;
+            let hash = match moved_hash {
+                Some(hash) => hash,
+                None => {
+                    let content = std::fs::read(&to_abs)?;
+                    format!("{:x}", Sha256::digest(&content))
+                }
+            };
+            self.content_hashes
+                .entry(workspace_id.to_string())
+                .or_default()
+                .insert(to_key, hash);
+        }
+        if let Err(e) = self.save_content_hashes(workspace_id) {
+            warn!("Failed to persist content hash after move: {}", e);
+        }
+        self.line_counts.insert(workspace_id.to_string(), line_counts);
+        self.save_line_counts(workspace_id);
+
+        writer.commit().map_err(|e| {
+            AppError::IndexError(format!("Failed to commit incremental index: {}", e))
+        })?;
+        drop(writer);
+
+        index_state.reader.reload().map_err(|e| {
+            AppError::IndexError(format!("Failed to reload reader: {}", e))
+        })?;
+
+        self.update_path_index(workspace_id, |paths| {
+            paths.remove(&relative_path_of(&from_abs, &ws_path_buf));
+            let to_relative = relative_path_of(&to_abs, &ws_path_buf);
+            match size_after {
+                Some(size) => {
+                    let modified = std::fs::metadata(&to_abs).map(|m| modified_secs(&m)).unwrap_or(0);
+                    paths.insert(to_relative, IndexedPathInfo { size, modified });
+                }
+                None => {
+                    paths.remove(&to_relative);
+                }
+            }
+        });
+
+        let delta = StatsDelta::replacing(from_size, None) + StatsDelta::replacing(to_size, size_after);
+        Self::apply_stats_delta(&index_state, delta);
+
+        info!("Incrementally moved file in index: {} -> {}", from_path, to_path);
+        Ok(delta)
+    }
+
+    /// Adjust the in-memory totals reported by `get_index_status` after an
+    /// incremental update, so they stay accurate between full passes.
+    fn apply_stats_delta(state: &IndexState, delta: StatsDelta) {
+        if delta.is_zero() {
+            return;
+        }
+        let adjust = |count: u64, by: i64| count.saturating_add_signed(by);
+        for counter in [&state.indexed_count, &state.total_count] {
+            let _ = counter.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+                Some(adjust(count as u64, delta.files) as usize)
+            });
+        }
+        let _ = state
+            .total_size_bytes
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bytes| {
+                Some(adjust(bytes, delta.bytes))
+            });
+    }
+
+    /// Stream every live document's stored fields as NDJSON rows, one `emit`
+    /// call per line. Stops early if `emit` returns false (client went away).
+    /// Runs synchronously — call from a blocking thread.
+    pub fn export_ndjson(
+        &self,
+        workspace_id: &str,
+        workspace_root: &Path,
+        fields: &[&str],
+        mut emit: impl FnMut(String) -> bool,
+    ) -> AppResult<usize> {
+        let state = self.get_or_create_index(workspace_id)?;
+        let searcher = state.reader.searcher();
+        let schema = &state.schema;
+
+        let mut exported = 0;
+        for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+            for doc_id in segment_reader.doc_ids_alive() {
+                let address = tantivy::DocAddress::new(segment_ord as u32, doc_id);
+                let doc: TantivyDocument = searcher
+                    .doc(address)
+                    .map_err(|e| AppError::IndexError(format!("Failed to read document: {}", e)))?;
+
+                let mut row = serde_json::Map::new();
+                for &name in fields {
+                    if name == "path" {
+                        // Not stored: rebuilt from the current root
+                        let path = doc
+                            .get_first(schema.relative_path)
+                            .and_then(|v| v.as_str())
+                            .map(|relative| workspace_root.join(relative).to_string_lossy().into_owned());
+                        row.insert(name.to_string(), path.map_or(serde_json::Value::Null, serde_json::Value::from));
+                        continue;
+                    }
+                    let value = schema
+                        .schema()
+                        .get_field(name)
+                        .ok()
+                        .and_then(|field| doc.get_first(field))
+                        .and_then(|v| {
+                            v.as_u64()
+                                .map(serde_json::Value::from)
+                                .or_else(|| v.as_str().map(serde_json::Value::from))
+                        });
+                    row.insert(name.to_string(), value.unwrap_or(serde_json::Value::Null));
+                }
+
+                let mut line = serde_json::to_string(&row)?;
+                line.push('\n');
+                if !emit(line) {
+                    return Ok(exported);
+                }
+                exported += 1;
+            }
+        }
+        Ok(exported)
+    }
+
+    /// Write the workspace's index directory (Tantivy files plus hash and line
+    /// count sidecars) as a gzipped tar bundle headed by a `BundleManifest`.
+    /// Holds the writer lock so no commit or merge changes files mid-copy.
+    /// Runs synchronously — call from a blocking thread.
+    pub fn export_bundle(
+        &self,
+        workspace_id: &str,
+        workspace_name: &str,
+        workspace_root: &str,
+        out: impl std::io::Write,
+    ) -> AppResult<BundleManifest> {
+        let state = self.get_or_create_index(workspace_id)?;
+        if state.is_indexing.load(Ordering::Acquire) {
+            return Err(AppError::Conflict(
+                "Indexing is in progress; export the bundle once it completes".into(),
+            ));
+        }
+        let _guard = self.writer_lock.blocking_lock();
+
+        let manifest = BundleManifest {
+            format_version: BUNDLE_FORMAT_VERSION,
+            schema_version: SCHEMA_VERSION,
+            store_content: self.store_content,
+            fold_accents: self.fold_accents_for(workspace_id),
+            backend_version: env!("CARGO_PKG_VERSION").to_string(),
+            workspace_name: workspace_name.to_string(),
+            source_root: workspace_root.to_string(),
+            document_count: state.reader.searcher().num_docs(),
+            created_at: chrono::Utc::now(),
+        };
+
+        let gz = flate2::write::GzEncoder::new(out, flate2::Compression::fast());
+        let mut tar = tar::Builder::new(gz);
+
+        let manifest_json = serde_json::to_vec_pretty(&manifest)?;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(manifest_json.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(manifest.created_at.timestamp().max(0) as u64);
+        tar.append_data(&mut header, BUNDLE_MANIFEST, manifest_json.as_slice())?;
+
+        for entry in std::fs::read_dir(self.index_dir(workspace_id))? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            // Lock files belong to this process, not the index.
+            if !entry.file_type()?.is_file() || name.starts_with(".tantivy-") {
+                continue;
+            }
+            tar.append_path_with_name(entry.path(), format!("{}/{}", BUNDLE_INDEX_DIR, name))?;
+        }
+
+        tar.into_inner()?.finish()?;
+        Ok(manifest)
+    }
+
+    /// Unpack an index bundle into `staging` (which must not exist yet) and
+    /// check it was produced by a compatible backend: bundle format, schema
+    /// version, content storage setting, and the Tantivy schema itself.
+    /// Only plain files directly under `index/` are extracted, at most
+    /// `MAX_BUNDLE_ENTRIES` entries and `max_unpacked_bytes` in total.
+    pub fn unpack_bundle(
+        &self,
+        bundle: impl std::io::Read,
+        staging: &Path,
+        max_unpacked_bytes: u64,
+    ) -> AppResult<BundleManifest> {
+        let bad_bundle = |msg: String| AppError::BadRequest(format!("Invalid index bundle: {}", msg));
+
+        std::fs::create_dir_all(staging)?;
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(bundle));
+        let mut manifest: Option<BundleManifest> = None;
+        let mut unpacked: u64 = 0;
+
+        for (count, entry) in archive.entries().map_err(|e| bad_bundle(e.to_string()))?.enumerate() {
+            if count >= MAX_BUNDLE_ENTRIES {
+                return Err(bad_bundle(format!("more than {} entries", MAX_BUNDLE_ENTRIES)));
+            }
+            let mut entry = entry.map_err(|e| bad_bundle(e.to_string()))?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let entry_path = entry.path().map_err(|e| bad_bundle(e.to_string()))?.into_owned();
+            let entry_name = entry_path.to_string_lossy().replace('\\', "/");
+
+            if entry_name == BUNDLE_MANIFEST {
+                manifest = Some(
+                    serde_json::from_reader(std::io::Read::take(&mut entry, MAX_BUNDLE_MANIFEST_BYTES))
+                        .map_err(|e| bad_bundle(format!("unreadable manifest: {}", e)))?,
+                );
+                continue;
+            }
+
+            let file_name = entry_name
+                .strip_prefix(BUNDLE_INDEX_DIR)
+                .and_then(|rest| rest.strip_prefix('/'))
+                .filter(|name| !name.is_empty() && !name.contains('/') && *name != "." && *name != "..")
+                .ok_or_else(|| bad_bundle(format!("unexpected entry '{}'", entry_name)))?;
+            // Read one byte past the budget, so running over it is detected
+            // without trusting the size in the tar header
+            let budget = max_unpacked_bytes - unpacked;
+            let mut file = std::fs::File::create(staging.join(file_name))?;
+            unpacked += std::io::copy(&mut std::io::Read::take(&mut entry, budget.saturating_add(1)), &mut file)
+                .map_err(|e| bad_bundle(e.to_string()))?;
+            if unpacked > max_unpacked_bytes {
+                return Err(AppError::FileTooLarge { size: unpacked, max: max_unpacked_bytes });
+            }
+        }
+
+        let manifest = manifest.ok_or_else(|| bad_bundle(format!("missing {}", BUNDLE_MANIFEST)))?;
+        if manifest.format_version != BUNDLE_FORMAT_VERSION {
+            return Err(bad_bundle(format!(
+                "bundle format {} is not supported (expected {})",
+                manifest.format_version, BUNDLE_FORMAT_VERSION
+            )));
+        }
+        if manifest.schema_version != SCHEMA_VERSION {
+            return Err(bad_bundle(format!(
+                "index schema version {} does not match this backend's {}",
+                manifest.schema_version, SCHEMA_VERSION
+            )));
+        }
+        if manifest.store_content != self.store_content {
+            return Err(bad_bundle(format!(
+                "bundle was built with index_store_content={}, this backend uses {}",
+                manifest.store_content, self.store_content
+            )));
+        }
+
+        let index = Index::open_in_dir(staging).map_err(|e| bad_bundle(e.to_string()))?;
+        if index.schema() != *IndexSchema::build(self.store_content, manifest.fold_accents).schema() {
+            return Err(bad_bundle("index schema differs from this backend's".to_string()));
+        }
+        Ok(manifest)
+    }
+
+    /// Move an unpacked bundle into place as the index of a newly created
+    /// workspace. Returns false (and discards the bundle) when it was built
+    /// with a different `fold_accents`; the next indexing pass then builds the
+    /// index from scratch. Otherwise that pass only re-reads files whose
+    /// content hash changed.
+    pub fn install_bundle(
+        &self,
+        workspace_id: &str,
+        staging: &Path,
+        manifest: &BundleManifest,
+    ) -> AppResult<bool> {
+        if manifest.fold_accents != self.fold_accents_for(workspace_id) {
+            warn!(
+                "Index bundle for {} was built with fold_accents={}; rebuilding instead of reusing it",
+                workspace_id, manifest.fold_accents
+            );
+            return Ok(false);
+        }
+
+        self.indexes.remove(workspace_id);
+        self.content_hashes.remove(workspace_id);
+        self.line_counts.remove(workspace_id);
+        self.path_indexes.remove(workspace_id);
+        let index_dir = self.index_dir(workspace_id);
+        if index_dir.exists() {
+            std::fs::remove_dir_all(&index_dir)?;
+        }
+        std::fs::rename(staging, &index_dir)?;
+        info!(
+            "Installed index bundle for {} ({} documents)",
+            workspace_id, manifest.document_count
+        );
+        Ok(true)
     }
 
     pub fn remove_index(&self, workspace_id: &str) -> AppResult<()> {
         self.indexes.remove(workspace_id);
+        self.max_file_size_overrides.remove(workspace_id);
+        self.fold_accents.remove(workspace_id);
         self.content_hashes.remove(workspace_id);
+        self.line_counts.remove(workspace_id);
+        self.path_indexes.remove(workspace_id);
         self.indexed_workspaces.remove(workspace_id);
+        self.index_errors.remove(workspace_id);
         let index_dir = self.index_dir(workspace_id);
         if index_dir.exists() {
             std::fs::remove_dir_all(&index_dir)?;
@@ -797,6 +2489,20 @@ impl IndexManager {
     }
 }
 
+/// Convert a Tantivy error into an `AppError`, singling out lock contention
+/// (another process holding the index writer) so callers get actionable guidance
+/// instead of an opaque 500.
+fn index_error(context: &str, e: tantivy::TantivyError) -> AppError {
+    match e {
+        tantivy::TantivyError::LockFailure(..) => AppError::IndexLocked(format!(
+            "{}: {}. Another vyotiq-backend instance is probably using the same data directory; \
+             stop it or set a different VYOTIQ_DATA_DIR",
+            context, e
+        )),
+        other => AppError::IndexError(format!("{}: {}", context, other)),
+    }
+}
+
 #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 pub struct IndexStatusResponse {
     pub indexed: bool,
@@ -804,12 +2510,70 @@ pub struct IndexStatusResponse {
     pub indexed_count: usize,
     pub total_count: usize,
     pub total_size_bytes: u64,
+    pub last_index_duration_ms: u64,
+    /// The last pass stopped at `max_indexed_files`; some files are not indexed.
+    pub truncated: bool,
+    /// Files the last pass could not read.
+    #[serde(default)]
+    pub skipped: SkippedFiles,
+    /// Most recent indexing error since the last completed pass.
+    pub last_error: Option<IndexingErrorRecord>,
 }
 
 // =============================================================================
 // Regex-based Symbol Extraction
 // =============================================================================
 
+/// Find TODO/FIXME/HACK/XXX markers in comments. A line counts as a comment
+/// from the first comment opener for its language (or a leading `*` inside a
+/// C-style block comment); languages without known comment syntax match on
+/// any line. Strings containing `//` can produce false positives — good
+/// enough for a tech-debt list.
+fn extract_markers(content: &str, language: &str) -> Vec<CodeMarker> {
+    use regex::Regex;
+    use std::sync::LazyLock;
+
+    static RE_MARKER: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"\b(TODO|FIXME|HACK|XXX)\b(?:\([^)]*\))?:?\s*(.*)").unwrap()
+    });
+
+    if !RE_MARKER.is_match(content) {
+        return Vec::new();
+    }
+
+    let prefixes = crate::lang::comment_prefixes(language);
+    let block_comments = prefixes.contains(&"/*");
+    let mut markers = Vec::new();
+
+    for (i, line) in content.lines().enumerate() {
+        let comment = if prefixes.is_empty() {
+            line
+        } else if let Some(at) = prefixes.iter().filter_map(|p| line.find(p)).min() {
+            &line[at..]
+        } else if block_comments && line.trim_start().starts_with('*') {
+            line
+        } else {
+            continue;
+        };
+
+        let Some(caps) = RE_MARKER.captures(comment) else { continue };
+        let text = caps[2]
+            .trim()
+            .trim_end_matches("*/")
+            .trim_end_matches("-->")
+            .trim_end();
+        markers.push(CodeMarker {
+            kind: caps[1].to_string(),
+            line: i as u64 + 1,
+            text: text.chars().take(MAX_MARKER_TEXT_CHARS).collect(),
+        });
+        if markers.len() >= MAX_MARKERS_PER_FILE {
+            break;
+        }
+    }
+    markers
+}
+
 /// Extract top-level symbol names from source code using regex patterns.
 /// Returns a space-separated string of symbol names for full-text indexing.
 /// Covers functions, classes, structs, interfaces, enums, types, traits, and impls
@@ -903,3 +2667,277 @@ fn extract_symbols(content: &str, language: &str) -> String {
 
     symbols.join(" ")
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn test_manager(base_dir: &Path) -> IndexManager {
+        let config = AppConfig {
+            index_read_threads: 1,
+            ..AppConfig::default()
+        };
+        IndexManager::new(base_dir.to_path_buf(), &config)
+    }
+
+    /// A `.tar.gz` with `entries` as (name, contents) plain files.
+    fn bundle_of(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut tar = tar::Builder::new(flate2::write::GzEncoder::new(
+            Vec::new(),
+            flate2::Compression::default(),
+        ));
+        for (name, contents) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(&mut header, name, *contents).unwrap();
+        }
+        tar.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn unpack_bundle_stops_past_the_unpacked_size_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = test_manager(&dir.path().join("indexes"));
+        // 4 MiB of zeros compresses to a few KiB
+        let zeros = vec![0u8; 4 * 1024 * 1024];
+        let bundle = bundle_of(&[("index/meta.json", &zeros)]);
+        assert!(bundle.len() < 64 * 1024);
+
+        let staging = dir.path().join("staging");
+        let err = manager
+            .unpack_bundle(bundle.as_slice(), &staging, 1024 * 1024)
+            .unwrap_err();
+        assert!(matches!(err, AppError::FileTooLarge { max, .. } if max == 1024 * 1024));
+        let written = std::fs::metadata(staging.join("meta.json")).unwrap().len();
+        assert!(written <= 1024 * 1024 + 1);
+    }
+
+    #[test]
+    fn unpack_bundle_rejects_too_many_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = test_manager(&dir.path().join("indexes"));
+        let names: Vec<String> = (0..=MAX_BUNDLE_ENTRIES).map(|i| format!("index/{}", i)).collect();
+        let entries: Vec<(&str, &[u8])> = names.iter().map(|name| (name.as_str(), &b""[..])).collect();
+        let bundle = bundle_of(&entries);
+
+        let err = manager
+            .unpack_bundle(bundle.as_slice(), &dir.path().join("staging"), u64::MAX)
+            .unwrap_err();
+        assert!(matches!(err, AppError::BadRequest(msg) if msg.contains("entries")));
+    }
+
+    /// Create `ws`'s index with a fresh manager and return its directory,
+    /// with a marker file that survives only if the index is kept.
+    fn created_index(base_dir: &Path) -> PathBuf {
+        test_manager(base_dir).get_or_create_index("ws").unwrap();
+        let index_path = base_dir.join("ws");
+        std::fs::write(index_path.join("marker"), "").unwrap();
+        index_path
+    }
+
+    #[test]
+    fn missing_schema_version_sidecar_rebuilds_the_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let index_path = created_index(dir.path());
+        std::fs::remove_file(index_path.join(SCHEMA_VERSION_FILE)).unwrap();
+
+        let schema = IndexSchema::build(crate::config::AppConfig::default().index_store_content, false);
+        let reason = IndexManager::open_existing_index(&index_path, &schema).unwrap().unwrap_err();
+        assert!(reason.contains("missing"), "{}", reason);
+
+        test_manager(dir.path()).get_or_create_index("ws").unwrap();
+        assert!(!index_path.join("marker").exists());
+        assert_eq!(
+            std::fs::read_to_string(index_path.join(SCHEMA_VERSION_FILE)).unwrap(),
+            SCHEMA_VERSION.to_string()
+        );
+    }
+
+    #[test]
+    fn mismatched_schema_version_rebuilds_the_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let index_path = created_index(dir.path());
+        std::fs::write(index_path.join(SCHEMA_VERSION_FILE), (SCHEMA_VERSION - 1).to_string()).unwrap();
+
+        let schema = IndexSchema::build(crate::config::AppConfig::default().index_store_content, false);
+        let reason = IndexManager::open_existing_index(&index_path, &schema).unwrap().unwrap_err();
+        assert!(reason.contains(&format!("schema version {}", SCHEMA_VERSION - 1)), "{}", reason);
+
+        test_manager(dir.path()).get_or_create_index("ws").unwrap();
+        assert!(!index_path.join("marker").exists());
+        assert_eq!(
+            std::fs::read_to_string(index_path.join(SCHEMA_VERSION_FILE)).unwrap(),
+            SCHEMA_VERSION.to_string()
+        );
+    }
+
+    #[test]
+    fn matching_schema_version_reopens_the_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let index_path = created_index(dir.path());
+
+        let schema = IndexSchema::build(crate::config::AppConfig::default().index_store_content, false);
+        assert!(IndexManager::open_existing_index(&index_path, &schema).unwrap().is_ok());
+
+        test_manager(dir.path()).get_or_create_index("ws").unwrap();
+        assert!(index_path.join("marker").exists());
+    }
+
+    /// A workspace at `<dir>/ws` with `files` (relative path, contents),
+    /// indexed by `manager` as `ws`.
+    pub(crate) async fn indexed_workspace(manager: &IndexManager, dir: &Path, files: &[(&str, &str)]) -> PathBuf {
+        let root = dir.join("ws");
+        for (path, contents) in files {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+        let (event_tx, _) = broadcast::channel(64);
+        manager
+            .index_workspace("ws", &root.to_string_lossy(), event_tx)
+            .await
+            .unwrap();
+        root
+    }
+
+    #[tokio::test]
+    async fn removing_a_directory_keeps_siblings_sharing_its_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = test_manager(&dir.path().join("indexes"));
+        let root = indexed_workspace(
+            &manager,
+            dir.path(),
+            &[("src/a/x.rs", "fn x() {}"), ("src/ab/y.rs", "fn y() {}"), ("src/a.rs", "fn a() {}")],
+        )
+        .await;
+        assert!(manager.indexed_file_size("ws", "src/a/x.rs").is_some());
+
+        std::fs::remove_dir_all(root.join("src/a")).unwrap();
+        manager
+            .remove_path_prefix("ws", "src/a", &root.to_string_lossy())
+            .await
+            .unwrap();
+
+        assert!(manager.indexed_file_size("ws", "src/a/x.rs").is_none());
+        assert!(manager.indexed_file_size("ws", "src/ab/y.rs").is_some());
+        assert!(manager.indexed_file_size("ws", "src/a.rs").is_some());
+    }
+
+    #[tokio::test]
+    async fn content_edit_with_unchanged_mtime_is_reindexed_and_checked() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = test_manager(&dir.path().join("indexes"));
+        let root = indexed_workspace(&manager, dir.path(), &[("src/a.rs", "fn a() {}")]).await;
+        let file = root.join("src/a.rs");
+        let old_hash = hash_file(&file).unwrap();
+        let mtime = std::fs::metadata(&file).unwrap().modified().unwrap();
+
+        // Same size, same mtime: only the content hash tells them apart
+        std::fs::write(&file, "fn b() {}").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+        let new_hash = hash_file(&file).unwrap();
+        assert_ne!(old_hash, new_hash);
+
+        let (event_tx, _) = broadcast::channel(64);
+        manager
+            .index_workspace("ws", &root.to_string_lossy(), event_tx)
+            .await
+            .unwrap();
+        let check = manager.check_content_hashes("ws", false).await.unwrap();
+        assert!(check.is_consistent(), "{:?}", check);
+        let state = manager.existing_index("ws").unwrap().unwrap();
+        assert_eq!(IndexManager::indexed_hashes(&state).unwrap()["src/a.rs"], new_hash);
+
+        // A sidecar still holding the old hash is reported and repaired
+        manager
+            .content_hashes
+            .insert("ws".to_string(), HashMap::from([("src/a.rs".to_string(), old_hash)]));
+        let check = manager.check_content_hashes("ws", false).await.unwrap();
+        assert_eq!(check.hash_mismatches, 1);
+        assert_eq!(check.sample_hash_mismatches, ["src/a.rs"]);
+        assert!(!check.repaired);
+
+        let check = manager.check_content_hashes("ws", true).await.unwrap();
+        assert!(check.repaired);
+        assert_eq!(manager.read_existing_hashes("ws").unwrap()["src/a.rs"], new_hash);
+        assert!(manager.check_content_hashes("ws", false).await.unwrap().is_consistent());
+    }
+
+    #[tokio::test]
+    async fn file_that_fails_to_reindex_is_retried_by_the_next_pass() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = test_manager(&dir.path().join("indexes"));
+        let root = indexed_workspace(&manager, dir.path(), &[("src/a.rs", "fn a() {}")]).await;
+        let ws_path = root.to_string_lossy();
+
+        // Not UTF-8, so the file can't be read as text
+        std::fs::write(root.join("src/a.rs"), b"fn a() {\xff}").unwrap();
+        manager.reindex_file("ws", "src/a.rs", &ws_path, "modify").await.unwrap();
+        assert!(!manager.read_existing_hashes("ws").unwrap().contains_key("src/a.rs"));
+
+        let (event_tx, mut event_rx) = broadcast::channel(64);
+        manager.index_workspace("ws", &ws_path, event_tx).await.unwrap();
+        let skipped = std::iter::from_fn(|| event_rx.try_recv().ok()).find_map(|event| match event {
+            ServerEvent::IndexingCompleted { skipped_files, .. } => Some(skipped_files),
+            _ => None,
+        });
+        assert_eq!(skipped, Some(1));
+    }
+
+    #[test]
+    fn path_key_uses_forward_slashes() {
+        assert_eq!(path_key("src\\net\\tcp.rs"), "src/net/tcp.rs");
+        assert_eq!(path_key("src/net/tcp.rs"), "src/net/tcp.rs");
+    }
+
+    #[cfg(not(any(target_os = "macos", windows)))]
+    #[tokio::test]
+    async fn case_variants_stay_distinct_on_case_sensitive_filesystems() {
+        assert_ne!(path_key("src/Foo.rs"), path_key("src/foo.rs"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let manager = test_manager(&dir.path().join("indexes"));
+        let root = indexed_workspace(
+            &manager,
+            dir.path(),
+            &[("src/Foo.rs", "struct Foo;"), ("src/foo.rs", "fn foo() {}")],
+        )
+        .await;
+        manager
+            .reindex_file("ws", "src/foo.rs", &root.to_string_lossy(), "modify")
+            .await
+            .unwrap();
+
+        let state = manager.existing_index("ws").unwrap().unwrap();
+        let mut keys: Vec<String> =
+            IndexManager::indexed_hashes(&state).unwrap().into_keys().collect();
+        keys.sort();
+        assert_eq!(keys, ["src/Foo.rs", "src/foo.rs"]);
+        assert_eq!(manager.read_existing_hashes("ws").unwrap().len(), 2);
+    }
+
+    #[cfg(any(target_os = "macos", windows))]
+    #[tokio::test]
+    async fn case_variant_event_replaces_the_indexed_document() {
+        assert_eq!(path_key("src/Foo.rs"), path_key("src/foo.rs"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let manager = test_manager(&dir.path().join("indexes"));
+        let root = indexed_workspace(&manager, dir.path(), &[("src/Foo.rs", "struct Foo;")]).await;
+        manager
+            .reindex_file("ws", "src/foo.rs", &root.to_string_lossy(), "modify")
+            .await
+            .unwrap();
+
+        let state = manager.existing_index("ws").unwrap().unwrap();
+        assert_eq!(IndexManager::indexed_hashes(&state).unwrap().len(), 1);
+        assert_eq!(manager.read_existing_hashes("ws").unwrap().len(), 1);
+    }
+}
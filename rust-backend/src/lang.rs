@@ -1,59 +1,209 @@
-/// Shared language detection utility.
-///
-/// Consolidated from 4 duplicate implementations across
-/// indexer.rs, watcher.rs, routes/files.rs, and routes/search.rs.
-/// Provides the most comprehensive language detection covering all supported extensions.
+//! Shared language detection utility.
+//!
+//! Consolidated from 4 duplicate implementations across
+//! indexer.rs, watcher.rs, routes/files.rs, and routes/search.rs.
+//! Provides the most comprehensive language detection covering all supported extensions.
+//!
+//! The extension is always checked first; only files whose extension is
+//! unknown fall back to their name (`Makefile.inc`, `setup.py.in`) and then
+//! to their first bytes (shebang line, `<?php`, `<?xml`).
+
+use std::io::Read;
+use std::path::Path;
+
+/// Language reported for extensions not listed in [`LANGUAGE_EXTENSIONS`].
+pub const DEFAULT_LANGUAGE: &str = "plaintext";
+
+/// Extensions (lowercase, without the dot) recognised for each language.
+/// Single source for [`detect_language`] and the `/api/languages` endpoint.
+pub const LANGUAGE_EXTENSIONS: &[(&str, &[&str])] = &[
+    ("typescript", &["ts", "tsx"]),
+    ("javascript", &["js", "jsx", "mjs", "cjs"]),
+    ("rust", &["rs"]),
+    ("python", &["py", "pyi", "pyw"]),
+    ("go", &["go"]),
+    ("java", &["java"]),
+    ("c", &["c", "h"]),
+    ("cpp", &["cpp", "hpp", "cc", "cxx", "hxx"]),
+    ("csharp", &["cs"]),
+    ("ruby", &["rb", "rake"]),
+    ("php", &["php"]),
+    ("swift", &["swift"]),
+    ("kotlin", &["kt", "kts"]),
+    ("scala", &["scala", "sc"]),
+    ("html", &["html", "htm"]),
+    ("css", &["css"]),
+    ("scss", &["scss", "sass", "less"]),
+    ("json", &["json", "jsonc"]),
+    ("yaml", &["yaml", "yml"]),
+    ("toml", &["toml"]),
+    ("xml", &["xml", "xsl", "xslt"]),
+    ("markdown", &["md", "mdx", "rst"]),
+    ("sql", &["sql"]),
+    ("graphql", &["graphql", "gql"]),
+    ("shell", &["sh", "bash", "zsh", "fish"]),
+    ("powershell", &["ps1", "psm1", "psd1"]),
+    ("batch", &["bat", "cmd"]),
+    ("vue", &["vue"]),
+    ("svelte", &["svelte"]),
+    ("astro", &["astro"]),
+    ("lua", &["lua"]),
+    ("zig", &["zig"]),
+    ("nim", &["nim"]),
+    ("dart", &["dart"]),
+    ("elixir", &["elixir", "ex", "exs"]),
+    ("erlang", &["erl", "hrl"]),
+    ("r", &["r"]),
+    ("julia", &["jl"]),
+    ("clojure", &["clj", "cljs", "cljc"]),
+    ("hcl", &["tf", "hcl"]),
+    ("protobuf", &["proto"]),
+    ("dockerfile", &["dockerfile"]),
+    ("makefile", &["makefile"]),
+    ("ini", &["ini", "cfg", "conf"]),
+    ("dotenv", &["env"]),
+    ("plaintext", &["txt"]),
+];
+
+/// Bytes read from the start of a file when sniffing its language.
+const SNIFF_BYTES: usize = 512;
+
+/// Suffixes of template/sample copies of another file (`config.h.in`,
+/// `settings.py.example`); the language is taken from the name underneath.
+const TEMPLATE_SUFFIXES: &[&str] = &["in", "tpl", "template", "dist", "example", "sample"];
 
 /// Detect programming language from file extension.
 /// Returns a static string identifier for the language.
 pub fn detect_language(ext: &str) -> &'static str {
-    match ext.to_lowercase().as_str() {
-        "ts" | "tsx" => "typescript",
-        "js" | "jsx" | "mjs" | "cjs" => "javascript",
-        "rs" => "rust",
-        "py" | "pyi" | "pyw" => "python",
-        "go" => "go",
-        "java" => "java",
-        "c" | "h" => "c",
-        "cpp" | "hpp" | "cc" | "cxx" | "hxx" => "cpp",
-        "cs" => "csharp",
-        "rb" | "rake" => "ruby",
-        "php" => "php",
-        "swift" => "swift",
-        "kt" | "kts" => "kotlin",
-        "scala" | "sc" => "scala",
-        "html" | "htm" => "html",
-        "css" => "css",
-        "scss" | "sass" | "less" => "scss",
-        "json" | "jsonc" => "json",
-        "yaml" | "yml" => "yaml",
-        "toml" => "toml",
-        "xml" | "xsl" | "xslt" => "xml",
-        "md" | "mdx" | "rst" => "markdown",
-        "sql" => "sql",
-        "graphql" | "gql" => "graphql",
-        "sh" | "bash" | "zsh" | "fish" => "shell",
-        "ps1" | "psm1" | "psd1" => "powershell",
-        "bat" | "cmd" => "batch",
-        "vue" => "vue",
-        "svelte" => "svelte",
-        "astro" => "astro",
-        "lua" => "lua",
-        "zig" => "zig",
-        "nim" => "nim",
-        "dart" => "dart",
-        "elixir" | "ex" | "exs" => "elixir",
-        "erl" | "hrl" => "erlang",
-        "r" => "r",
-        "jl" => "julia",
-        "clj" | "cljs" | "cljc" => "clojure",
-        "tf" | "hcl" => "hcl",
-        "proto" => "protobuf",
-        "dockerfile" => "dockerfile",
-        "makefile" => "makefile",
-        "ini" | "cfg" | "conf" => "ini",
-        "env" => "dotenv",
-        "txt" => "plaintext",
-        _ => "plaintext",
+    language_for_extension(ext).unwrap_or(DEFAULT_LANGUAGE)
+}
+
+/// Language for an extension listed in [`LANGUAGE_EXTENSIONS`], if any.
+pub fn language_for_extension(ext: &str) -> Option<&'static str> {
+    let ext = ext.to_lowercase();
+    LANGUAGE_EXTENSIONS
+        .iter()
+        .find(|(_, exts)| exts.contains(&ext.as_str()))
+        .map(|(language, _)| *language)
+}
+
+/// Language of a file whose content is already in memory: extension, then
+/// file name, then the content itself.
+pub fn detect_file_language(path: &Path, content: &str) -> &'static str {
+    detect_by_path(path)
+        .or_else(|| detect_language_from_content(content))
+        .unwrap_or(DEFAULT_LANGUAGE)
+}
+
+/// Language of a file whose extension is not recognised, from its name or,
+/// failing that, its first few hundred bytes. `None` when nothing matches or
+/// the file can't be read.
+pub fn sniff_language(path: &Path) -> Option<&'static str> {
+    detect_by_path(path).or_else(|| {
+        let mut head = Vec::with_capacity(SNIFF_BYTES);
+        std::fs::File::open(path)
+            .ok()?
+            .take(SNIFF_BYTES as u64)
+            .read_to_end(&mut head)
+            .ok()?;
+        // The cut may split a multi-byte character; only the start matters
+        detect_language_from_content(&String::from_utf8_lossy(&head))
+    })
+}
+
+fn detect_by_path(path: &Path) -> Option<&'static str> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    detect_language_from_name(&name)
+}
+
+/// Language from a lowercase file name: its extension, a well-known build
+/// file name, or the extension under a template suffix.
+fn detect_language_from_name(name: &str) -> Option<&'static str> {
+    // Like `Path::extension`, a leading dot doesn't start an extension
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, ext),
+        _ => (name, ""),
+    };
+    if let Some(language) = language_for_extension(ext) {
+        return Some(language);
+    }
+    match stem {
+        "makefile" | "gnumakefile" => return Some("makefile"),
+        "dockerfile" | "containerfile" => return Some("dockerfile"),
+        "rakefile" | "gemfile" => return Some("ruby"),
+        _ => {}
+    }
+    if TEMPLATE_SUFFIXES.contains(&ext) {
+        return detect_language_from_name(stem);
+    }
+    None
+}
+
+/// Language from the start of a file: the interpreter named on a shebang
+/// line, or a leading `<?php` / `<?xml` / HTML doctype.
+pub fn detect_language_from_content(content: &str) -> Option<&'static str> {
+    let first_line = content.trim_start_matches('\u{feff}').lines().next()?;
+    if let Some(shebang) = first_line.strip_prefix("#!") {
+        return language_for_interpreter(shebang);
+    }
+    let start = first_line.trim_start().to_lowercase();
+    if start.starts_with("<?php") {
+        Some("php")
+    } else if start.starts_with("<?xml") {
+        Some("xml")
+    } else if start.starts_with("<!doctype html") || start.starts_with("<html") {
+        Some("html")
+    } else {
+        None
+    }
+}
+
+/// `/usr/bin/python3.11`, `/usr/bin/env -S node --flag`, `/bin/bash -e`.
+fn language_for_interpreter(shebang: &str) -> Option<&'static str> {
+    let mut words = shebang.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = words.find(|w| !w.starts_with('-') && !w.contains('='))?;
+        program = program.rsplit('/').next()?;
+    }
+    let program = program
+        .trim_end_matches(|c: char| c.is_ascii_digit() || c == '.')
+        .to_lowercase();
+    match program.as_str() {
+        "python" | "pypy" => Some("python"),
+        "sh" | "bash" | "zsh" | "fish" | "dash" | "ksh" | "ash" => Some("shell"),
+        "node" | "nodejs" | "bun" => Some("javascript"),
+        "deno" | "ts-node" | "tsx" => Some("typescript"),
+        "ruby" => Some("ruby"),
+        "php" => Some("php"),
+        "lua" | "luajit" => Some("lua"),
+        "pwsh" | "powershell" => Some("powershell"),
+        "rscript" => Some("r"),
+        "julia" => Some("julia"),
+        "elixir" => Some("elixir"),
+        "escript" => Some("erlang"),
+        "make" => Some("makefile"),
+        _ => None,
+    }
+}
+
+/// Comment openers for a language, used to find TODO-style markers.
+/// An empty slice means we know no comment syntax for the language
+/// (plain text, markdown, JSON) and every line is treated as comment-ish.
+pub fn comment_prefixes(language: &str) -> &'static [&'static str] {
+    match language {
+        "typescript" | "javascript" | "rust" | "go" | "java" | "c" | "cpp" | "csharp"
+        | "swift" | "kotlin" | "scala" | "dart" | "zig" | "protobuf" | "scss" => &["//", "/*"],
+        "css" => &["/*"],
+        "php" | "hcl" => &["//", "/*", "#"],
+        "python" | "ruby" | "shell" | "powershell" | "elixir" | "r" | "julia" | "yaml"
+        | "toml" | "dotenv" | "makefile" | "dockerfile" | "nim" | "graphql" => &["#"],
+        "sql" | "lua" => &["--"],
+        "html" | "xml" => &["<!--"],
+        "vue" | "svelte" | "astro" => &["<!--", "//", "/*"],
+        "clojure" | "ini" => &[";", "#"],
+        "erlang" => &["%"],
+        "batch" => &["REM", "::"],
+        _ => &[],
     }
 }
@@ -0,0 +1,101 @@
+use crate::error::{AppError, AppResult};
+use std::fs::{File, TryLockError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Name of the lock file created inside the data directory.
+const LOCK_FILE_NAME: &str = "vyotiq-backend.lock";
+
+/// Exclusive ownership of a data directory for the lifetime of the process.
+/// Two backends sharing one data dir would fight over Tantivy's writer locks,
+/// so the second instance refuses to start instead of failing every request.
+///
+/// Backed by an OS advisory lock, which the kernel releases if the process dies,
+/// so a crash never leaves the directory locked. The owner's PID is written into
+/// the file purely for diagnostics.
+pub struct DataDirLock {
+    path: PathBuf,
+    _file: File,
+}
+
+impl DataDirLock {
+    /// Take the lock. If another process holds it and `recover_stale` is set,
+    /// a lock whose recorded owner is no longer running (e.g. left behind on a
+    /// network filesystem without working advisory locks) is deleted and
+    /// re-acquired; otherwise an `IndexLocked` error is returned.
+    pub fn acquire(data_dir: &Path, recover_stale: bool) -> AppResult<Self> {
+        std::fs::create_dir_all(data_dir)?;
+        let path = data_dir.join(LOCK_FILE_NAME);
+
+        if let Some(lock) = Self::try_lock(&path)? {
+            return Ok(lock);
+        }
+
+        let owner = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| s.trim().parse::<u32>().ok());
+
+        if recover_stale && owner.is_some_and(|pid| !is_process_alive(pid)) {
+            warn!(
+                "Removing stale data dir lock {} (owner PID {} is not running)",
+                path.display(),
+                owner.unwrap_or_default()
+            );
+            std::fs::remove_file(&path)?;
+            if let Some(lock) = Self::try_lock(&path)? {
+                return Ok(lock);
+            }
+        }
+
+        let owner_desc = owner
+            .map(|pid| format!("PID {}", pid))
+            .unwrap_or_else(|| "an unknown process".to_string());
+        Err(AppError::IndexLocked(format!(
+            "data directory {} is already in use by {}. Stop the other backend instance, \
+             point this one at a different VYOTIQ_DATA_DIR, or set VYOTIQ_RECOVER_STALE_LOCK=1 \
+             to remove the lock if that process has exited",
+            data_dir.display(),
+            owner_desc
+        )))
+    }
+
+    /// Returns `Ok(None)` when another process currently holds the lock.
+    fn try_lock(path: &Path) -> AppResult<Option<Self>> {
+        let mut file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => return Ok(None),
+            Err(TryLockError::Error(e)) => return Err(e.into()),
+        }
+        file.set_len(0)?;
+        write!(file, "{}", std::process::id())?;
+        info!("Acquired data dir lock {}", path.display());
+        Ok(Some(Self {
+            path: path.to_path_buf(),
+            _file: file,
+        }))
+    }
+}
+
+impl Drop for DataDirLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Best-effort liveness check. Only Linux exposes this cheaply (via /proc);
+/// elsewhere we conservatively assume the owner is still running.
+fn is_process_alive(pid: u32) -> bool {
+    let proc_root = Path::new("/proc");
+    if proc_root.join("self").exists() {
+        proc_root.join(pid.to_string()).exists()
+    } else {
+        true
+    }
+}
@@ -0,0 +1,164 @@
+//! Tracing output layers for stdout (captured by the Electron sidecar) and
+//! the rotated log files.
+
+use tracing::Subscriber;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{reload, EnvFilter, Layer, Registry};
+
+/// Filter used when `RUST_LOG` is unset or invalid.
+pub const DEFAULT_LOG_FILTER: &str = "vyotiq_backend=info,tower_http=info";
+
+/// Handle to the active `EnvFilter`, which `/admin/log-level` swaps at runtime.
+pub type LogFilterHandle = reload::Handle<EnvFilter, Registry>;
+
+/// The filter from `RUST_LOG` (or `DEFAULT_LOG_FILTER`) as a reloadable
+/// layer, plus the handle that replaces it.
+pub fn reloadable_filter() -> (reload::Layer<EnvFilter, Registry>, LogFilterHandle) {
+    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| DEFAULT_LOG_FILTER.into());
+    reload::Layer::new(env_filter)
+}
+
+/// Formatter of the log layers, set with `VYOTIQ_LOG_FORMAT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// One line per event; the default, easiest to read during development.
+    #[default]
+    Compact,
+    /// Multi-line, human-oriented output.
+    Pretty,
+    /// One JSON object per event, including the current span and its parents
+    /// with their fields (e.g. `workspace_id`), for log aggregation.
+    Json,
+}
+
+impl std::str::FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "compact" => Ok(Self::Compact),
+            "pretty" => Ok(Self::Pretty),
+            "json" => Ok(Self::Json),
+            other => Err(format!("unknown log format '{}'", other)),
+        }
+    }
+}
+
+impl LogFormat {
+    /// `VYOTIQ_LOG_FORMAT`, or compact when unset or unrecognised. Read
+    /// before the config is loaded so startup messages use the chosen format.
+    pub fn from_env() -> Self {
+        std::env::var("VYOTIQ_LOG_FORMAT")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or_default()
+    }
+}
+
+/// A fmt layer writing to `writer` in `format`, with target, thread id and
+/// source location on every event.
+pub fn fmt_layer<S, W>(format: LogFormat, writer: W, ansi: bool) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer()
+        .with_target(true)
+        .with_thread_ids(true)
+        .with_file(true)
+        .with_line_number(true)
+        .with_ansi(ansi)
+        .with_writer(writer);
+    match format {
+        LogFormat::Compact => layer.compact().boxed(),
+        LogFormat::Pretty => layer.pretty().boxed(),
+        LogFormat::Json => layer.json().with_current_span(true).with_span_list(true).boxed(),
+    }
+}
+
+/// File name prefix of the daily rotated logs (`<prefix>.YYYY-MM-DD`).
+pub const LOG_FILE_PREFIX: &str = "vyotiq-backend.log";
+
+/// Default `VYOTIQ_LOG_RETENTION_DAYS`.
+const DEFAULT_LOG_RETENTION_DAYS: u64 = 14;
+
+/// How often the background task prunes the log directory.
+pub const LOG_PRUNE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+/// Limits applied to the rotated log files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogRetention {
+    /// Delete log files last written more than this many days ago (0 = keep all).
+    pub max_age_days: u64,
+    /// Delete the oldest log files while the directory's logs exceed this
+    /// many bytes (0 = no cap). The newest file is always kept.
+    pub max_total_bytes: u64,
+}
+
+impl LogRetention {
+    /// `VYOTIQ_LOG_RETENTION_DAYS` (default 14) and `VYOTIQ_LOG_MAX_TOTAL_MB`
+    /// (default 0, no size cap).
+    pub fn from_env() -> Self {
+        let env_u64 = |key: &str| std::env::var(key).ok().and_then(|v| v.trim().parse::<u64>().ok());
+        Self {
+            max_age_days: env_u64("VYOTIQ_LOG_RETENTION_DAYS").unwrap_or(DEFAULT_LOG_RETENTION_DAYS),
+            max_total_bytes: env_u64("VYOTIQ_LOG_MAX_TOTAL_MB").unwrap_or(0).saturating_mul(1024 * 1024),
+        }
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.max_age_days == 0 && self.max_total_bytes == 0
+    }
+}
+
+/// Delete rotated log files in `log_dir` beyond `retention`, oldest first.
+/// Only files named after `LOG_FILE_PREFIX` are touched. Returns how many
+/// files were removed; failures are logged and skipped.
+pub fn prune_logs(log_dir: &std::path::Path, retention: LogRetention) -> usize {
+    if retention.is_unlimited() {
+        return 0;
+    }
+    let Ok(entries) = std::fs::read_dir(log_dir) else {
+        return 0;
+    };
+
+    // (modified, size, path), newest first
+    let mut logs: Vec<(std::time::SystemTime, u64, std::path::PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(LOG_FILE_PREFIX))
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok().filter(|m| m.is_file())?;
+            Some((metadata.modified().ok()?, metadata.len(), entry.path()))
+        })
+        .collect();
+    logs.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| b.2.cmp(&a.2)));
+
+    let cutoff = (retention.max_age_days > 0)
+        .then(|| {
+            let max_age = std::time::Duration::from_secs(retention.max_age_days.saturating_mul(24 * 60 * 60));
+            std::time::SystemTime::now().checked_sub(max_age)
+        })
+        .flatten();
+    let mut kept_bytes = 0u64;
+    let mut removed = 0;
+    for (index, (modified, size, path)) in logs.into_iter().enumerate() {
+        let too_old = cutoff.is_some_and(|cutoff| modified < cutoff);
+        let over_cap = retention.max_total_bytes > 0 && kept_bytes + size > retention.max_total_bytes;
+        // The newest file is the one being written to
+        if index > 0 && (too_old || over_cap) {
+            match std::fs::remove_file(&path) {
+                Ok(()) => {
+                    removed += 1;
+                    continue;
+                }
+                Err(e) => tracing::warn!("Failed to remove old log file {}: {}", path.display(), e),
+            }
+        }
+        kept_bytes += size;
+    }
+    if removed > 0 {
+        tracing::info!("Pruned {} old log file(s) from {}", removed, log_dir.display());
+    }
+    removed
+}
@@ -3,14 +3,20 @@ use std::net::SocketAddr;
 use tracing::{info, error};
 use tracing_subscriber::prelude::*;
 
+mod changes;
 mod config;
 mod error;
+mod git;
+mod history;
 mod indexer;
 mod lang;
+mod lock;
+mod logging;
 mod routes;
 mod search;
 mod server;
 mod state;
+mod tokenizer;
 mod watcher;
 mod workspace;
 
@@ -25,30 +31,21 @@ async fn main() -> Result<()> {
     std::fs::create_dir_all(&log_dir).ok();
 
     // File appender: daily rotated log files
-    let file_appender = tracing_appender::rolling::daily(&log_dir, "vyotiq-backend.log");
+    let file_appender = tracing_appender::rolling::daily(&log_dir, logging::LOG_FILE_PREFIX);
     let (non_blocking_writer, _guard) = tracing_appender::non_blocking(file_appender);
 
-    // Build tracing subscriber with both stdout + file output using layers
-    let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| "vyotiq_backend=info,tower_http=info".into());
+    // Build tracing subscriber with both stdout + file output using layers.
+    // The filter is reloadable so /admin/log-level can change it at runtime.
+    let (env_filter, log_filter) = logging::reloadable_filter();
+
+    let log_format = logging::LogFormat::from_env();
+    let log_retention = logging::LogRetention::from_env();
 
     // Stdout layer (captured by Electron sidecar)
-    let stdout_layer = tracing_subscriber::fmt::layer()
-        .with_target(true)
-        .with_thread_ids(true)
-        .with_file(true)
-        .with_line_number(true)
-        .compact();
+    let stdout_layer = logging::fmt_layer(log_format, std::io::stdout, true);
 
     // File layer (daily rotated, non-blocking)
-    let file_layer = tracing_subscriber::fmt::layer()
-        .with_target(true)
-        .with_thread_ids(true)
-        .with_file(true)
-        .with_line_number(true)
-        .with_ansi(false)
-        .with_writer(non_blocking_writer)
-        .compact();
+    let file_layer = logging::fmt_layer(log_format, non_blocking_writer, false);
 
     tracing_subscriber::registry()
         .with(env_filter)
@@ -83,17 +80,75 @@ async fn main() -> Result<()> {
         max_index_size_mb = config.max_index_size_mb,
         max_file_size_bytes = config.max_file_size_bytes,
         max_indexed_files = config.max_indexed_files,
+        max_search_results = config.max_search_results,
+        index_store_content = config.index_store_content,
+        index_lockfiles = config.index_lockfiles,
         watcher_debounce_ms = config.watcher_debounce_ms,
+        watcher_batch_threshold = config.watcher_batch_threshold,
+        watcher_mode = ?config.watcher_mode,
+        watch_dirs = ?config.watch_dirs,
+        watch_skip_excluded_dirs = config.watch_skip_excluded_dirs,
         index_batch_size = config.index_batch_size,
+        index_read_threads = config.index_read_threads,
+        index_commit_interval = config.index_commit_interval,
+        max_loaded_indexes = config.max_loaded_indexes,
+        event_channel_capacity = config.event_channel_capacity,
+        compression_algorithms = ?config.compression_algorithms,
+        compression_level = ?config.compression_level,
         log_dir = %log_dir,
+        log_format = ?log_format,
+        log_retention = ?log_retention,
         "Vyotiq backend starting"
     );
 
-    let app_state = state::AppState::new(config).await?;
+    // Prune rotated logs now and once a day, so always-on installs don't
+    // fill the disk
+    if !log_retention.is_unlimited() {
+        let log_dir = std::path::PathBuf::from(&log_dir);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(logging::LOG_PRUNE_INTERVAL);
+            loop {
+                interval.tick().await;
+                let dir = log_dir.clone();
+                let _ = tokio::task::spawn_blocking(move || logging::prune_logs(&dir, log_retention)).await;
+            }
+        });
+    }
+
+    // Fail fast if another backend already owns this data dir — otherwise every
+    // index write would later fail with a Tantivy lock error.
+    let _data_dir_lock = match lock::DataDirLock::acquire(
+        std::path::Path::new(&config.data_dir),
+        config.recover_stale_lock,
+    ) {
+        Ok(lock) => lock,
+        Err(e) => {
+            error!("Cannot start: {}", e);
+            return Err(e.into());
+        }
+    };
+
+    let app_state = state::AppState::new(config, log_filter).await?;
 
     // Initialize the shutdown notify channel for graceful HTTP-based shutdown
     routes::health::init_shutdown_notify();
 
+    // Write coalesced workspace changes in the background; the final flush
+    // happens after the server stops
+    let persist_state = app_state.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(workspace::PERSIST_INTERVAL);
+        loop {
+            interval.tick().await;
+            let manager = persist_state.workspace_manager.clone();
+            match tokio::task::spawn_blocking(move || manager.flush()).await {
+                Ok(Err(e)) => tracing::warn!("Failed to save workspaces: {}", e),
+                Err(e) => tracing::warn!("Workspace save task failed: {}", e),
+                Ok(Ok(())) => {}
+            }
+        }
+    });
+
     let _app_state_shutdown = app_state.clone();
     let app = server::create_app(app_state.clone());
 
@@ -117,6 +172,14 @@ async fn main() -> Result<()> {
         let total = workspaces.len();
         let mut restored = 0;
         for ws in &workspaces {
+            let available = watcher_state
+                .workspace_manager
+                .refresh_availability(&ws.id)
+                .map_or(true, |(ws, _)| ws.available);
+            if !available {
+                tracing::warn!("Not watching workspace {} ({}): root {} no longer exists", ws.name, ws.id, ws.path);
+                continue;
+            }
             if let Err(e) = watcher_state.watcher_manager.start_watching(
                 &ws.id,
                 &ws.path,
@@ -134,14 +197,17 @@ async fn main() -> Result<()> {
     });
 
     axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
+        .with_graceful_shutdown(shutdown_signal(app_state.event_tx.clone()))
         .await?;
 
+    if let Err(e) = app_state.workspace_manager.flush() {
+        error!("Failed to save workspaces on shutdown: {}", e);
+    }
     info!("Vyotiq backend shutdown complete");
     Ok(())
 }
 
-async fn shutdown_signal() {
+async fn shutdown_signal(event_tx: tokio::sync::broadcast::Sender<state::ServerEvent>) {
     let ctrl_c = async {
         tokio::signal::ctrl_c()
             .await
@@ -175,4 +241,5 @@ async fn shutdown_signal() {
     }
 
     info!("Shutdown signal received");
+    let _ = event_tx.send(state::ServerEvent::ServerShuttingDown);
 }
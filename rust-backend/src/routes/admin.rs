@@ -0,0 +1,94 @@
+use axum::{extract::State, Extension, Json};
+use serde::Deserialize;
+
+use crate::error::{AppError, AppResult};
+use crate::server::BootstrapAuthorized;
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct RotateTokenRequest {
+    pub token: String,
+}
+
+/// Replace the global auth token without a restart. The request itself must
+/// authenticate with the current token; requests already past the auth check
+/// finish normally, later ones need the new token. Setting the first global
+/// token takes the bootstrap token (`VYOTIQ_BOOTSTRAP_TOKEN`), which is
+/// refused once a global token exists. The new token is not persisted: a
+/// restart goes back to `VYOTIQ_AUTH_TOKEN`.
+pub async fn rotate_token(
+    State(state): State<AppState>,
+    bootstrap: Option<Extension<BootstrapAuthorized>>,
+    Json(req): Json<RotateTokenRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    let token = req.token.trim();
+    if token.is_empty() {
+        return Err(AppError::BadRequest("Token must not be empty".into()));
+    }
+    if token.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(AppError::BadRequest(
+            "Token must not contain whitespace or control characters".into(),
+        ));
+    }
+
+    // Checked under the write lock: two bootstrap requests that both passed
+    // auth before either set a token must not both succeed
+    let mut current = state.auth_token_hash.write();
+    match (current.is_some(), bootstrap.is_some()) {
+        (false, false) => {
+            return Err(AppError::Forbidden(
+                "No global token is set; setting one requires the bootstrap token".into(),
+            ));
+        }
+        (true, true) => {
+            return Err(AppError::Forbidden(
+                "A global token is already set; rotating it requires the current token".into(),
+            ));
+        }
+        _ => {}
+    }
+    *current = Some(crate::workspace::hash_auth_token(token));
+    drop(current);
+    tracing::info!("Global auth token rotated");
+
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+/// The log filter currently in effect, in `RUST_LOG` syntax.
+pub async fn get_log_level(State(state): State<AppState>) -> AppResult<Json<serde_json::Value>> {
+    let filter = state
+        .log_filter
+        .with_current(|filter| filter.to_string())
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Log filter unavailable: {}", e)))?;
+
+    Ok(Json(serde_json::json!({ "filter": filter })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LogLevelRequest {
+    /// `RUST_LOG` syntax, e.g. `vyotiq_backend=debug,tower_http=info`.
+    pub filter: String,
+}
+
+/// Replace the log filter without a restart. Not persisted: a restart goes
+/// back to `RUST_LOG`.
+pub async fn set_log_level(
+    State(state): State<AppState>,
+    Json(req): Json<LogLevelRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    let filter = req.filter.trim();
+    if filter.is_empty() {
+        return Err(AppError::BadRequest("Filter must not be empty".into()));
+    }
+    let env_filter = tracing_subscriber::EnvFilter::try_new(filter)
+        .map_err(|e| AppError::BadRequest(format!("Invalid log filter: {}", e)))?;
+    let active = env_filter.to_string();
+
+    state
+        .log_filter
+        .reload(env_filter)
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to reload log filter: {}", e)))?;
+    tracing::info!(filter = %active, "Log filter changed");
+
+    Ok(Json(serde_json::json!({ "filter": active })))
+}
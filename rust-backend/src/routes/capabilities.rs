@@ -0,0 +1,47 @@
+use axum::{extract::State, Json};
+use serde_json::{json, Value};
+
+use crate::config::SUPPORTED_EXTENSIONS;
+use crate::indexer::SCHEMA_VERSION;
+use crate::lang::LANGUAGE_EXTENSIONS;
+use crate::state::AppState;
+
+/// What this server supports, so clients can feature-detect instead of
+/// parsing version strings. Served behind auth like every route except
+/// `/health`: it exposes configured limits, and any client able to use the
+/// features already holds the token. New keys may be added; existing ones
+/// keep their meaning.
+pub async fn capabilities(State(state): State<AppState>) -> Json<Value> {
+    let config = &state.config;
+
+    Json(json!({
+        "service": "vyotiq-backend",
+        "version": env!("CARGO_PKG_VERSION"),
+        "schema_version": SCHEMA_VERSION,
+        "limits": {
+            "max_file_size_bytes": config.max_file_size_bytes,
+            "max_indexed_files": config.max_indexed_files,
+            "max_search_results": config.max_search_results,
+            "max_import_bundle_bytes": config.max_import_bundle_bytes,
+            "max_import_unpacked_bytes": config.max_import_unpacked_bytes,
+        },
+        "supported_extensions": SUPPORTED_EXTENSIONS.len(),
+        "languages": LANGUAGE_EXTENSIONS.len(),
+        "compression": config.compression_algorithms,
+        "features": {
+            "fulltext_search": true,
+            "grep": true,
+            "global_search": true,
+            "semantic_search": false,
+            "embedding_model": Value::Null,
+            "git": true,
+            "file_watcher": config.enable_file_watcher,
+            "multiple_active_workspaces": config.multiple_active_workspaces,
+            "stored_content": config.index_store_content,
+            "websocket_events": true,
+            "sse": false,
+            "metrics": false,
+            "index_bundles": true,
+        },
+    }))
+}
@@ -1,14 +1,17 @@
 use axum::{
     extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 use tracing::{info, warn, debug, instrument};
 
 use crate::error::{AppError, AppResult};
 use crate::state::AppState;
-use crate::workspace::FileEntry;
+use crate::workspace::{EntryTypeFilter, FileEntry, ListOptions, ListSortKey, SortDirection, TreeNode};
 
 #[derive(Debug, Deserialize)]
 pub struct ListFilesQuery {
@@ -20,6 +23,37 @@ pub struct ListFilesQuery {
     pub show_hidden: Option<bool>,
     #[serde(default)]
     pub max_depth: Option<usize>,
+    /// `name` (default), `size` or `modified`; directories always come first.
+    #[serde(default)]
+    pub sort_by: ListSortKey,
+    /// `asc` (default) or `desc`.
+    #[serde(default)]
+    pub sort_dir: SortDirection,
+    /// `all` (default), `files` or `dirs`.
+    #[serde(default)]
+    pub entry_type: EntryTypeFilter,
+    /// Paging over the sorted listing. When either is set the response is
+    /// `{ entries, total, offset, limit }` instead of a bare array.
+    #[serde(default)]
+    pub offset: Option<usize>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// List from the index's in-memory path list instead of the filesystem
+    /// (see `workspace::list_indexed`). Falls back to reading the directory
+    /// until the workspace has been indexed.
+    #[serde(default)]
+    pub cached: bool,
+    /// Attach each entry's `git_status` (one git status call per request).
+    #[serde(default)]
+    pub git: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TreeQuery {
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub max_depth: Option<usize>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -37,6 +71,10 @@ pub struct ReadFileQuery {
 pub struct WriteFileRequest {
     pub path: String,
     pub content: String,
+    /// Optimistic lock: SHA-256 the caller last read. The write is rejected
+    /// with 409 if the file on disk no longer has this hash.
+    #[serde(default)]
+    pub expected_hash: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -64,12 +102,41 @@ pub struct SearchFilesRequest {
     pub file_types: Option<Vec<String>>,
     #[serde(default = "default_limit")]
     pub limit: usize,
+    /// Walk the workspace even when it is indexed, so files the index leaves
+    /// out (binaries, lockfiles, files over the size limit) are found too.
+    #[serde(default)]
+    pub include_unindexed: bool,
 }
 
 fn default_limit() -> usize {
     100
 }
 
+#[derive(Debug, Deserialize)]
+pub struct QuickOpenQuery {
+    #[serde(default)]
+    pub query: String,
+    #[serde(default = "default_quick_open_limit")]
+    pub limit: usize,
+}
+
+fn default_quick_open_limit() -> usize {
+    50
+}
+
+/// Upper bound on `QuickOpenQuery.limit`.
+const MAX_QUICK_OPEN_RESULTS: usize = 500;
+
+#[derive(Debug, Serialize)]
+pub struct QuickOpenMatch {
+    pub relative_path: String,
+    pub filename: String,
+    pub score: i64,
+    /// Character (not byte) offsets into `relative_path` of the matched
+    /// query characters, for highlighting.
+    pub positions: Vec<usize>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct ReadFileResponse {
     pub path: String,
@@ -84,70 +151,151 @@ pub async fn list_files(
     State(state): State<AppState>,
     Path(workspace_id): Path<String>,
     Query(params): Query<ListFilesQuery>,
-) -> AppResult<Json<Vec<FileEntry>>> {
+) -> AppResult<Response> {
     let relative_path = params.path.unwrap_or_default();
     let recursive = params.recursive.unwrap_or(false);
     let show_hidden = params.show_hidden.unwrap_or(false);
     let max_depth = params.max_depth.unwrap_or(1);
+    let options = ListOptions {
+        sort_by: params.sort_by,
+        sort_dir: params.sort_dir,
+        entry_type: params.entry_type,
+    };
 
-    debug!(path = %relative_path, recursive, show_hidden, max_depth, "Listing files");
+    debug!(path = %relative_path, recursive, show_hidden, max_depth, ?options, "Listing files");
 
     // Use spawn_blocking because list_directory does synchronous std::fs I/O
     // which would otherwise block the tokio runtime thread.
     let wm = state.workspace_manager.clone();
+    let im = state.index_manager.clone();
     let ws_id = workspace_id.clone();
+    let (cached, git) = (params.cached, params.git);
     let entries = tokio::task::spawn_blocking(move || {
-        wm.list_directory(
-            &ws_id,
-            &relative_path,
-            recursive,
-            show_hidden,
-            max_depth,
-        )
+        let ws = wm.get_workspace(&ws_id)?;
+        let ws_path = std::path::Path::new(&ws.path);
+        let mut entries = if cached && ws.indexed {
+            if !relative_path.is_empty() && relative_path != "." {
+                wm.validate_path(&ws_id, &relative_path)?;
+            }
+            let paths = im.path_index(&ws_id)?;
+            crate::workspace::list_indexed(
+                ws_path,
+                &paths,
+                &relative_path,
+                recursive,
+                show_hidden,
+                max_depth,
+                options,
+            )
+        } else {
+            wm.list_directory(
+                &ws_id,
+                &relative_path,
+                recursive,
+                show_hidden,
+                max_depth,
+                options,
+            )?
+        };
+        if git {
+            crate::workspace::attach_git_status(ws_path, &relative_path, &mut entries);
+        }
+        AppResult::Ok(entries)
     })
     .await
     .map_err(|e| AppError::Internal(anyhow::anyhow!("Task join error: {}", e)))??;
 
     debug!(count = entries.len(), "Listed files");
-    Ok(Json(entries))
+    if params.offset.is_none() && params.limit.is_none() {
+        return Ok(Json(entries).into_response());
+    }
+
+    let total = entries.len();
+    let offset = params.offset.unwrap_or(0);
+    let limit = params.limit.unwrap_or(usize::MAX);
+    let page: Vec<FileEntry> = entries.into_iter().skip(offset).take(limit).collect();
+    Ok(Json(serde_json::json!({
+        "entries": page,
+        "total": total,
+        "offset": offset,
+        "limit": params.limit,
+    }))
+    .into_response())
+}
+
+/// Nested directory tree with recursive sizes; `max_depth` defaults to 3
+/// and is capped at `MAX_TREE_DEPTH`.
+#[instrument(skip(state), fields(workspace_id = %workspace_id))]
+pub async fn get_tree(
+    State(state): State<AppState>,
+    Path(workspace_id): Path<String>,
+    Query(params): Query<TreeQuery>,
+) -> AppResult<Json<TreeNode>> {
+    let relative_path = params.path.unwrap_or_default();
+    let max_depth = params.max_depth.unwrap_or(3);
+
+    let wm = state.workspace_manager.clone();
+    let tree = tokio::task::spawn_blocking(move || wm.tree(&workspace_id, &relative_path, max_depth))
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Task join error: {}", e)))??;
+
+    Ok(Json(tree))
 }
 
 pub async fn read_file(
     State(state): State<AppState>,
     Path(workspace_id): Path<String>,
+    headers: HeaderMap,
     Json(req): Json<FilePathRequest>,
-) -> AppResult<Json<ReadFileResponse>> {
-    read_file_inner(state, &workspace_id, &req.path).await
+) -> AppResult<Response> {
+    read_file_inner(state, &workspace_id, &req.path, &headers).await
 }
 
 /// GET handler for reading files via query parameter
 pub async fn read_file_query(
     State(state): State<AppState>,
     Path(workspace_id): Path<String>,
+    headers: HeaderMap,
     Query(query): Query<ReadFileQuery>,
-) -> AppResult<Json<ReadFileResponse>> {
-    read_file_inner(state, &workspace_id, &query.path).await
+) -> AppResult<Response> {
+    read_file_inner(state, &workspace_id, &query.path, &headers).await
 }
 
+/// Reads carry an `ETag` of the content hash; a matching `If-None-Match`
+/// gets `304 Not Modified` with no body.
 async fn read_file_inner(
     state: AppState,
     workspace_id: &str,
     file_path: &str,
-) -> AppResult<Json<ReadFileResponse>> {
-    let full_path = state.workspace_manager.validate_path(workspace_id, file_path)?;
+    headers: &HeaderMap,
+) -> AppResult<Response> {
+    let full_path = state.workspace_manager.validate_read_path(workspace_id, file_path)?;
 
     if !full_path.is_file() {
         warn!(workspace_id, path = file_path, "File not found");
         return Err(AppError::FileNotFound(file_path.to_string()));
     }
 
+    let max_file_size = state
+        .workspace_manager
+        .get_workspace(workspace_id)?
+        .max_file_size_bytes
+        .unwrap_or(state.config.max_file_size_bytes);
     let metadata = tokio::fs::metadata(&full_path).await?;
-    if metadata.len() > state.config.max_file_size_bytes as u64 {
-        warn!(workspace_id, path = file_path, size = metadata.len(), max = state.config.max_file_size_bytes, "File too large to read");
-        return Err(AppError::BadRequest("File too large to read".into()));
+    if metadata.len() > max_file_size as u64 {
+        warn!(workspace_id, path = file_path, size = metadata.len(), max = max_file_size, "File too large to read");
+        return Err(AppError::FileTooLarge { size: metadata.len(), max: max_file_size as u64 });
     }
 
+    // Always hashed from disk: the index records mtime in whole seconds and
+    // can lag behind the watcher, so its hash may be stale
     let content = tokio::fs::read_to_string(&full_path).await?;
+    let hash = content_hash(content.as_bytes());
+    if if_none_match(headers, &hash) {
+        debug!(workspace_id, path = file_path, "File unchanged, not modified");
+        return Ok(not_modified(&hash));
+    }
+
     let extension = full_path
         .extension()
         .unwrap_or_default()
@@ -156,34 +304,68 @@ async fn read_file_inner(
 
     debug!(workspace_id, path = file_path, size = metadata.len(), "File read");
 
-    Ok(Json(ReadFileResponse {
-        path: file_path.to_string(),
-        content,
-        size: metadata.len(),
-        language: detect_language(&extension),
-        encoding: "utf-8".into(),
-    }))
+    Ok((
+        [(header::ETAG, etag(&hash))],
+        Json(ReadFileResponse {
+            path: file_path.to_string(),
+            content,
+            size: metadata.len(),
+            language: detect_language(&extension),
+            encoding: "utf-8".into(),
+        }),
+    )
+        .into_response())
 }
 
 /// Maximum write content size (50 MiB) to prevent OOM from oversized payloads
 const MAX_WRITE_SIZE: usize = 50 * 1024 * 1024;
 
+/// Serializes writes that carry `expected_hash` so the hash check and the
+/// write happen atomically with respect to each other.
+static CONDITIONAL_WRITE_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
 #[instrument(skip(state, req), fields(workspace_id = %workspace_id, path = %req.path))]
 pub async fn write_file(
     State(state): State<AppState>,
     Path(workspace_id): Path<String>,
     Json(req): Json<WriteFileRequest>,
-) -> AppResult<Json<serde_json::Value>> {
+) -> AppResult<Response> {
     let size = req.content.len();
     if size > MAX_WRITE_SIZE {
-        return Err(AppError::BadRequest(format!(
-            "Content too large ({} bytes, max {} bytes)",
-            size, MAX_WRITE_SIZE
-        )));
+        return Err(AppError::FileTooLarge { size: size as u64, max: MAX_WRITE_SIZE as u64 });
     }
 
     let full_path = state.workspace_manager.validate_path(&workspace_id, &req.path)?;
-    
+
+    let _conditional_guard = match &req.expected_hash {
+        Some(expected) => {
+            let guard = CONDITIONAL_WRITE_LOCK.lock().await;
+            let path = full_path.clone();
+            let current = tokio::task::spawn_blocking(move || crate::indexer::hash_file(&path))
+                .await
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Task join error: {}", e)))?;
+            match current {
+                Ok(current) if current.eq_ignore_ascii_case(expected.trim_matches('"')) => {}
+                Ok(current) => {
+                    warn!(path = %req.path, expected = %expected, current = %current, "Write rejected: file changed");
+                    return Err(AppError::Conflict(format!(
+                        "File '{}' has changed since it was read (current hash {})",
+                        req.path, current
+                    )));
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    return Err(AppError::Conflict(format!(
+                        "File '{}' no longer exists",
+                        req.path
+                    )));
+                }
+                Err(e) => return Err(e.into()),
+            }
+            Some(guard)
+        }
+        None => None,
+    };
+
     // Ensure parent directory exists
     if let Some(parent) = full_path.parent() {
         tokio::fs::create_dir_all(parent).await?;
@@ -192,11 +374,18 @@ pub async fn write_file(
 
     info!(path = %req.path, size, "File written");
 
-    Ok(Json(serde_json::json!({
-        "success": true,
-        "path": req.path,
-        "size": size
-    })))
+    let hash = content_hash(req.content.as_bytes());
+    Ok((
+        [(header::ETAG, etag(&hash))],
+        Json(serde_json::json!({
+            "success": true,
+            "path": req.path,
+            "size": size,
+            "hash": hash,
+            "etag": hash
+        })),
+    )
+        .into_response())
 }
 
 #[instrument(skip(state, req), fields(workspace_id = %workspace_id, path = %req.path))]
@@ -204,7 +393,7 @@ pub async fn create_file(
     State(state): State<AppState>,
     Path(workspace_id): Path<String>,
     Json(req): Json<WriteFileRequest>,
-) -> AppResult<Json<serde_json::Value>> {
+) -> AppResult<Response> {
     let full_path = state.workspace_manager.validate_path(&workspace_id, &req.path)?;
 
     // Ensure parent directory exists
@@ -235,10 +424,16 @@ pub async fn create_file(
 
     info!(path = %req.path, size = req.content.len(), "File created");
 
-    Ok(Json(serde_json::json!({
-        "success": true,
-        "path": req.path
-    })))
+    let hash = content_hash(req.content.as_bytes());
+    Ok((
+        [(header::ETAG, etag(&hash))],
+        Json(serde_json::json!({
+            "success": true,
+            "path": req.path,
+            "etag": hash
+        })),
+    )
+        .into_response())
 }
 
 #[instrument(skip(state), fields(workspace_id = %workspace_id, path = %req.path))]
@@ -370,6 +565,75 @@ pub async fn stat_file(
     Ok(Json(stats))
 }
 
+#[derive(Debug, Serialize)]
+pub struct FileHashResponse {
+    pub path: String,
+    /// Hex SHA-256 of the file contents
+    pub hash: String,
+    pub size: u64,
+}
+
+/// SHA-256 of a file for client cache validation, computed from the file on
+/// disk: the index records mtime in whole seconds and can lag behind the
+/// watcher, so its stored hash may be stale and fail `expected_hash` writes.
+/// Sends `304 Not Modified` with no body if `If-None-Match` matches the hash.
+#[instrument(skip(state, headers), fields(workspace_id = %workspace_id, path = %req.path))]
+pub async fn file_hash(
+    State(state): State<AppState>,
+    Path(workspace_id): Path<String>,
+    headers: HeaderMap,
+    Json(req): Json<FilePathRequest>,
+) -> AppResult<Response> {
+    let full_path = state.workspace_manager.validate_read_path(&workspace_id, &req.path)?;
+    if !full_path.is_file() {
+        return Err(AppError::FileNotFound(req.path));
+    }
+
+    let metadata = tokio::fs::metadata(&full_path).await?;
+    let path = full_path.clone();
+    let hash = tokio::task::spawn_blocking(move || crate::indexer::hash_file(&path))
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Task join error: {}", e)))??;
+
+    if if_none_match(&headers, &hash) {
+        debug!(path = %req.path, "Hash unchanged, not modified");
+        return Ok(not_modified(&hash));
+    }
+
+    Ok((
+        [(header::ETAG, etag(&hash))],
+        Json(FileHashResponse {
+            path: req.path,
+            hash,
+            size: metadata.len(),
+        }),
+    )
+        .into_response())
+}
+
+/// Hex SHA-256, the same form as the index `content_hash` field.
+fn content_hash(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+fn etag(hash: &str) -> String {
+    format!("\"{}\"", hash)
+}
+
+fn not_modified(hash: &str) -> Response {
+    (StatusCode::NOT_MODIFIED, [(header::ETAG, etag(hash))]).into_response()
+}
+
+/// True if the `If-None-Match` header lists `hash` (quoted, weak, or `*`).
+fn if_none_match(headers: &HeaderMap, hash: &str) -> bool {
+    let Some(value) = headers.get(header::IF_NONE_MATCH).and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+    value.split(',').map(str::trim).any(|tag| {
+        tag == "*" || tag.trim_start_matches("W/").trim_matches('"') == hash
+    })
+}
+
 /// Create a new directory (mkdir -p behavior)
 #[instrument(skip(state), fields(workspace_id = %workspace_id, path = %req.path))]
 pub async fn create_directory(
@@ -398,20 +662,102 @@ pub async fn create_directory(
     })))
 }
 
+/// Fuzzy (subsequence) match of indexed file paths for quick open. Scores
+/// every path in the workspace index without touching the filesystem; an
+/// empty query returns the first paths in sorted order.
+pub async fn quick_open(
+    State(state): State<AppState>,
+    Path(workspace_id): Path<String>,
+    Query(params): Query<QuickOpenQuery>,
+) -> AppResult<Json<serde_json::Value>> {
+    let start = std::time::Instant::now();
+    let ws = state.workspace_manager.get_workspace(&workspace_id)?;
+    let status = state.index_manager.get_index_status(&workspace_id).unwrap_or_default();
+    if status.is_indexing && !ws.indexed {
+        return Err(AppError::IndexingInProgress(workspace_id));
+    }
+
+    let limit = params.limit.clamp(1, MAX_QUICK_OPEN_RESULTS);
+    let im = state.index_manager.clone();
+    let (candidates, matches) = tokio::task::spawn_blocking(move || {
+        let paths = im.path_index(&workspace_id)?;
+        let matches = fuzzy_match_paths(paths.keys().map(String::as_str), params.query.trim(), limit);
+        AppResult::Ok((paths.len(), matches))
+    })
+    .await
+    .map_err(|e| AppError::Internal(anyhow::anyhow!("Task join error: {}", e)))??;
+
+    Ok(Json(serde_json::json!({
+        "results": matches,
+        "total_candidates": candidates,
+        "query_time_ms": start.elapsed().as_millis() as u64,
+    })))
+}
+
+/// Best `limit` paths for `query`, highest score first; ties go to the
+/// shorter path. Match positions are only computed for the returned paths.
+fn fuzzy_match_paths<'a>(
+    paths: impl Iterator<Item = &'a str>,
+    query: &str,
+    limit: usize,
+) -> Vec<QuickOpenMatch> {
+    use fuzzy_matcher::skim::SkimMatcherV2;
+    use fuzzy_matcher::FuzzyMatcher;
+
+    let filename_of = |path: &str| path.rsplit('/').next().unwrap_or(path).to_string();
+    if query.is_empty() {
+        return paths
+            .take(limit)
+            .map(|relative_path| QuickOpenMatch {
+                filename: filename_of(relative_path),
+                relative_path: relative_path.to_string(),
+                score: 0,
+                positions: Vec::new(),
+            })
+            .collect();
+    }
+
+    let matcher = SkimMatcherV2::default();
+    let mut scored: Vec<(i64, &str)> = paths
+        .filter_map(|path| matcher.fuzzy_match(path, query).map(|score| (score, path)))
+        .collect();
+    scored.sort_by(|(a_score, a), (b_score, b)| {
+        b_score.cmp(a_score).then(a.len().cmp(&b.len())).then_with(|| a.cmp(b))
+    });
+    scored.truncate(limit);
+
+    scored
+        .into_iter()
+        .map(|(score, relative_path)| QuickOpenMatch {
+            filename: filename_of(relative_path),
+            positions: matcher
+                .fuzzy_indices(relative_path, query)
+                .map(|(_, positions)| positions)
+                .unwrap_or_default(),
+            relative_path: relative_path.to_string(),
+            score,
+        })
+        .collect()
+}
+
+/// Header of `search_files` responses naming the files searched: `indexed`
+/// (only files in the full-text index) or `all` (a walk of the workspace).
+pub const FILE_SEARCH_SCOPE_HEADER: &str = "x-file-search-scope";
+
+/// Substring search over file and directory names and relative paths. Once
+/// the workspace has been indexed the candidates come from the index's
+/// in-memory path list, so no directory tree is walked. That list only holds
+/// indexable files: binaries, excluded lockfiles and files over the size
+/// limit are not found, and neither are directories holding only such files.
+/// Before the first pass, or with `include_unindexed`, it falls back to a
+/// recursive listing. The `x-file-search-scope` header says which was used.
 #[instrument(skip(state), fields(workspace_id = %workspace_id, query = %req.query))]
 pub async fn search_files(
     State(state): State<AppState>,
     Path(workspace_id): Path<String>,
     Json(req): Json<SearchFilesRequest>,
-) -> AppResult<Json<Vec<FileEntry>>> {
-    let _ws = state.workspace_manager.get_workspace(&workspace_id)?;
-    let all_entries = state.workspace_manager.list_directory(
-        &workspace_id,
-        "",
-        true,
-        false,
-        10,
-    )?;
+) -> AppResult<Response> {
+    let ws = state.workspace_manager.get_workspace(&workspace_id)?;
 
     let query_lower = req.query.to_lowercase();
 
@@ -423,31 +769,52 @@ pub async fn search_files(
             .collect()
     });
 
-    let results: Vec<FileEntry> = all_entries
+    let matches = move |name: &str, relative_path: &str, extension: Option<&str>| {
+        let name_match = name.to_lowercase().contains(&query_lower);
+        let path_match = relative_path.to_lowercase().contains(&query_lower);
+        let text_match = name_match || path_match;
+
+        // Apply file_types filter if provided
+        if let Some(ref types) = file_type_filter {
+            let ext = extension.unwrap_or("").to_lowercase();
+            text_match && (types.is_empty() || types.contains(&ext))
+        } else {
+            text_match
+        }
+    };
+
+    let use_index = ws.indexed && !req.include_unindexed;
+    let wm = state.workspace_manager.clone();
+    let im = state.index_manager.clone();
+    let candidates = tokio::task::spawn_blocking(move || {
+        if use_index {
+            let paths = im.path_index(&workspace_id)?;
+            Ok(crate::workspace::list_indexed(
+                std::path::Path::new(&ws.path),
+                &paths,
+                "",
+                true,
+                false,
+                10,
+                ListOptions::default(),
+            ))
+        } else {
+            wm.list_directory(&workspace_id, "", true, false, 10, ListOptions::default())
+        }
+    })
+    .await
+    .map_err(|e| AppError::Internal(anyhow::anyhow!("Task join error: {}", e)))??;
+
+    let results: Vec<FileEntry> = candidates
         .into_iter()
-        .filter(|entry| {
-            let name_match = entry.name.to_lowercase().contains(&query_lower);
-            let path_match = entry.relative_path.to_lowercase().contains(&query_lower);
-            let text_match = name_match || path_match;
-
-            // Apply file_types filter if provided
-            if let Some(ref types) = file_type_filter {
-                let ext = entry
-                    .extension
-                    .as_deref()
-                    .unwrap_or("")
-                    .to_lowercase();
-                text_match && (types.is_empty() || types.contains(&ext))
-            } else {
-                text_match
-            }
-        })
+        .filter(|entry| matches(&entry.name, &entry.relative_path, entry.extension.as_deref()))
         .take(req.limit)
         .collect();
 
     debug!(results = results.len(), "File search completed");
 
-    Ok(Json(results))
+    let scope = if use_index { "indexed" } else { "all" };
+    Ok(([(FILE_SEARCH_SCOPE_HEADER, scope)], Json(results)).into_response())
 }
 
 async fn copy_dir_recursive(
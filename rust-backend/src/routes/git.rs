@@ -0,0 +1,63 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct BlameQuery {
+    pub path: String,
+    /// 1-based, inclusive; defaults to the whole file.
+    #[serde(default)]
+    pub start_line: Option<usize>,
+    #[serde(default)]
+    pub end_line: Option<usize>,
+}
+
+/// Who last changed each line of a file (commit, author, date, summary).
+/// `supported` is false for workspaces outside a git work tree.
+pub async fn blame(
+    State(state): State<AppState>,
+    Path(workspace_id): Path<String>,
+    Query(query): Query<BlameQuery>,
+) -> AppResult<Json<serde_json::Value>> {
+    let ws = state.workspace_manager.get_workspace(&workspace_id)?;
+    let full_path = state.workspace_manager.validate_path(&workspace_id, &query.path)?;
+    if !full_path.is_file() {
+        return Err(AppError::FileNotFound(query.path));
+    }
+    let start_line = query.start_line.unwrap_or(1).max(1);
+    let end_line = query.end_line.unwrap_or(usize::MAX);
+    if end_line < start_line {
+        return Err(AppError::BadRequest(format!(
+            "end_line ({}) is before start_line ({})",
+            end_line, start_line
+        )));
+    }
+
+    let cache = state.blame_cache.clone();
+    let path = query.path.clone();
+    let blame = tokio::task::spawn_blocking(move || {
+        cache.blame(&workspace_id, std::path::Path::new(&ws.path), &path)
+    })
+    .await
+    .map_err(|e| AppError::Internal(anyhow::anyhow!("Task join error: {}", e)))?;
+
+    Ok(Json(match blame {
+        Some(blame) => serde_json::json!({
+            "path": query.path,
+            "supported": true,
+            "tracked": blame.tracked,
+            "hunks": blame.lines(start_line, end_line),
+        }),
+        None => serde_json::json!({
+            "path": query.path,
+            "supported": false,
+            "tracked": false,
+            "hunks": [],
+        }),
+    }))
+}
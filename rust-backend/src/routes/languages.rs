@@ -0,0 +1,25 @@
+use axum::Json;
+use serde_json::{json, Map, Value};
+
+use crate::config::SUPPORTED_EXTENSIONS;
+use crate::lang::{DEFAULT_LANGUAGE, LANGUAGE_EXTENSIONS};
+
+/// Extension→language mapping used by `detect_language`, plus the extensions
+/// that get indexed, so clients don't have to keep their own copy in sync.
+pub async fn list_languages() -> Json<Value> {
+    let languages: Map<String, Value> = LANGUAGE_EXTENSIONS
+        .iter()
+        .map(|(language, exts)| (language.to_string(), json!(exts)))
+        .collect();
+    let extensions: Map<String, Value> = LANGUAGE_EXTENSIONS
+        .iter()
+        .flat_map(|(language, exts)| exts.iter().map(move |ext| (ext.to_string(), json!(language))))
+        .collect();
+
+    Json(json!({
+        "languages": languages,
+        "extensions": extensions,
+        "default_language": DEFAULT_LANGUAGE,
+        "supported_extensions": SUPPORTED_EXTENSIONS,
+    }))
+}
@@ -1,4 +1,8 @@
+pub mod admin;
+pub mod capabilities;
 pub mod files;
+pub mod git;
 pub mod health;
+pub mod languages;
 pub mod search;
 pub mod workspace;
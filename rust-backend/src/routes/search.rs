@@ -1,10 +1,18 @@
 use axum::{
-    extract::{Path, State},
+    body::Body,
+    extract::{Extension, Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
     Json,
 };
+use futures_util::StreamExt;
+use serde::Deserialize;
 
-use crate::error::AppResult;
-use crate::search::{self, GrepQuery, GrepResponse, SearchQuery, SearchResponse};
+use crate::error::{AppError, AppResult};
+use crate::history::SearchHistoryEntry;
+use crate::indexer::{HashSidecarCheck, IndexExplanation, IndexPlanSummary, EXPORT_FIELDS, MARKER_KINDS};
+use crate::server::Credentials;
+use crate::search::{self, GlobalSearchResult, GrepQuery, GrepResponse, SearchQuery, SearchResponse};
 use crate::state::AppState;
 
 /// Spawn a background task that runs full-text indexing.
@@ -19,22 +27,54 @@ pub fn spawn_background_indexing(
     index_manager: std::sync::Arc<crate::indexer::IndexManager>,
     workspace_manager: std::sync::Arc<crate::workspace::WorkspaceManager>,
     event_tx: tokio::sync::broadcast::Sender<crate::state::ServerEvent>,
+) {
+    spawn_index_pass(workspace_id, workspace_path, None, index_manager, workspace_manager, event_tx);
+}
+
+/// `spawn_background_indexing` for a pass that skips hashing files last
+/// modified before `modified_since` (see `IndexManager::sync_workspace`).
+fn spawn_index_pass(
+    workspace_id: String,
+    workspace_path: String,
+    modified_since: Option<u64>,
+    index_manager: std::sync::Arc<crate::indexer::IndexManager>,
+    workspace_manager: std::sync::Arc<crate::workspace::WorkspaceManager>,
+    event_tx: tokio::sync::broadcast::Sender<crate::state::ServerEvent>,
 ) {
     // Quick pre-check: skip spawning if full-text is already indexing
-    if let Ok(status) = index_manager.get_index_status(&workspace_id) {
-        if status.is_indexing {
-            tracing::info!(
-                "Skipping spawn_background_indexing for {} — full-text indexing already in progress",
-                workspace_id
-            );
+    if let Ok(status) = index_manager.get_index_status(&workspace_id)
+        && status.is_indexing
+    {
+        tracing::info!(
+            "Skipping spawn_background_indexing for {} — full-text indexing already in progress",
+            workspace_id
+        );
+        return;
+    }
+
+    // A missing root would otherwise look like every file was deleted
+    match workspace_manager.refresh_availability(&workspace_id) {
+        Ok((workspace, changed)) if !workspace.available => {
+            tracing::warn!("Skipping indexing for {}: root {} no longer exists", workspace_id, workspace_path);
+            if changed {
+                let _ = event_tx.send(crate::state::ServerEvent::WorkspaceUnavailable {
+                    workspace_id,
+                    path: workspace.path,
+                });
+            }
             return;
         }
+        _ => {}
     }
 
     tokio::spawn(async move {
         // Full-text indexing (Tantivy)
-        if let Err(e) = index_manager.index_workspace(&workspace_id, &workspace_path, event_tx.clone()).await {
+        if let Err(e) = index_manager
+            .sync_workspace(&workspace_id, &workspace_path, modified_since, event_tx.clone())
+            .await
+        {
             tracing::error!("Full-text indexing failed for {}: {}", workspace_id, e);
+            index_manager.record_index_error(&workspace_id, &e.to_string());
             let _ = event_tx.send(crate::state::ServerEvent::IndexingError {
                 workspace_id: workspace_id.clone(),
                 error: e.to_string(),
@@ -48,6 +88,7 @@ pub fn spawn_background_indexing(
                 status.indexed_count,
                 status.total_size_bytes,
                 true,
+                status.last_index_duration_ms,
             );
 
             // Emit SearchReady when indexing is complete
@@ -87,6 +128,86 @@ pub async fn index_workspace(
     })))
 }
 
+/// Slack subtracted from the last pass's start time before comparing mtimes,
+/// covering coarse filesystem timestamps and small clock differences.
+const SYNC_MTIME_SLACK_SECS: i64 = 2;
+
+/// Lightweight refresh: like `index_workspace`, but files whose mtime is
+/// older than the start of the last completed pass keep their recorded hash
+/// instead of being read and hashed again. Runs a full pass when the
+/// workspace has never been indexed.
+pub async fn sync_index(
+    State(state): State<AppState>,
+    Path(workspace_id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    let ws = state.workspace_manager.get_workspace(&workspace_id)?;
+
+    let status = state.index_manager.get_index_status(&workspace_id).unwrap_or_default();
+    if status.is_indexing {
+        return Ok(Json(serde_json::json!({
+            "status": "already_indexing",
+            "workspace_id": workspace_id,
+        })));
+    }
+
+    // last_indexed_at is stamped when a pass completes; files changed while
+    // it ran must still count as modified, so compare against its start
+    let modified_since = ws.last_indexed_at.map(|at| {
+        let duration_secs = ws.last_index_duration_ms.unwrap_or(0).div_ceil(1000) as i64;
+        (at.timestamp() - duration_secs - SYNC_MTIME_SLACK_SECS).max(0) as u64
+    });
+
+    spawn_index_pass(
+        workspace_id.clone(),
+        ws.path.clone(),
+        modified_since,
+        state.index_manager.clone(),
+        state.workspace_manager.clone(),
+        state.event_tx.clone(),
+    );
+
+    Ok(Json(serde_json::json!({
+        "status": "indexing_started",
+        "workspace_id": workspace_id,
+        "mode": if modified_since.is_some() { "changed_only" } else { "full" },
+    })))
+}
+
+/// Load the workspace's index and path index in the background so the
+/// first search after activation is fast. Emits `search_ready` once loaded if
+/// the workspace has been indexed; a no-op when everything is already warm.
+pub async fn warmup(
+    State(state): State<AppState>,
+    Path(workspace_id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    state.workspace_manager.get_workspace(&workspace_id)?;
+
+    if state.index_manager.is_warm(&workspace_id) {
+        return Ok(Json(serde_json::json!({
+            "status": "ready",
+            "workspace_id": workspace_id,
+        })));
+    }
+
+    let im = state.index_manager.clone();
+    let event_tx = state.event_tx.clone();
+    let ws_id = workspace_id.clone();
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = im.warm_up(&ws_id) {
+            tracing::warn!("Failed to warm up index for {}: {}", ws_id, e);
+            return;
+        }
+        if im.get_index_status(&ws_id).is_ok_and(|status| status.indexed) {
+            let _ = event_tx.send(crate::state::ServerEvent::SearchReady { workspace_id: ws_id });
+        }
+    });
+
+    Ok(Json(serde_json::json!({
+        "status": "warming",
+        "workspace_id": workspace_id,
+    })))
+}
+
 pub async fn index_status(
     State(state): State<AppState>,
     Path(workspace_id): Path<String>,
@@ -99,16 +220,274 @@ pub async fn index_status(
         "indexed_count": status.indexed_count,
         "total_count": status.total_count,
         "total_size_bytes": status.total_size_bytes,
+        "truncated": status.truncated,
+        "skipped_files": status.skipped.count,
+        "skipped_sample": status.skipped.sample,
+        "watcher_active": state.watcher_manager.is_watching(&workspace_id),
+        "watcher_paused": state.watcher_manager.is_paused(&workspace_id),
+        "last_error": status.last_error,
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExplainQuery {
+    pub path: String,
+}
+
+/// Report why a file is or isn't indexed: ignore rules, excluded
+/// directories, size limit, file type and encoding.
+pub async fn explain_index_path(
+    State(state): State<AppState>,
+    Path(workspace_id): Path<String>,
+    Query(query): Query<ExplainQuery>,
+) -> AppResult<Json<IndexExplanation>> {
+    let ws = state.workspace_manager.get_workspace(&workspace_id)?;
+    state.workspace_manager.validate_path(&workspace_id, &query.path)?;
+
+    let im = state.index_manager.clone();
+    let explanation = tokio::task::spawn_blocking(move || {
+        im.explain_path(&workspace_id, &ws.path, &query.path)
+    })
+    .await
+    .map_err(|e| AppError::Internal(anyhow::anyhow!("Task join error: {}", e)))?;
+
+    Ok(Json(explanation))
+}
+
+/// Dry run of an indexing pass: how many files would be indexed, left
+/// unchanged or removed, and their size. Hashes files but writes nothing.
+pub async fn index_plan(
+    State(state): State<AppState>,
+    Path(workspace_id): Path<String>,
+) -> AppResult<Json<IndexPlanSummary>> {
+    let ws = state.workspace_manager.get_workspace(&workspace_id)?;
+
+    let im = state.index_manager.clone();
+    let plan = tokio::task::spawn_blocking(move || im.index_plan(&workspace_id, &ws.path))
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Task join error: {}", e)))??;
+
+    Ok(Json(plan))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyIndexQuery {
+    /// Rebuild the content hash sidecar from the index if they diverge
+    #[serde(default)]
+    pub repair: bool,
+}
+
+/// Compare the index's documents with the content hash sidecar that
+/// incremental indexing relies on, and optionally rebuild the sidecar.
+pub async fn verify_index(
+    State(state): State<AppState>,
+    Path(workspace_id): Path<String>,
+    Query(query): Query<VerifyIndexQuery>,
+) -> AppResult<Json<HashSidecarCheck>> {
+    state.workspace_manager.get_workspace(&workspace_id)?;
+    let check = state
+        .index_manager
+        .check_content_hashes(&workspace_id, query.repair)
+        .await?;
+
+    Ok(Json(check))
+}
+
+/// Indexing errors since the last completed pass (up to the last few), oldest
+/// first. Lets clients that missed the `index_error` event see what failed.
+pub async fn index_errors(
+    State(state): State<AppState>,
+    Path(workspace_id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    state.workspace_manager.get_workspace(&workspace_id)?;
+    let errors = state.index_manager.index_errors(&workspace_id);
+
+    Ok(Json(serde_json::json!({
+        "workspace_id": workspace_id,
+        "errors": errors,
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatsQuery {
+    /// Leave whitespace-only lines out of the line counts
+    #[serde(default)]
+    pub exclude_blank: bool,
+}
+
+/// Dashboard metrics for one workspace. Counts come from the live index;
+/// the last-indexed time and duration are persisted in the workspace record.
+pub async fn workspace_stats(
+    State(state): State<AppState>,
+    Path(workspace_id): Path<String>,
+    Query(params): Query<StatsQuery>,
+) -> AppResult<Json<serde_json::Value>> {
+    let ws = state.workspace_manager.get_workspace(&workspace_id)?;
+    let status = state.index_manager.get_index_status(&workspace_id)?;
+
+    let im = state.index_manager.clone();
+    let ws_id = workspace_id.clone();
+    let ((document_count, facets), lines) = tokio::task::spawn_blocking(move || {
+        let facets = search::workspace_facets(&im, &ws_id)?;
+        let lines = im.lines_by_language(&ws_id, params.exclude_blank);
+        AppResult::Ok((facets, lines))
+    })
+    .await
+    .map_err(|e| AppError::Internal(anyhow::anyhow!("Task join error: {}", e)))??;
+
+    let total_lines: u64 = lines.iter().map(|(_, count)| count).sum();
+    let lines_by_language: Vec<serde_json::Value> = lines
+        .into_iter()
+        .map(|(language, lines)| serde_json::json!({ "language": language, "lines": lines }))
+        .collect();
+
+    Ok(Json(serde_json::json!({
+        "workspace_id": workspace_id,
+        "indexed": status.indexed,
+        "is_indexing": status.is_indexing,
+        "indexed_files": document_count,
+        "total_size_bytes": ws.total_size_bytes,
+        "language_count": facets.language.len(),
+        "languages": facets.language,
+        "extensions": facets.extension,
+        "total_lines": total_lines,
+        "lines_by_language": lines_by_language,
+        "last_index_duration_ms": ws.last_index_duration_ms,
+        "last_indexed_at": ws.last_indexed_at,
     })))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct MarkersQuery {
+    /// Comma-separated subset of `MARKER_KINDS` (case-insensitive); all kinds when omitted
+    #[serde(default)]
+    pub kind: Option<String>,
+}
+
+/// TODO/FIXME/HACK/XXX comments across the workspace, collected at index time.
+pub async fn list_markers(
+    State(state): State<AppState>,
+    Path(workspace_id): Path<String>,
+    Query(params): Query<MarkersQuery>,
+) -> AppResult<Json<serde_json::Value>> {
+    state.workspace_manager.get_workspace(&workspace_id)?;
+
+    let kinds: Vec<&'static str> = match params.kind.as_deref().filter(|k| !k.trim().is_empty()) {
+        Some(list) => list
+            .split(',')
+            .map(|kind| {
+                let kind = kind.trim();
+                MARKER_KINDS
+                    .iter()
+                    .find(|known| known.eq_ignore_ascii_case(kind))
+                    .copied()
+                    .ok_or_else(|| {
+                        AppError::BadRequest(format!(
+                            "Unknown marker kind '{}'; expected one of {}",
+                            kind,
+                            MARKER_KINDS.join(", ")
+                        ))
+                    })
+            })
+            .collect::<AppResult<_>>()?,
+        None => MARKER_KINDS.to_vec(),
+    };
+
+    let im = state.index_manager.clone();
+    let ws_id = workspace_id.clone();
+    let markers = tokio::task::spawn_blocking(move || search::workspace_markers(&im, &ws_id, &kinds))
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Task join error: {}", e)))??;
+
+    let mut counts = std::collections::BTreeMap::new();
+    for marker in &markers {
+        *counts.entry(marker.marker.kind.clone()).or_insert(0usize) += 1;
+    }
+
+    Ok(Json(serde_json::json!({
+        "workspace_id": workspace_id,
+        "total": markers.len(),
+        "counts": counts,
+        "markers": markers,
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    /// Comma-separated subset of `EXPORT_FIELDS`; all fields when omitted
+    #[serde(default)]
+    pub fields: Option<String>,
+}
+
+/// Stream all indexed documents as NDJSON. Rows are produced on a blocking
+/// thread and handed over through a small bounded channel, so memory stays
+/// flat regardless of index size.
+pub async fn export_index(
+    State(state): State<AppState>,
+    Path(workspace_id): Path<String>,
+    Query(params): Query<ExportQuery>,
+) -> AppResult<Response> {
+    let ws = state.workspace_manager.get_workspace(&workspace_id)?;
+
+    let fields: Vec<&'static str> = match params.fields.as_deref() {
+        None | Some("") => EXPORT_FIELDS.to_vec(),
+        Some(list) => list
+            .split(',')
+            .map(str::trim)
+            .filter(|f| !f.is_empty())
+            .map(|f| {
+                EXPORT_FIELDS.iter().copied().find(|known| *known == f).ok_or_else(|| {
+                    AppError::BadRequest(format!(
+                        "Unknown export field '{}' (expected one of: {})",
+                        f,
+                        EXPORT_FIELDS.join(", ")
+                    ))
+                })
+            })
+            .collect::<AppResult<_>>()?,
+    };
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<Result<String, std::io::Error>>(64);
+    let im = state.index_manager.clone();
+    tokio::task::spawn_blocking(move || {
+        let result = im.export_ndjson(&workspace_id, std::path::Path::new(&ws.path), &fields, |line| tx.blocking_send(Ok(line)).is_ok());
+        match result {
+            Ok(count) => tracing::info!("Exported {} documents from index {}", count, workspace_id),
+            Err(e) => {
+                tracing::warn!("Index export failed for {}: {}", workspace_id, e);
+                let _ = tx.blocking_send(Err(std::io::Error::other(e.to_string())));
+            }
+        }
+    });
+
+    // Fused: the compression layer may poll again after the stream has ended
+    let stream = futures_util::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|item| (item, rx))
+    })
+    .fuse();
+    Ok((
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(stream),
+    )
+        .into_response())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchDebugQuery {
+    #[serde(default)]
+    pub debug: bool,
+}
+
 /// Full-text search (Tantivy BM25)
 /// Uses spawn_blocking to avoid starving the tokio runtime with synchronous I/O.
 pub async fn fulltext_search(
     State(state): State<AppState>,
     Path(workspace_id): Path<String>,
-    Json(query): Json<SearchQuery>,
+    Query(params): Query<SearchDebugQuery>,
+    Json(mut query): Json<SearchQuery>,
 ) -> AppResult<Json<SearchResponse>> {
+    query.debug |= params.debug;
+    query.limit = query.limit.min(state.config.max_search_results);
     // Validate query is not empty
     if query.query.trim().is_empty() {
         return Err(crate::error::AppError::BadRequest(
@@ -123,21 +502,182 @@ pub async fn fulltext_search(
             crate::config::MAX_SEARCH_QUERY_LENGTH,
         )));
     }
+    // Mid-way through the first pass the index holds only some files; once a
+    // pass has completed, a background refresh still serves the last results
+    let ws = state.workspace_manager.get_workspace(&workspace_id)?;
+    let status = state.index_manager.get_index_status(&workspace_id).unwrap_or_default();
+    if status.is_indexing && !ws.indexed {
+        return Err(crate::error::AppError::IndexingInProgress(workspace_id));
+    }
+    if let Some(rev) = query.since_commit.take() {
+        let since = crate::git::revision_time(std::path::Path::new(&ws.path), &rev).ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "Unknown commit '{}' (or the workspace is not a git repository)",
+                rev
+            ))
+        })?;
+        query.modified_after = Some(query.modified_after.map_or(since, |after| after.max(since)));
+    }
     let index_manager = state.index_manager.clone();
+    let blame_cache = state.blame_cache.clone();
+    let ws_id = workspace_id.clone();
+    let query_text = query.query.clone();
+    let boosts = search::FieldBoosts::from_config(&state.config);
     let response = tokio::task::spawn_blocking(move || {
-        search::search_workspace(&index_manager, &workspace_id, &query)
+        let mut response = search::search_workspace(&index_manager, &ws_id, &ws.path, &query, boosts)?;
+        if query.blame {
+            let root = std::path::Path::new(&ws.path);
+            for result in &mut response.results {
+                let Some(line) = result.line_number else { continue };
+                result.last_commit = blame_cache
+                    .blame(&ws_id, root, &result.relative_path)
+                    .and_then(|blame| blame.at(line).cloned());
+            }
+        }
+        AppResult::Ok(response)
     })
     .await
     .map_err(|e| crate::error::AppError::Internal(anyhow::anyhow!("Search task failed: {}", e)))??;
+    // Recorded off the request path; the first record may read the history file
+    let history = state.search_history.clone();
+    let total_hits = response.total_hits;
+    tokio::spawn(async move { history.record(&workspace_id, &query_text, "fulltext", total_hits).await });
     Ok(Json(response))
 }
 
+/// Upper bound on the merged result count of a global search.
+const MAX_GLOBAL_SEARCH_RESULTS: usize = 200;
+
+/// Default and maximum per-workspace time budget for a global search.
+const DEFAULT_GLOBAL_SEARCH_TIMEOUT_MS: u64 = 2_000;
+const MAX_GLOBAL_SEARCH_TIMEOUT_MS: u64 = 30_000;
+
+#[derive(Debug, Deserialize)]
+pub struct GlobalSearchRequest {
+    #[serde(flatten)]
+    pub query: SearchQuery,
+    /// Time budget per workspace; workspaces that exceed it are left out of
+    /// the results and listed in `timed_out`.
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+/// Full-text search across every indexed workspace. Each workspace is queried
+/// concurrently on the blocking pool; hits are merged by score and cut to
+/// `limit`. Scores come from separate indexes with their own term statistics,
+/// so the ranking across workspaces is approximate.
+pub async fn global_search(
+    State(state): State<AppState>,
+    Extension(credentials): Extension<Credentials>,
+    Json(req): Json<GlobalSearchRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    let start = std::time::Instant::now();
+    let mut query = req.query;
+    if query.query.trim().is_empty() {
+        return Err(AppError::BadRequest("Search query must not be empty".to_string()));
+    }
+    if query.query.len() > crate::config::MAX_SEARCH_QUERY_LENGTH {
+        return Err(AppError::BadRequest(format!(
+            "Search query too long ({} chars). Maximum allowed is {}.",
+            query.query.len(),
+            crate::config::MAX_SEARCH_QUERY_LENGTH,
+        )));
+    }
+    if query.since_commit.is_some() {
+        return Err(AppError::BadRequest(
+            "since_commit is only supported when searching a single workspace".to_string(),
+        ));
+    }
+    query.limit = query
+        .limit
+        .min(MAX_GLOBAL_SEARCH_RESULTS)
+        .min(state.config.max_search_results);
+    query.offset = 0;
+    query.facets = false;
+    let timeout = std::time::Duration::from_millis(
+        req.timeout_ms
+            .unwrap_or(DEFAULT_GLOBAL_SEARCH_TIMEOUT_MS)
+            .min(MAX_GLOBAL_SEARCH_TIMEOUT_MS),
+    );
+
+    // Only workspaces that finished an indexing pass; searching the rest
+    // would create empty indexes on disk. Workspaces with their own token
+    // are left out unless the request presented it.
+    let workspaces: Vec<_> = state
+        .workspace_manager
+        .list_workspaces()
+        .into_iter()
+        .filter(|ws| credentials.can_access(&state.workspace_manager, &ws.id))
+        .filter(|ws| {
+            state
+                .index_manager
+                .get_index_status(&ws.id)
+                .is_ok_and(|status| status.indexed)
+        })
+        .collect();
+
+    let boosts = search::FieldBoosts::from_config(&state.config);
+    let searches = workspaces.iter().map(|ws| {
+        let index_manager = state.index_manager.clone();
+        let ws_id = ws.id.clone();
+        let ws_path = ws.path.clone();
+        let query = query.clone();
+        async move {
+            let task = tokio::task::spawn_blocking(move || {
+                search::search_workspace(&index_manager, &ws_id, &ws_path, &query, boosts)
+            });
+            tokio::time::timeout(timeout, task).await
+        }
+    });
+    let outcomes = futures_util::future::join_all(searches).await;
+
+    let mut results = Vec::new();
+    let mut total_hits = 0usize;
+    let mut total_matches = 0usize;
+    let mut timed_out = Vec::new();
+    let mut failed = Vec::new();
+    for (ws, outcome) in workspaces.iter().zip(outcomes) {
+        match outcome {
+            Ok(Ok(Ok(response))) => {
+                total_hits += response.total_hits;
+                total_matches += response.total_matches;
+                results.extend(response.results.into_iter().map(|result| GlobalSearchResult {
+                    workspace_id: ws.id.clone(),
+                    workspace_name: ws.name.clone(),
+                    result,
+                }));
+            }
+            Ok(Ok(Err(e))) => failed.push(serde_json::json!({ "workspace_id": ws.id, "error": e.to_string() })),
+            Ok(Err(e)) => failed.push(serde_json::json!({ "workspace_id": ws.id, "error": e.to_string() })),
+            Err(_) => {
+                tracing::warn!("Global search timed out for workspace {} after {:?}", ws.id, timeout);
+                timed_out.push(ws.id.clone());
+            }
+        }
+    }
+
+    results.sort_by(|a, b| query.sort.compare(&a.result, &b.result));
+    results.truncate(query.limit);
+
+    Ok(Json(serde_json::json!({
+        "results": results,
+        "total_hits": total_hits,
+        "total_matches": total_matches,
+        "limit": query.limit,
+        "searched_workspaces": workspaces.len(),
+        "timed_out": timed_out,
+        "failed": failed,
+        "query_time_ms": start.elapsed().as_millis() as u64,
+    })))
+}
+
 /// Grep search uses spawn_blocking to avoid starving the tokio runtime.
 pub async fn grep_search(
     State(state): State<AppState>,
     Path(workspace_id): Path<String>,
-    Json(query): Json<GrepQuery>,
+    Json(mut query): Json<GrepQuery>,
 ) -> AppResult<Json<GrepResponse>> {
+    query.limit = query.limit.min(state.config.max_search_results);
     // Validate pattern is not empty (empty pattern matches every line in every file)
     if query.pattern.trim().is_empty() {
         return Err(crate::error::AppError::BadRequest(
@@ -153,11 +693,36 @@ pub async fn grep_search(
         )));
     }
     let ws = state.workspace_manager.get_workspace(&workspace_id)?;
+    if let Some(sub_path) = &query.path {
+        state.workspace_manager.validate_path(&workspace_id, sub_path)?;
+    }
     let ws_path = ws.root_path().to_string();
+    let pattern = query.pattern.clone();
     let response = tokio::task::spawn_blocking(move || {
         search::grep_workspace(&ws_path, &query)
     })
     .await
     .map_err(|e| crate::error::AppError::Internal(anyhow::anyhow!("Grep task failed: {}", e)))??;
+    let history = state.search_history.clone();
+    let total_matches = response.total_matches;
+    tokio::spawn(async move { history.record(&workspace_id, &pattern, "grep", total_matches).await });
     Ok(Json(response))
 }
+
+/// Recent searches for a workspace, newest first.
+pub async fn search_history(
+    State(state): State<AppState>,
+    Path(workspace_id): Path<String>,
+) -> AppResult<Json<Vec<SearchHistoryEntry>>> {
+    let _ws = state.workspace_manager.get_workspace(&workspace_id)?;
+    Ok(Json(state.search_history.list(&workspace_id).await))
+}
+
+pub async fn clear_search_history(
+    State(state): State<AppState>,
+    Path(workspace_id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    let _ws = state.workspace_manager.get_workspace(&workspace_id)?;
+    state.search_history.clear(&workspace_id).await?;
+    Ok(Json(serde_json::json!({ "success": true })))
+}
@@ -1,45 +1,178 @@
 use axum::{
-    extract::{Path, State},
+    body::Body,
+    extract::{Extension, Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
     Json,
 };
+use futures_util::StreamExt;
 use serde::Deserialize;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use tokio::io::AsyncWriteExt;
 
-use crate::error::{AppError, AppResult};
+use crate::error::{AppError, AppResult, FieldError};
+use crate::server::Credentials;
 use crate::state::{AppState, ServerEvent};
 
+/// Directory under the data dir for in-flight bundle uploads and downloads.
+const BUNDLE_TEMP_DIR: &str = "bundles";
+
 #[derive(Debug, Deserialize)]
 pub struct CreateWorkspaceRequest {
     pub name: String,
     /// Accepts both "path" and "root_path" from the client
     #[serde(alias = "root_path")]
     pub path: String,
+    #[serde(default)]
+    pub allow_symlinks_outside: bool,
 }
 
+/// Settings left out of the request are unchanged.
+#[derive(Debug, Deserialize)]
+pub struct WorkspaceSettingsRequest {
+    #[serde(default)]
+    pub allow_symlinks_outside: Option<bool>,
+    /// Per-workspace file size limit for indexing and reads; 0 clears the
+    /// override so the global `max_file_size_bytes` applies again.
+    #[serde(default)]
+    pub max_file_size_bytes: Option<usize>,
+    /// Accent-insensitive full-text search (`café` matches `cafe`). Changing
+    /// it rebuilds the workspace's index.
+    #[serde(default)]
+    pub fold_accents: Option<bool>,
+    /// Token required for this workspace's routes (the global token is still
+    /// accepted); an empty string removes it. Stored hashed.
+    #[serde(default)]
+    pub auth_token: Option<String>,
+}
+
+/// Workspaces with their own token are listed only when the request
+/// presented it (or the global token).
 pub async fn list_workspaces(
     State(state): State<AppState>,
+    Extension(credentials): Extension<Credentials>,
 ) -> AppResult<Json<Vec<crate::workspace::Workspace>>> {
-    let workspaces = state.workspace_manager.list_workspaces();
+    let workspaces = state
+        .workspace_manager
+        .list_workspaces()
+        .into_iter()
+        .filter(|ws| credentials.can_access(&state.workspace_manager, &ws.id))
+        .collect();
     Ok(Json(workspaces))
 }
 
-pub async fn create_workspace(
-    State(state): State<AppState>,
-    Json(req): Json<CreateWorkspaceRequest>,
-) -> AppResult<Json<crate::workspace::Workspace>> {
-    // Validate workspace name
-    let name = req.name.trim().to_string();
+#[derive(Debug, Deserialize)]
+pub struct ImportWorkspaceQuery {
+    /// Root directory of the new workspace
+    pub path: String,
+    /// Defaults to the workspace name recorded in the bundle
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// Removes a temp file or directory when dropped, so every early return
+/// cleans up after a bundle upload or download.
+struct TempPath(PathBuf);
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        let _ = if self.0.is_dir() {
+            std::fs::remove_dir_all(&self.0)
+        } else {
+            std::fs::remove_file(&self.0)
+        };
+    }
+}
+
+/// Refuse a root that contains, or lies inside, a workspace with its own
+/// token the caller didn't present: a workspace there would expose its files.
+fn check_protected_overlap(
+    state: &AppState,
+    credentials: &Credentials,
+    path: &str,
+    except_id: Option<&str>,
+) -> AppResult<()> {
+    let blocked = state
+        .workspace_manager
+        .protected_overlapping(path, except_id)
+        .iter()
+        .any(|id| !credentials.can_access(&state.workspace_manager, id));
+    if blocked {
+        return Err(AppError::Forbidden(format!(
+            "{} overlaps a workspace that requires its own token",
+            path
+        )));
+    }
+    Ok(())
+}
+
+/// The trimmed workspace name, or why it is rejected.
+fn check_workspace_name(name: &str) -> Result<String, FieldError> {
+    let name = name.trim().to_string();
     if name.is_empty() {
-        return Err(AppError::BadRequest("Workspace name cannot be empty".into()));
+        return Err(FieldError::new("name", "Workspace name cannot be empty"));
     }
     if name.len() > 200 {
-        return Err(AppError::BadRequest("Workspace name too long (max 200 characters)".into()));
+        return Err(FieldError::new("name", "Workspace name too long (max 200 characters)"));
     }
     if name.chars().any(|c| c.is_control()) {
-        return Err(AppError::BadRequest("Workspace name cannot contain control characters".into()));
+        return Err(FieldError::new("name", "Workspace name cannot contain control characters"));
+    }
+    Ok(name)
+}
+
+fn validate_workspace_name(name: &str) -> AppResult<String> {
+    check_workspace_name(name).map_err(|e| AppError::Validation(vec![e]))
+}
+
+/// Why a workspace root path is rejected, if it is.
+fn check_workspace_path(path: &str) -> Option<FieldError> {
+    if path.trim().is_empty() {
+        return Some(FieldError::new("path", "Workspace path cannot be empty"));
+    }
+    let path = std::path::Path::new(path);
+    if !path.exists() {
+        return Some(FieldError::new("path", "Path does not exist"));
     }
+    if !path.is_dir() {
+        return Some(FieldError::new("path", "Path is not a directory"));
+    }
+    None
+}
+
+async fn bundle_temp_dir(state: &AppState) -> AppResult<PathBuf> {
+    let dir = PathBuf::from(&state.config.data_dir).join(BUNDLE_TEMP_DIR);
+    tokio::fs::create_dir_all(&dir).await?;
+    Ok(dir)
+}
 
-    let workspace = state.workspace_manager.create_workspace(name, req.path.clone())?;
+pub async fn create_workspace(
+    State(state): State<AppState>,
+    Extension(credentials): Extension<Credentials>,
+    Json(req): Json<CreateWorkspaceRequest>,
+) -> AppResult<Json<crate::workspace::Workspace>> {
+    let name = check_workspace_name(&req.name);
+    let path_error = check_workspace_path(&req.path);
+    let name = match (name, path_error) {
+        (Ok(name), None) => name,
+        (name, path_error) => {
+            let errors = name.err().into_iter().chain(path_error).collect();
+            return Err(AppError::Validation(errors));
+        }
+    };
+    check_protected_overlap(&state, &credentials, &req.path, None)?;
+    let workspace = state.workspace_manager.create_workspace(
+        name,
+        req.path.clone(),
+        req.allow_symlinks_outside,
+    )?;
+    start_workspace(&state, &workspace);
+    Ok(Json(workspace))
+}
 
+/// Start watching and indexing a newly registered workspace.
+fn start_workspace(state: &AppState, workspace: &crate::workspace::Workspace) {
     // Start watching the workspace with incremental re-indexing
     if let Err(e) = state.watcher_manager.start_watching(
         &workspace.id,
@@ -62,8 +195,146 @@ pub async fn create_workspace(
         workspace_id: workspace.id.clone(),
         path: workspace.path.clone(),
     });
+}
 
-    Ok(Json(workspace))
+/// Register a new workspace from an index bundle produced by `export_workspace`
+/// (request body: the `.tar.gz`). The bundle is validated before the workspace
+/// is created. Its index is reused whatever root `path` resolves to, unless
+/// it was built with different accent folding, in which case the workspace is
+/// indexed from scratch. Either way the usual background pass runs and picks
+/// up files changed since the export.
+pub async fn import_workspace(
+    State(state): State<AppState>,
+    Extension(credentials): Extension<Credentials>,
+    Query(params): Query<ImportWorkspaceQuery>,
+    body: Body,
+) -> AppResult<Json<serde_json::Value>> {
+    check_protected_overlap(&state, &credentials, &params.path, None)?;
+    let temp_dir = bundle_temp_dir(&state).await?;
+    let id = uuid::Uuid::new_v4();
+    let upload = TempPath(temp_dir.join(format!("{}.tar.gz", id)));
+    let staging = TempPath(temp_dir.join(id.to_string()));
+
+    let limit = state.config.max_import_bundle_bytes;
+    let mut file = tokio::fs::File::create(&upload.0).await?;
+    let mut received: u64 = 0;
+    let mut chunks = body.into_data_stream();
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk
+            .map_err(|e| AppError::BadRequest(format!("Failed to read bundle upload: {}", e)))?;
+        received += chunk.len() as u64;
+        if received > limit {
+            return Err(AppError::FileTooLarge { size: received, max: limit });
+        }
+        file.write_all(&chunk).await?;
+    }
+    file.flush().await?;
+    drop(file);
+
+    let im = state.index_manager.clone();
+    let max_unpacked = state.config.max_import_unpacked_bytes;
+    let (staging, manifest) = tokio::task::spawn_blocking(move || {
+        let bundle = std::io::BufReader::new(std::fs::File::open(&upload.0)?);
+        let manifest = im.unpack_bundle(bundle, &staging.0, max_unpacked)?;
+        AppResult::Ok((staging, manifest))
+    })
+    .await
+    .map_err(|e| AppError::Internal(anyhow::anyhow!("Task join error: {}", e)))??;
+
+    let name = validate_workspace_name(params.name.as_deref().unwrap_or(&manifest.workspace_name))?;
+    let workspace = state.workspace_manager.create_workspace(name, params.path, false)?;
+    let reused_index = match state
+        .index_manager
+        .install_bundle(&workspace.id, &staging.0, &manifest)
+    {
+        Ok(reused) => reused,
+        Err(e) => {
+            let _ = state.index_manager.remove_index(&workspace.id);
+            let _ = state.workspace_manager.remove_workspace(&workspace.id);
+            return Err(e);
+        }
+    };
+    start_workspace(&state, &workspace);
+
+    Ok(Json(serde_json::json!({
+        "workspace": workspace,
+        "manifest": manifest,
+        "reused_index": reused_index,
+    })))
+}
+
+/// Download the workspace index as a `.tar.gz` bundle for `import_workspace`.
+/// The bundle is built in a temp file first so the index writer lock is not
+/// held while a slow client downloads it.
+pub async fn export_workspace(
+    State(state): State<AppState>,
+    Path(workspace_id): Path<String>,
+) -> AppResult<Response> {
+    let ws = state.workspace_manager.get_workspace(&workspace_id)?;
+    let temp_dir = bundle_temp_dir(&state).await?;
+    let bundle = TempPath(temp_dir.join(format!("{}.tar.gz", uuid::Uuid::new_v4())));
+
+    let im = state.index_manager.clone();
+    let ws_id = workspace_id.clone();
+    let bundle = tokio::task::spawn_blocking(move || {
+        let mut out = std::io::BufWriter::new(std::fs::File::create(&bundle.0)?);
+        let manifest = im.export_bundle(&ws_id, &ws.name, &ws.path, &mut out)?;
+        out.flush()?;
+        tracing::info!(
+            "Exported index bundle for {} ({} documents)",
+            ws_id, manifest.document_count
+        );
+        AppResult::Ok(bundle)
+    })
+    .await
+    .map_err(|e| AppError::Internal(anyhow::anyhow!("Task join error: {}", e)))??;
+    let size = tokio::fs::metadata(&bundle.0).await?.len();
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<Result<Vec<u8>, std::io::Error>>(16);
+    tokio::task::spawn_blocking(move || {
+        let mut file = match std::fs::File::open(&bundle.0) {
+            Ok(file) => file,
+            Err(e) => {
+                let _ = tx.blocking_send(Err(e));
+                return;
+            }
+        };
+        let mut buf = vec![0u8; 64 * 1024];
+        loop {
+            match file.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    if tx.blocking_send(Ok(buf[..n].to_vec())).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    let _ = tx.blocking_send(Err(e));
+                    break;
+                }
+            }
+        }
+        // Close before `bundle` drops and deletes the file (required on Windows).
+        drop(file);
+    });
+
+    // Fused: the compression layer may poll again after the stream has ended
+    let stream = futures_util::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|item| (item, rx))
+    })
+    .fuse();
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/gzip".to_string()),
+            (header::CONTENT_LENGTH, size.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}.tar.gz\"", workspace_id),
+            ),
+        ],
+        Body::from_stream(stream),
+    )
+        .into_response())
 }
 
 pub async fn get_workspace(
@@ -74,12 +345,67 @@ pub async fn get_workspace(
     Ok(Json(workspace))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct RelocateWorkspaceRequest {
+    /// New root directory of the workspace
+    #[serde(alias = "root_path")]
+    pub path: String,
+}
+
+/// Point a workspace at the folder its project moved to, keeping its id,
+/// settings and index. Documents are keyed by path relative to the root, so
+/// the usual background pass only re-reads files whose content changed.
+pub async fn relocate_workspace(
+    State(state): State<AppState>,
+    Extension(credentials): Extension<Credentials>,
+    Path(workspace_id): Path<String>,
+    Json(req): Json<RelocateWorkspaceRequest>,
+) -> AppResult<Json<crate::workspace::Workspace>> {
+    let previous = state.workspace_manager.get_workspace(&workspace_id)?;
+    if let Some(error) = check_workspace_path(&req.path) {
+        return Err(AppError::Validation(vec![error]));
+    }
+    check_protected_overlap(&state, &credentials, &req.path, Some(&workspace_id))?;
+    if state.index_manager.get_index_status(&workspace_id)?.is_indexing {
+        return Err(AppError::IndexingInProgress(workspace_id));
+    }
+
+    let workspace = state.workspace_manager.relocate_workspace(&workspace_id, &req.path)?;
+    state.watcher_manager.stop_watching(&workspace_id);
+    tracing::info!("Relocated workspace {} from {} to {}", workspace_id, previous.path, workspace.path);
+
+    if state.config.enable_file_watcher
+        && let Err(e) = state.watcher_manager.start_watching(
+            &workspace_id,
+            &workspace.path,
+            Some(state.index_manager.clone()),
+        )
+    {
+        tracing::warn!("Failed to start file watcher for relocated workspace: {}", e);
+    }
+    crate::routes::search::spawn_background_indexing(
+        workspace_id.clone(),
+        workspace.path.clone(),
+        state.index_manager.clone(),
+        state.workspace_manager.clone(),
+        state.event_tx.clone(),
+    );
+
+    let _ = state.event_tx.send(ServerEvent::WorkspaceRelocated {
+        workspace_id,
+        from: previous.path,
+        to: workspace.path.clone(),
+    });
+    Ok(Json(workspace))
+}
+
 pub async fn remove_workspace(
     State(state): State<AppState>,
     Path(workspace_id): Path<String>,
 ) -> AppResult<Json<serde_json::Value>> {
     state.watcher_manager.stop_watching(&workspace_id);
     let _ = state.index_manager.remove_index(&workspace_id);
+    let _ = state.search_history.clear(&workspace_id).await;
     state.workspace_manager.remove_workspace(&workspace_id)?;
 
     let _ = state.event_tx.send(ServerEvent::WorkspaceRemoved {
@@ -93,17 +419,26 @@ pub async fn activate_workspace(
     State(state): State<AppState>,
     Path(workspace_id): Path<String>,
 ) -> AppResult<Json<crate::workspace::Workspace>> {
-    let workspace = state.workspace_manager.activate_workspace(&workspace_id)?;
+    state.workspace_manager.activate_workspace(&workspace_id)?;
+    let (workspace, _) = state.workspace_manager.refresh_availability(&workspace_id)?;
+    if !workspace.available {
+        tracing::warn!("Workspace {} root {} no longer exists; not watching or indexing", workspace_id, workspace.path);
+        let _ = state.event_tx.send(ServerEvent::WorkspaceUnavailable {
+            workspace_id: workspace_id.clone(),
+            path: workspace.path.clone(),
+        });
+        return Ok(Json(workspace));
+    }
 
     // Ensure watcher is running with index manager
-    if !state.watcher_manager.is_watching(&workspace_id) {
-        if let Err(e) = state.watcher_manager.start_watching(
+    if !state.watcher_manager.is_watching(&workspace_id)
+        && let Err(e) = state.watcher_manager.start_watching(
             &workspace_id,
             &workspace.path,
             Some(state.index_manager.clone()),
-        ) {
-            tracing::warn!("Failed to start file watcher: {}", e);
-        }
+        )
+    {
+        tracing::warn!("Failed to start file watcher: {}", e);
     }
 
     // Ensure indexes are loaded from disk (they persist across restarts)
@@ -124,7 +459,132 @@ pub async fn activate_workspace(
             state.workspace_manager.clone(),
             state.event_tx.clone(),
         );
+    } else if index_status.indexed && !index_status.is_indexing {
+        // An index whose content hash sidecar drifted from its documents makes
+        // the next pass duplicate or skip files; reconcile before that happens
+        let index_manager = state.index_manager.clone();
+        let workspace_id = workspace_id.clone();
+        tokio::spawn(async move {
+            if let Err(e) = index_manager.check_content_hashes(&workspace_id, true).await {
+                tracing::warn!("Failed to verify content hashes for {}: {}", workspace_id, e);
+            }
+        });
     }
 
     Ok(Json(workspace))
 }
+
+pub async fn deactivate_workspace(
+    State(state): State<AppState>,
+    Path(workspace_id): Path<String>,
+) -> AppResult<Json<crate::workspace::Workspace>> {
+    Ok(Json(state.workspace_manager.deactivate_workspace(&workspace_id)?))
+}
+
+pub async fn update_workspace_settings(
+    State(state): State<AppState>,
+    Path(workspace_id): Path<String>,
+    Json(req): Json<WorkspaceSettingsRequest>,
+) -> AppResult<Json<crate::workspace::Workspace>> {
+    let mut workspace = state.workspace_manager.get_workspace(&workspace_id)?;
+    if let Some(allow) = req.allow_symlinks_outside {
+        workspace = state
+            .workspace_manager
+            .set_allow_symlinks_outside(&workspace_id, allow)?;
+    }
+    if let Some(limit) = req.max_file_size_bytes {
+        let limit = (limit > 0).then_some(limit);
+        if limit != workspace.max_file_size_bytes {
+            workspace = state.workspace_manager.set_max_file_size(&workspace_id, limit)?;
+            state.index_manager.set_max_file_size_override(&workspace_id, limit);
+            // Pick up files that now fit and drop ones that no longer do
+            crate::routes::search::spawn_background_indexing(
+                workspace_id.clone(),
+                workspace.path.clone(),
+                state.index_manager.clone(),
+                state.workspace_manager.clone(),
+                state.event_tx.clone(),
+            );
+        }
+    }
+    if let Some(fold) = req.fold_accents.filter(|&fold| fold != workspace.fold_accents) {
+        if state.index_manager.get_index_status(&workspace_id)?.is_indexing {
+            return Err(AppError::IndexingInProgress(workspace_id));
+        }
+        workspace = state.workspace_manager.set_fold_accents(&workspace_id, fold)?;
+        state.index_manager.set_fold_accents(&workspace_id, fold);
+        crate::routes::search::spawn_background_indexing(
+            workspace_id.clone(),
+            workspace.path.clone(),
+            state.index_manager.clone(),
+            state.workspace_manager.clone(),
+            state.event_tx.clone(),
+        );
+    }
+    if let Some(token) = req.auth_token.as_deref() {
+        let token = token.trim();
+        state
+            .workspace_manager
+            .set_auth_token(&workspace_id, (!token.is_empty()).then_some(token))?;
+    }
+    Ok(Json(workspace))
+}
+
+pub async fn pause_watcher(
+    State(state): State<AppState>,
+    Path(workspace_id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    let _ws = state.workspace_manager.get_workspace(&workspace_id)?;
+    if !state.watcher_manager.pause_watching(&workspace_id) {
+        return Err(AppError::BadRequest(format!(
+            "Workspace {} is not being watched",
+            workspace_id
+        )));
+    }
+    Ok(Json(serde_json::json!({ "success": true, "paused": true })))
+}
+
+/// Resume a paused watcher; triggers one reconciliation reindex if it was paused.
+pub async fn resume_watcher(
+    State(state): State<AppState>,
+    Path(workspace_id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    let _ws = state.workspace_manager.get_workspace(&workspace_id)?;
+    if !state.watcher_manager.resume_watching(&workspace_id) {
+        return Err(AppError::BadRequest(format!(
+            "Workspace {} is not being watched",
+            workspace_id
+        )));
+    }
+    Ok(Json(serde_json::json!({ "success": true, "paused": false })))
+}
+
+/// Start watching again after the watcher stopped or failed to start (e.g.
+/// once the OS watch limit has been raised). A healthy watcher is left as is.
+pub async fn restart_watcher(
+    State(state): State<AppState>,
+    Path(workspace_id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    let ws = state.workspace_manager.get_workspace(&workspace_id)?;
+    if !state.config.enable_file_watcher {
+        return Err(AppError::BadRequest("File watching is disabled in settings".to_string()));
+    }
+    let result = state.watcher_manager.restart_watching(
+        &workspace_id,
+        &ws.path,
+        Some(state.index_manager.clone()),
+    );
+    let (restarted, error) = match result {
+        Ok(restarted) => (restarted, None),
+        Err(e) => {
+            tracing::warn!("Failed to restart file watcher for {}: {}", workspace_id, e);
+            (false, Some(e.to_string()))
+        }
+    };
+    Ok(Json(serde_json::json!({
+        "success": error.is_none(),
+        "restarted": restarted,
+        "is_watching": state.watcher_manager.is_watching(&workspace_id),
+        "error": error,
+    })))
+}
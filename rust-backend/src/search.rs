@@ -1,13 +1,19 @@
 use crate::error::{AppError, AppResult};
-use crate::indexer::IndexManager;
+use crate::indexer::{CodeMarker, IndexManager};
 use globset::{Glob, GlobMatcher};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use tantivy::collector::TopDocs;
-use tantivy::query::{BooleanQuery, FuzzyTermQuery, Occur, QueryParser};
-use tantivy::schema::Value;
-use tantivy::TantivyDocument;
+use tantivy::aggregation::agg_req::Aggregations;
+use tantivy::aggregation::agg_result::{AggregationResult, AggregationResults, BucketResult};
+use tantivy::aggregation::{AggregationCollector, AggregationLimitsGuard, Key};
+use tantivy::collector::{Collector, DocSetCollector, SegmentCollector, TopDocs};
+use std::ops::Bound;
+use tantivy::query::{
+    AllQuery, BooleanQuery, BoostQuery, ConstScoreQuery, FuzzyTermQuery, Occur, QueryParser, RangeQuery, TermQuery,
+};
+use tantivy::schema::{IndexRecordOption, Value};
+use tantivy::store::StoreReader;
+use tantivy::{DocAddress, DocId, DocSet, Score, Searcher, SegmentOrdinal, SegmentReader, TantivyDocument};
 use tracing::{debug, info};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,16 +21,120 @@ pub struct SearchQuery {
     pub query: String,
     #[serde(default = "default_limit")]
     pub limit: usize,
+    /// Number of matching hits to skip before `limit` results are returned;
+    /// pair with `SearchResponse.total_matches` to page through results.
+    #[serde(default)]
+    pub offset: usize,
     #[serde(default)]
     pub file_pattern: Option<String>,
     #[serde(default)]
     pub language: Option<String>,
+    /// Only files last changed after this time (Unix seconds, exclusive).
+    /// "Changed" is the file's last git commit, or its mtime when uncommitted
+    /// or outside a git work tree.
+    #[serde(default)]
+    pub modified_after: Option<u64>,
+    /// Only files last changed before this time (Unix seconds, exclusive).
+    #[serde(default)]
+    pub modified_before: Option<u64>,
+    /// Only files changed after this commit (id, branch or tag); resolved to
+    /// its commit time and combined with `modified_after`. Workspace search only.
+    #[serde(default)]
+    pub since_commit: Option<String>,
     #[serde(default)]
     pub fuzzy: bool,
+    /// When true, count matching documents per language and extension and return
+    /// them in `SearchResponse.facets`. Runs a term aggregation over the whole
+    /// match set, so it makes the query slightly slower.
+    #[serde(default)]
+    pub facets: bool,
+    /// When set, return this many lines before and after the best-matching
+    /// line in `SearchResult.context` (capped at `MAX_CONTEXT_LINES`).
+    #[serde(default)]
+    pub context_lines: Option<usize>,
+    /// Include the parsed Tantivy query in `SearchResponse.parsed_query`.
+    /// Also settable as `?debug=true` on the search route.
+    #[serde(default)]
+    pub debug: bool,
+    /// Report which query terms occur in each hit (`SearchResult.matched_terms`).
+    /// Costs one postings lookup per term per returned hit.
+    #[serde(default)]
+    pub matched_terms: bool,
+    /// Order of the returned hits. `recency` reorders the best-scoring hits
+    /// by `SearchResult.modified`; it does not look for newer, weaker matches.
+    #[serde(default)]
+    pub sort: SearchSort,
+    /// Boost for recently modified files: each hit's score becomes
+    /// `relevance * (1 + recency_weight * decay(age))`, where `decay` halves
+    /// every `RECENCY_HALF_LIFE_DAYS` of `SearchResult.modified` age. Like
+    /// `sort`, it reorders the best-scoring candidates. 0 (the default) keeps
+    /// plain relevance ranking.
+    #[serde(default)]
+    pub recency_weight: f32,
+    /// Attach git blame for each hit's line (`SearchResult.last_commit`).
+    /// Blames every file in the page, so only ask when it will be shown.
+    #[serde(default)]
+    pub blame: bool,
+}
+
+/// Result ordering for full-text search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchSort {
+    /// Highest score first, ties broken by relative path.
+    #[default]
+    Relevance,
+    /// Newest first, ties broken by score, then relative path.
+    Recency,
+}
+
+impl SearchSort {
+    pub fn compare(self, a: &SearchResult, b: &SearchResult) -> std::cmp::Ordering {
+        let by_score = b.score.total_cmp(&a.score);
+        let by_path = || a.relative_path.cmp(&b.relative_path).then_with(|| a.path.cmp(&b.path));
+        match self {
+            Self::Relevance => by_score.then_with(by_path),
+            Self::Recency => b.modified.cmp(&a.modified).then(by_score).then_with(by_path),
+        }
+    }
 }
 
 fn default_limit() -> usize {
-    20
+    crate::config::DEFAULT_SEARCH_LIMIT
+}
+
+/// Per-field score multipliers applied to unscoped query terms
+/// (see `AppConfig::boost_*` for defaults and tradeoffs).
+#[derive(Debug, Clone, Copy)]
+pub struct FieldBoosts {
+    pub filename: f32,
+    pub symbols: f32,
+    pub relative_path: f32,
+    pub content: f32,
+}
+
+impl FieldBoosts {
+    pub fn from_config(config: &crate::config::AppConfig) -> Self {
+        Self {
+            filename: config.boost_filename,
+            symbols: config.boost_symbols,
+            relative_path: config.boost_relative_path,
+            content: config.boost_content,
+        }
+    }
+}
+
+/// Upper bound on `SearchQuery.context_lines`.
+const MAX_CONTEXT_LINES: usize = 50;
+
+/// Age at which a file gets half of the `SearchQuery.recency_weight` boost.
+const RECENCY_HALF_LIFE_DAYS: f64 = 30.0;
+
+/// Recency factor in (0, 1] for a file modified at `modified` (Unix seconds);
+/// files modified in the future count as brand new.
+fn recency_decay(modified: u64, now: u64) -> f32 {
+    let age_days = now.saturating_sub(modified) as f64 / 86_400.0;
+    0.5f64.powf(age_days / RECENCY_HALF_LIFE_DAYS) as f32
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,17 +144,82 @@ pub struct SearchResult {
     pub filename: String,
     pub language: String,
     pub score: f32,
+    /// Query relevance before the recency boost; only when
+    /// `SearchQuery.recency_weight` is set, in which case `score` is blended.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relevance: Option<f32>,
+    /// Modification time (Unix seconds) recorded when the file was indexed.
+    #[serde(default)]
+    pub modified: u64,
     pub snippet: String,
     pub line_number: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<SearchContext>,
+    /// Query terms (as tokenized) present in this document, sorted; only with
+    /// `SearchQuery.matched_terms`. Fuzzy queries report exact occurrences only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched_terms: Option<Vec<String>>,
+    /// Last change to `line_number` according to git blame; only with
+    /// `SearchQuery.blame` in a git work tree.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_commit: Option<crate::git::BlameHunk>,
+}
+
+/// A hit from `/api/search/global`, tagged with the workspace it came from.
+#[derive(Debug, Clone, Serialize)]
+pub struct GlobalSearchResult {
+    pub workspace_id: String,
+    pub workspace_name: String,
+    #[serde(flatten)]
+    pub result: SearchResult,
+}
+
+/// Lines surrounding the best match; line numbers are 1-based and inclusive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchContext {
+    pub start_line: usize,
+    pub end_line: usize,
+    pub text: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResponse {
     pub results: Vec<SearchResult>,
+    /// Hits that passed the filters among the top-scoring candidates fetched
+    /// for this page; at most about twice `offset + limit`.
     pub total_hits: usize,
+    /// Every document matching the query and the `language`/`file_pattern`
+    /// filters, however many were returned.
+    #[serde(default)]
+    pub total_matches: usize,
+    /// Effective `limit` after clamping to the server's maximum.
+    #[serde(default)]
+    pub limit: usize,
     pub query_time_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub facets: Option<SearchFacets>,
+    /// Debug rendering of how the query string was interpreted (`debug` only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parsed_query: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FacetCount {
+    pub value: String,
+    pub count: u64,
+}
+
+/// Per-field document counts over all documents matching the query
+/// (computed before the `language`/`file_pattern` filters are applied).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchFacets {
+    pub language: Vec<FacetCount>,
+    pub extension: Vec<FacetCount>,
+}
+
+/// Maximum number of distinct values returned per facet field.
+const MAX_FACET_VALUES: u32 = 100;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GrepQuery {
     pub pattern: String,
@@ -52,8 +227,13 @@ pub struct GrepQuery {
     pub is_regex: bool,
     #[serde(default)]
     pub case_sensitive: bool,
-    #[serde(default = "default_limit")]
+    /// Maximum matches returned; scanning stops once it is reached.
+    #[serde(default = "default_limit", alias = "max_results")]
     pub limit: usize,
+    /// Maximum matches taken from any one file, so a single huge file cannot
+    /// fill the whole result set. Defaults to a quarter of `limit` (at least 20).
+    #[serde(default)]
+    pub max_matches_per_file: Option<usize>,
     #[serde(default)]
     pub file_pattern: Option<String>,
     #[serde(default)]
@@ -75,9 +255,14 @@ pub struct GrepResult {
     pub path: String,
     pub relative_path: String,
     pub line_number: usize,
+    /// The matching line; lines over `MAX_GREP_LINE_BYTES` (e.g. minified
+    /// bundles) are cut to a window around the match, with `…` marking the
+    /// cut ends.
     pub line_content: String,
+    /// Byte range of the match within `line_content`.
     pub match_start: usize,
     pub match_end: usize,
+    /// Surrounding lines, cut to `MAX_GREP_LINE_BYTES` like `line_content`.
     pub context_before: Vec<String>,
     pub context_after: Vec<String>,
 }
@@ -87,6 +272,13 @@ pub struct GrepResponse {
     pub results: Vec<GrepResult>,
     pub total_matches: usize,
     pub files_searched: usize,
+    /// Effective `limit` after clamping to the server's maximum.
+    #[serde(default)]
+    pub limit: usize,
+    /// More matches exist than were returned: the result cap or a per-file
+    /// cap cut the scan short.
+    #[serde(default)]
+    pub truncated: bool,
     pub query_time_ms: u64,
 }
 
@@ -96,7 +288,9 @@ pub struct GrepResponse {
 pub fn search_workspace(
     index_manager: &IndexManager,
     workspace_id: &str,
+    workspace_root: &str,
     query: &SearchQuery,
+    boosts: FieldBoosts,
 ) -> AppResult<SearchResponse> {
     let start = std::time::Instant::now();
 
@@ -110,6 +304,12 @@ pub fn search_workspace(
         "Full-text search starting"
     );
 
+    if !query.recency_weight.is_finite() || query.recency_weight < 0.0 {
+        return Err(AppError::BadRequest(
+            "recency_weight must be a non-negative number".to_string(),
+        ));
+    }
+
     let state = index_manager.get_or_create_index(workspace_id)?;
     let searcher = state.reader.searcher();
     let schema = &state.schema;
@@ -119,57 +319,98 @@ pub fn search_workspace(
         Glob::new(pattern).ok().map(|g| g.compile_matcher())
     });
 
+    // Terms checked against each hit for `matched_terms`
+    let mut query_terms: Vec<tantivy::Term> = Vec::new();
+
     // Build query — use fuzzy term queries when fuzzy is enabled
     let parsed_query: Box<dyn tantivy::query::Query> = if query.fuzzy {
         // Build fuzzy boolean query across fields
-        let fields = vec![schema.content, schema.filename, schema.symbols];
+        let fields = [
+            (schema.content, boosts.content),
+            (schema.filename, boosts.filename),
+            (schema.symbols, boosts.symbols),
+        ];
         let mut subqueries: Vec<(Occur, Box<dyn tantivy::query::Query>)> = Vec::new();
 
         for word in query.query.split_whitespace() {
-            for &field in &fields {
+            for &(field, boost) in &fields {
                 let term = tantivy::Term::from_field_text(field, &word.to_lowercase());
+                if query.matched_terms {
+                    query_terms.push(term.clone());
+                }
                 let fuzzy = FuzzyTermQuery::new(term, 2, true); // distance=2, transpositions=true
-                subqueries.push((Occur::Should, Box::new(fuzzy)));
+                subqueries.push((Occur::Should, Box::new(BoostQuery::new(Box::new(fuzzy), boost))));
             }
         }
 
         Box::new(BooleanQuery::new(subqueries))
     } else {
-        let query_parser = QueryParser::for_index(
+        // Full Tantivy query syntax: "exact phrase", AND/OR, +required,
+        // -excluded, and field-scoped terms such as language:rust or extension:ts.
+        // Unscoped terms search content, filename, symbols and relative path,
+        // weighted by the configured field boosts.
+        let mut query_parser = QueryParser::for_index(
             &state.index,
-            vec![schema.content, schema.filename, schema.symbols],
+            vec![schema.content, schema.filename, schema.symbols, schema.relative_path],
         );
+        query_parser.set_field_boost(schema.content, boosts.content);
+        query_parser.set_field_boost(schema.filename, boosts.filename);
+        query_parser.set_field_boost(schema.symbols, boosts.symbols);
+        query_parser.set_field_boost(schema.relative_path, boosts.relative_path);
         Box::new(
             query_parser
                 .parse_query(&query.query)
-                .map_err(|e| AppError::SearchError(format!("Invalid query: {}", e)))?,
+                .map_err(|e| AppError::BadRequest(format!("Invalid query syntax: {}", e)))?,
         )
     };
+    let parsed_query = with_changed_range(parsed_query, schema.changed, query.modified_after, query.modified_before);
+    let parsed_query_debug = query.debug.then(|| format!("{:?}", parsed_query));
+    if query.matched_terms && !query.fuzzy {
+        parsed_query.query_terms(&mut |term, _| query_terms.push(term.clone()));
+    }
 
-    let top_docs = searcher
-        .search(&*parsed_query, &TopDocs::with_limit(query.limit * 2)) // Over-fetch for filtering
-        .map_err(|e| AppError::SearchError(format!("Search failed: {}", e)))?;
+    // Over-fetch for filtering
+    let top_collector = TopDocs::with_limit((query.offset + query.limit).max(1) * 2);
+    let count_collector = FilteredCount {
+        language: query.language.clone(),
+        path_matcher: file_pattern_matcher.clone(),
+        relative_path: schema.relative_path,
+        filename: schema.filename,
+    };
+    let (top_docs, total_matches, facets) = if query.facets {
+        let (top_docs, total_matches, agg_results) = searcher
+            .search(&*parsed_query, &(top_collector, count_collector, facet_collector()?))
+            .map_err(|e| AppError::SearchError(format!("Search failed: {}", e)))?;
+        (top_docs, total_matches, Some(facets_from_aggregations(agg_results)))
+    } else {
+        let (top_docs, total_matches) = searcher
+            .search(&*parsed_query, &(top_collector, count_collector))
+            .map_err(|e| AppError::SearchError(format!("Search failed: {}", e)))?;
+        (top_docs, total_matches, None)
+    };
+
+    let highlight_terms = positive_terms(&query.query);
+    let top_docs = rank_hits(&searcher, schema, query.recency_weight, top_docs)?;
 
     // Track total matching results before applying the limit
     let mut total_matching = 0usize;
     let mut results = Vec::new();
 
-    for (score, doc_address) in top_docs {
+    for (score, relevance, doc_address) in top_docs {
         let doc: TantivyDocument = searcher.doc(doc_address).map_err(|e| {
             AppError::SearchError(format!("Failed to retrieve doc: {}", e))
         })?;
 
-        let path = doc
-            .get_first(schema.path)
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
-
         let relative_path = doc
             .get_first(schema.relative_path)
             .and_then(|v| v.as_str())
             .unwrap_or("")
             .to_string();
+        // Documents store only the relative path, so results follow a moved root
+        let path = std::path::Path::new(workspace_root)
+            .join(&relative_path)
+            .to_string_lossy()
+            .to_string();
 
         let filename = doc
             .get_first(schema.filename)
@@ -183,40 +424,57 @@ pub fn search_workspace(
             .unwrap_or("plaintext")
             .to_string();
 
-        // MEMORY FIX: content is no longer STORED in Tantivy.
-        // Read the file from disk to generate the snippet.
-        // Skip stale results where the file has been deleted/moved since indexing.
-        let content_on_disk = match std::fs::read_to_string(&path) {
-            Ok(c) if !c.is_empty() => c,
-            _ => continue, // File deleted, moved, or unreadable — skip stale result
+        let modified = doc.get_first(schema.modified).and_then(|v| v.as_u64()).unwrap_or(0);
+
+        // MEMORY FIX: content is only STORED when `index_store_content` is enabled.
+        // Otherwise read the file from disk to generate the snippet, skipping
+        // stale results where the file has been deleted/moved since indexing.
+        let stored_content = doc
+            .get_first(schema.content)
+            .and_then(|v| v.as_str())
+            .filter(|c| !c.is_empty())
+            .map(str::to_string);
+        let content_on_disk = match stored_content {
+            Some(c) => c,
+            None => match std::fs::read_to_string(&path) {
+                Ok(c) if !c.is_empty() => c,
+                _ => continue, // File deleted, moved, or unreadable — skip stale result
+            },
         };
 
         // Filter by file pattern if specified
-        if let Some(ref matcher) = file_pattern_matcher {
-            if !matcher.is_match(&relative_path) && !matcher.is_match(&filename) {
-                continue;
-            }
+        if let Some(ref matcher) = file_pattern_matcher
+            && !matcher.is_match(&relative_path)
+            && !matcher.is_match(&filename)
+        {
+            continue;
         }
 
         // Filter by language if specified
-        if let Some(ref lang) = query.language {
-            if language != *lang {
-                continue;
-            }
+        if let Some(ref lang) = query.language
+            && language != *lang
+        {
+            continue;
         }
 
         // Count total matching results (before applying the limit)
         total_matching += 1;
 
-        // Only collect results up to the limit
-        if results.len() >= query.limit {
+        // Only collect results from the requested page
+        if total_matching <= query.offset || results.len() >= query.limit {
             continue; // Keep counting total_matching but don't add more results
         }
 
         // Generate snippet around matching text
-        let (snippet, line_number) = generate_snippet(&content_on_disk, &query.query, 200);
+        let (snippet, line_number) = generate_snippet(&content_on_disk, &highlight_terms, 200);
+        let context = query.context_lines.map(|n| {
+            extract_context(&content_on_disk, line_number.unwrap_or(1), n.min(MAX_CONTEXT_LINES))
+        });
         // Drop full file content immediately to avoid accumulating in the loop
         drop(content_on_disk);
+        let matched_terms = query
+            .matched_terms
+            .then(|| terms_in_doc(&searcher, doc_address, &query_terms));
 
         results.push(SearchResult {
             path,
@@ -224,11 +482,20 @@ pub fn search_workspace(
             filename,
             language,
             score,
+            relevance,
+            modified,
             snippet,
             line_number,
+            context,
+            matched_terms,
+            last_commit: None,
         });
     }
 
+    if query.sort != SearchSort::Relevance {
+        results.sort_by(|a, b| query.sort.compare(a, b));
+    }
+
     let duration = start.elapsed();
 
     info!(
@@ -241,26 +508,331 @@ pub fn search_workspace(
 
     Ok(SearchResponse {
         total_hits: total_matching,
+        total_matches,
+        limit: query.limit,
         results,
         query_time_ms: duration.as_millis() as u64,
+        facets,
+        parsed_query: parsed_query_debug,
     })
 }
 
+/// Blend in the recency boost (see `SearchQuery.recency_weight`) and sort hits
+/// by score, then relative path, so documents with equal scores come back
+/// (and are paged) in the same order on every run. Each hit keeps its plain
+/// relevance alongside the score when the boost applies.
+fn rank_hits(
+    searcher: &Searcher,
+    schema: &crate::indexer::IndexSchema,
+    recency_weight: f32,
+    top_docs: Vec<(Score, DocAddress)>,
+) -> AppResult<Vec<(Score, Option<Score>, DocAddress)>> {
+    let now = chrono::Utc::now().timestamp().max(0) as u64;
+    let mut keyed = Vec::with_capacity(top_docs.len());
+    for (relevance, address) in top_docs {
+        let doc: TantivyDocument = searcher
+            .doc(address)
+            .map_err(|e| AppError::SearchError(format!("Failed to retrieve doc: {}", e)))?;
+        let path = doc.get_first(schema.relative_path).and_then(|v| v.as_str()).unwrap_or("").to_string();
+        let (score, relevance) = if recency_weight > 0.0 {
+            let modified = doc.get_first(schema.modified).and_then(|v| v.as_u64()).unwrap_or(0);
+            let boost = 1.0 + recency_weight * recency_decay(modified, now);
+            (relevance * boost, Some(relevance))
+        } else {
+            (relevance, None)
+        };
+        keyed.push((score, relevance, path, address));
+    }
+    keyed.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.2.cmp(&b.2)).then_with(|| a.3.cmp(&b.3)));
+    Ok(keyed
+        .into_iter()
+        .map(|(score, relevance, _, address)| (score, relevance, address))
+        .collect())
+}
+
+/// Restrict `query` to documents whose `changed` time lies strictly between
+/// the given bounds. The range clause scores zero so ranking is unchanged.
+fn with_changed_range(
+    query: Box<dyn tantivy::query::Query>,
+    changed: tantivy::schema::Field,
+    after: Option<u64>,
+    before: Option<u64>,
+) -> Box<dyn tantivy::query::Query> {
+    if after.is_none() && before.is_none() {
+        return query;
+    }
+    let bound = |value: Option<u64>| match value {
+        Some(value) => Bound::Excluded(tantivy::Term::from_field_u64(changed, value)),
+        None => Bound::Unbounded,
+    };
+    let range = RangeQuery::new(bound(after), bound(before));
+    Box::new(BooleanQuery::new(vec![
+        (Occur::Must, query),
+        (Occur::Must, Box::new(ConstScoreQuery::new(Box::new(range), 0.0))),
+    ]))
+}
+
+/// Counts matching documents that pass the `language` and `file_pattern`
+/// filters of a search. Language is checked against its fast field; the glob
+/// needs the stored paths, so documents are only loaded when one is set.
+struct FilteredCount {
+    language: Option<String>,
+    path_matcher: Option<GlobMatcher>,
+    relative_path: tantivy::schema::Field,
+    filename: tantivy::schema::Field,
+}
+
+impl Collector for FilteredCount {
+    type Fruit = usize;
+    type Child = FilteredSegmentCount;
+
+    fn for_segment(&self, _: SegmentOrdinal, segment: &SegmentReader) -> tantivy::Result<FilteredSegmentCount> {
+        let language = match &self.language {
+            None => LanguageFilter::Any,
+            Some(language) => {
+                let ord = match segment.fast_fields().str("language")? {
+                    Some(column) => column.dictionary().term_ord(language)?.map(|ord| (column, ord)),
+                    None => None,
+                };
+                // A language absent from the segment's dictionary matches nothing
+                ord.map_or(LanguageFilter::Absent, |(column, ord)| LanguageFilter::Ord(column, ord))
+            }
+        };
+        let paths = match &self.path_matcher {
+            Some(matcher) => Some((matcher.clone(), segment.get_store_reader(1)?)),
+            None => None,
+        };
+        Ok(FilteredSegmentCount {
+            language,
+            paths,
+            relative_path: self.relative_path,
+            filename: self.filename,
+            count: 0,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, counts: Vec<usize>) -> tantivy::Result<usize> {
+        Ok(counts.into_iter().sum())
+    }
+}
+
+enum LanguageFilter {
+    Any,
+    Absent,
+    Ord(tantivy::columnar::StrColumn, u64),
+}
+
+struct FilteredSegmentCount {
+    language: LanguageFilter,
+    paths: Option<(GlobMatcher, StoreReader)>,
+    relative_path: tantivy::schema::Field,
+    filename: tantivy::schema::Field,
+    count: usize,
+}
+
+impl SegmentCollector for FilteredSegmentCount {
+    type Fruit = usize;
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        match &self.language {
+            LanguageFilter::Any => {}
+            LanguageFilter::Absent => return,
+            LanguageFilter::Ord(column, ord) => {
+                if !column.term_ords(doc).any(|o| o == *ord) {
+                    return;
+                }
+            }
+        }
+        if let Some((matcher, store)) = &self.paths {
+            let Ok(stored) = store.get::<TantivyDocument>(doc) else { return };
+            let field_matches = |field| {
+                stored
+                    .get_first(field)
+                    .and_then(|v| v.as_str())
+                    .is_some_and(|value| matcher.is_match(value))
+            };
+            if !field_matches(self.relative_path) && !field_matches(self.filename) {
+                return;
+            }
+        }
+        self.count += 1;
+    }
+
+    fn harvest(self) -> usize {
+        self.count
+    }
+}
+
+/// Texts of the `terms` whose postings include the given document, deduplicated
+/// across fields and sorted.
+fn terms_in_doc(searcher: &Searcher, address: DocAddress, terms: &[tantivy::Term]) -> Vec<String> {
+    let segment = searcher.segment_reader(address.segment_ord);
+    let mut matched = std::collections::BTreeSet::new();
+    for term in terms {
+        let value = term.value();
+        let Some(text) = value.as_str() else { continue };
+        if matched.contains(text) {
+            continue;
+        }
+        let Ok(inverted_index) = segment.inverted_index(term.field()) else { continue };
+        if let Ok(Some(mut postings)) = inverted_index.read_postings(term, IndexRecordOption::Basic) {
+            // `seek` requires a target at or past the current doc
+            if postings.doc() <= address.doc_id && postings.seek(address.doc_id) == address.doc_id {
+                matched.insert(text.to_string());
+            }
+        }
+    }
+    matched.into_iter().collect()
+}
+
+/// Plain words from a query string for snippet placement: drops boolean
+/// operators, prohibited (`-term`) clauses, quotes, grouping and field prefixes.
+fn positive_terms(query: &str) -> String {
+    query
+        .split_whitespace()
+        .filter(|word| !matches!(*word, "AND" | "OR" | "NOT") && !word.starts_with('-'))
+        .map(|word| {
+            let word = word.trim_start_matches('+');
+            let word = word.split_once(':').map_or(word, |(_, value)| value);
+            word.trim_matches(|c: char| matches!(c, '"' | '(' | ')' | '^'))
+        })
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A TODO-style marker together with the file it was found in.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileMarker {
+    pub relative_path: String,
+    #[serde(flatten)]
+    pub marker: CodeMarker,
+}
+
+/// All markers of the given kinds in a workspace index, ordered by path and
+/// line. `kinds` must be non-empty and come from `MARKER_KINDS`.
+pub fn workspace_markers(
+    index_manager: &IndexManager,
+    workspace_id: &str,
+    kinds: &[&str],
+) -> AppResult<Vec<FileMarker>> {
+    let state = index_manager.get_or_create_index(workspace_id)?;
+    let searcher = state.reader.searcher();
+    let schema = &state.schema;
+
+    let query = BooleanQuery::new(
+        kinds
+            .iter()
+            .map(|kind| {
+                let term = tantivy::Term::from_field_text(schema.marker_kinds, kind);
+                let query: Box<dyn tantivy::query::Query> =
+                    Box::new(TermQuery::new(term, IndexRecordOption::Basic));
+                (Occur::Should, query)
+            })
+            .collect(),
+    );
+    let addresses = searcher
+        .search(&query, &DocSetCollector)
+        .map_err(|e| AppError::SearchError(format!("Marker query failed: {}", e)))?;
+
+    let mut markers = Vec::new();
+    for address in addresses {
+        let doc: TantivyDocument = searcher
+            .doc(address)
+            .map_err(|e| AppError::SearchError(format!("Failed to read document: {}", e)))?;
+        let relative_path = doc
+            .get_first(schema.relative_path)
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string();
+        markers.extend(
+            doc.get_all(schema.markers)
+                .filter_map(|v| v.as_str().and_then(CodeMarker::decode))
+                .filter(|marker| kinds.contains(&marker.kind.as_str()))
+                .map(|marker| FileMarker {
+                    relative_path: relative_path.clone(),
+                    marker,
+                }),
+        );
+    }
+
+    markers.sort_by(|a, b| {
+        a.relative_path
+            .cmp(&b.relative_path)
+            .then(a.marker.line.cmp(&b.marker.line))
+    });
+    Ok(markers)
+}
+
+/// Document count and language/extension breakdown of a whole workspace index.
+pub fn workspace_facets(
+    index_manager: &IndexManager,
+    workspace_id: &str,
+) -> AppResult<(u64, SearchFacets)> {
+    let state = index_manager.get_or_create_index(workspace_id)?;
+    let searcher = state.reader.searcher();
+    let aggregations = searcher
+        .search(&AllQuery, &facet_collector()?)
+        .map_err(|e| AppError::SearchError(format!("Facet aggregation failed: {}", e)))?;
+    Ok((searcher.num_docs(), facets_from_aggregations(aggregations)))
+}
+
+/// Build a terms aggregation over the FAST `language` and `extension` fields.
+fn facet_collector() -> AppResult<AggregationCollector> {
+    let aggs: Aggregations = serde_json::from_value(serde_json::json!({
+        "language": { "terms": { "field": "language", "size": MAX_FACET_VALUES } },
+        "extension": { "terms": { "field": "extension", "size": MAX_FACET_VALUES } },
+    }))
+    .map_err(|e| AppError::SearchError(format!("Invalid facet aggregation: {}", e)))?;
+    Ok(AggregationCollector::from_aggs(aggs, AggregationLimitsGuard::default()))
+}
+
+fn facets_from_aggregations(results: AggregationResults) -> SearchFacets {
+    let counts = |name: &str| -> Vec<FacetCount> {
+        match results.0.get(name) {
+            Some(AggregationResult::BucketResult(BucketResult::Terms { buckets, .. })) => buckets
+                .iter()
+                .filter_map(|bucket| match &bucket.key {
+                    Key::Str(value) if !value.is_empty() => Some(FacetCount {
+                        value: value.clone(),
+                        count: bucket.doc_count,
+                    }),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    };
+    SearchFacets {
+        language: counts("language"),
+        extension: counts("extension"),
+    }
+}
+
 /// Generate a snippet around the first match
 fn generate_snippet(content: &str, query: &str, max_len: usize) -> (String, Option<usize>) {
-    let lower_content = content.to_lowercase();
     let lower_query = query.to_lowercase();
 
-    // Find first occurrence of any query word
+    // Find first occurrence of any query word, line by line so offsets are
+    // mapped back to `content` (see `find_ignore_case`)
     let query_words: Vec<&str> = lower_query.split_whitespace().collect();
     let mut best_pos = None;
-
-    for word in &query_words {
-        if let Some(pos) = lower_content.find(word) {
-            if best_pos.map_or(true, |bp| pos < bp) {
-                best_pos = Some(pos);
-            }
+    let mut line_start = 0;
+    for line in content.split_inclusive('\n') {
+        let hit = query_words
+            .iter()
+            .filter_map(|word| find_ignore_case(line, word))
+            .map(|(start, _)| start)
+            .min();
+        if let Some(start) = hit {
+            best_pos = Some(line_start + start);
+            break;
         }
+        line_start += line.len();
     }
 
     let pos = best_pos.unwrap_or(0);
@@ -268,19 +840,21 @@ fn generate_snippet(content: &str, query: &str, max_len: usize) -> (String, Opti
     // Calculate line number
     let line_number = content[..pos].matches('\n').count() + 1;
 
-    // Extract snippet around the match
-    let start = pos.saturating_sub(max_len / 2);
-    let end = (pos + max_len / 2).min(content.len());
+    // Extract snippet around the match, cut on char boundaries inside the
+    // window so it stays within `max_len`
+    let start = content.ceil_char_boundary(pos.saturating_sub(max_len / 2));
+    let end = content.floor_char_boundary(pos + max_len / 2);
 
-    // Align to line boundaries
+    // Align to line boundaries, but never past the match: on a line longer
+    // than the window (e.g. minified code) the snippet is cut mid-line
     let snippet_start = if start > 0 {
-        content[start..].find('\n').map(|p| start + p + 1).unwrap_or(start)
+        content[start..pos].find('\n').map(|p| start + p + 1).unwrap_or(start)
     } else {
         0
     };
 
     let snippet_end = if end < content.len() {
-        content[..end].rfind('\n').unwrap_or(end)
+        content[pos..end].rfind('\n').map(|p| pos + p).unwrap_or(end)
     } else {
         content.len()
     };
@@ -290,8 +864,24 @@ fn generate_snippet(content: &str, query: &str, max_len: usize) -> (String, Opti
     (snippet, Some(line_number))
 }
 
+/// The `radius` lines either side of 1-based `line_number`.
+fn extract_context(content: &str, line_number: usize, radius: usize) -> SearchContext {
+    let start_line = line_number.saturating_sub(radius).max(1);
+    let lines: Vec<&str> = content
+        .lines()
+        .skip(start_line - 1)
+        .take(line_number + radius + 1 - start_line)
+        .collect();
+    SearchContext {
+        start_line,
+        end_line: start_line + lines.len().saturating_sub(1),
+        text: lines.join("\n"),
+    }
+}
+
 /// Grep search within indexed workspace using the actual files.
 /// Supports sub-directory scoping via `query.path` and parallel file reading via rayon.
+/// Files are searched in path order and results come back in that order.
 /// MEMORY FIX: Uses atomic counter for early termination to avoid accumulating
 /// unbounded results in memory across all parallel threads.
 pub fn grep_workspace(
@@ -299,7 +889,7 @@ pub fn grep_workspace(
     query: &GrepQuery,
 ) -> AppResult<GrepResponse> {
     use rayon::prelude::*;
-    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::atomic::{AtomicUsize, Ordering};
     
     let start = std::time::Instant::now();
 
@@ -333,15 +923,15 @@ pub fn grep_workspace(
         .max_depth(Some(20))
         .build();
 
-    // Build regex once outside the loop
-    let regex = if query.is_regex {
+    // Build the matcher once; every worker thread borrows it
+    let matcher = if query.is_regex {
         let pattern = if query.case_sensitive {
             query.pattern.clone()
         } else {
             format!("(?i){}", query.pattern)
         };
         match Regex::new(&pattern) {
-            Ok(re) => Some(re),
+            Ok(re) => LineMatcher::Regex(re),
             Err(e) => {
                 return Err(AppError::BadRequest(format!(
                     "Invalid regex pattern '{}': {}",
@@ -349,8 +939,10 @@ pub fn grep_workspace(
                 )));
             }
         }
+    } else if query.case_sensitive {
+        LineMatcher::Literal(query.pattern.clone())
     } else {
-        None
+        LineMatcher::LiteralIgnoreCase(query.pattern.to_lowercase())
     };
 
     // Phase 1: collect file paths (fast sequential directory walk)
@@ -359,7 +951,7 @@ pub fn grep_workspace(
         globset::Glob::new(pattern).ok().map(|g| g.compile_matcher())
     });
 
-    let paths: Vec<std::path::PathBuf> = walker
+    let mut paths: Vec<std::path::PathBuf> = walker
         .filter_map(|e| e.ok())
         .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
         .filter(|entry| !IndexManager::is_build_or_output_dir(entry.path()))
@@ -374,121 +966,85 @@ pub fn grep_workspace(
         })
         .map(|entry| entry.into_path())
         .collect();
+    paths.sort_unstable();
 
-    let pattern_lower = if !query.case_sensitive {
-        query.pattern.to_lowercase()
-    } else {
-        query.pattern.clone()
-    };
-    
     let limit = query.limit;
-    let include_context = query.include_context;
-    // Cap context_lines to prevent excessive memory allocations from malicious input
-    let context_lines = query.context_lines.min(10);
-    let case_sensitive = query.case_sensitive;
-
-    // MEMORY FIX: Atomic counter for global early termination.
-    // Once we've collected enough results, all threads stop reading new files.
-    let global_count = Arc::new(AtomicUsize::new(0));
-    let limit_reached = Arc::new(AtomicBool::new(false));
-
-    // Phase 2: parallel file reading and matching with rayon
-    // MEMORY FIX: Uses early termination via atomic flag to stop processing files
-    // once the global result limit is reached.
-    let all_results: Vec<GrepResult> = paths
-        .par_iter()
-        .flat_map(|path| {
-            // Early exit: skip file entirely if limit already reached
-            if limit_reached.load(Ordering::Relaxed) {
-                return Vec::new();
-            }
-
-            let content = match std::fs::read_to_string(path) {
-                Ok(c) => c,
-                Err(_) => return Vec::new(),
-            };
-
-            let lines: Vec<&str> = content.lines().collect();
-            let mut file_results = Vec::new();
-
-            for (line_idx, line) in lines.iter().enumerate() {
-                let matches = if let Some(ref re) = regex {
-                    re.find(line).map(|m: regex::Match<'_>| (m.start(), m.end()))
-                } else if case_sensitive {
-                    line.find(&pattern_lower).map(|s| (s, s + pattern_lower.len()))
-                } else {
-                    line.to_lowercase()
-                        .find(&pattern_lower)
-                        .map(|s| (s, s + pattern_lower.len()))
-                };
+    // Per-file limit to avoid overwhelming results from one file
+    // Use a fraction of the global limit, with a minimum of 20 results per file
+    let per_file_limit = query
+        .max_matches_per_file
+        .unwrap_or((limit / 4).max(20))
+        .clamp(1, limit.max(1));
+    let options = GrepFileOptions {
+        workspace_path,
+        include_context: query.include_context,
+        // Cap context_lines to prevent excessive memory allocations from malicious input
+        context_lines: query.context_lines.min(10),
+    };
 
-                if let Some((match_start, match_end)) = matches {
-                    let relative = path
-                        .strip_prefix(workspace_path)
-                        .unwrap_or(path)
-                        .to_string_lossy()
-                        .replace('\\', "/");
-
-                    let context_before = if include_context {
-                        let start = line_idx.saturating_sub(context_lines);
-                        lines[start..line_idx]
-                            .iter()
-                            .map(|l| l.to_string())
-                            .collect()
-                    } else {
-                        vec![]
-                    };
-
-                    let context_after = if include_context {
-                        let end = (line_idx + 1 + context_lines).min(lines.len());
-                        lines[line_idx + 1..end]
-                            .iter()
-                            .map(|l| l.to_string())
-                            .collect()
-                    } else {
-                        vec![]
-                    };
-
-                    file_results.push(GrepResult {
-                        path: path.to_string_lossy().to_string(),
-                        relative_path: relative,
-                        line_number: line_idx + 1,
-                        line_content: line.to_string(),
-                        match_start,
-                        match_end,
-                        context_before,
-                        context_after,
-                    });
-
-                    // Global limit check: stop this file if overall limit is reached
-                    let count = global_count.fetch_add(1, Ordering::Relaxed) + 1;
-                    if count >= limit {
-                        limit_reached.store(true, Ordering::Relaxed);
-                        break;
-                    }
-
-                    // Per-file limit to avoid overwhelming results from one file
-                    // Use a fraction of the global limit, with a minimum of 20 results per file
-                    let per_file_limit = (limit / 4).max(20);
-                    if file_results.len() >= per_file_limit {
-                        break;
-                    }
+    // Phase 2: parallel file reading and matching with rayon, one chunk of
+    // files at a time. Each chunk's per-file matches are stitched back in path
+    // order, so the same tree always yields the same results; scanning stops
+    // after the chunk that fills the limit.
+    let mut results: Vec<GrepResult> = Vec::new();
+    let mut truncated = false;
+    let mut files_searched = 0;
+    for chunk in paths.chunks(GREP_CHUNK_FILES) {
+        let remaining = limit - results.len();
+        // MEMORY FIX: Atomic per-file match counts for early termination.
+        // A file is skipped once the files before it in the chunk have
+        // already produced more than the remaining matches, so skipping
+        // never changes which results are returned.
+        let counts: Vec<AtomicUsize> = chunk.iter().map(|_| AtomicUsize::new(PENDING)).collect();
+        let per_file: Vec<Option<Vec<GrepResult>>> = chunk
+            .par_iter()
+            .enumerate()
+            .map(|(i, path)| {
+                let found_before: usize = counts[..i]
+                    .iter()
+                    .map(|c| c.load(Ordering::Relaxed))
+                    .filter(|&c| c != PENDING)
+                    .sum();
+                if found_before > remaining {
+                    counts[i].store(0, Ordering::Relaxed);
+                    return None;
                 }
+                // One match past a cap proves the results are truncated.
+                let max_matches = per_file_limit.min(remaining) + 1;
+                let file = grep_file(path, &matcher, max_matches, &options);
+                counts[i].store(file.len(), Ordering::Relaxed);
+                Some(file)
+            })
+            .collect();
+
+        for file in per_file {
+            let Some(mut file) = file else {
+                truncated = true;
+                continue;
+            };
+            files_searched += 1;
+            if file.len() > per_file_limit {
+                file.truncate(per_file_limit);
+                truncated = true;
             }
-
-            file_results
-        })
-        .collect();
-
-    // Truncate to limit (rayon may produce more than limit across all files)
-    let results: Vec<GrepResult> = all_results.into_iter().take(limit).collect();
-    let files_searched = paths.len();
+            let room = limit - results.len();
+            if file.len() > room {
+                file.truncate(room);
+                truncated = true;
+            }
+            results.append(&mut file);
+        }
+        if truncated && results.len() >= limit {
+            break;
+        }
+    }
     let duration = start.elapsed();
 
     info!(
         pattern = %query.pattern,
         total_matches = results.len(),
         files_searched,
+        truncated,
         query_time_ms = duration.as_millis() as u64,
         "Grep search completed"
     );
@@ -497,6 +1053,520 @@ pub fn grep_workspace(
         total_matches: results.len(),
         results,
         files_searched,
+        limit,
+        truncated,
         query_time_ms: duration.as_millis() as u64,
     })
 }
+
+/// Files grepped in parallel per batch; matches are stitched in path order
+/// between batches.
+const GREP_CHUNK_FILES: usize = 256;
+
+/// Per-file match count placeholder for files still being searched.
+const PENDING: usize = usize::MAX;
+
+/// Longest line (in bytes, before ellipses) that grep returns in full.
+const MAX_GREP_LINE_BYTES: usize = 500;
+
+/// Marks where `clip_line`/`clip_context_line` cut a line.
+const ELLIPSIS: &str = "…";
+
+/// `line` cut to about `MAX_GREP_LINE_BYTES` around the match at
+/// `start..end`, together with the match's range in the returned text.
+/// Cuts fall on char boundaries.
+fn clip_line(line: &str, start: usize, end: usize) -> (String, usize, usize) {
+    if line.len() <= MAX_GREP_LINE_BYTES {
+        return (line.to_string(), start, end);
+    }
+    // Centre the window on the match; a match longer than the window is cut
+    let lead = MAX_GREP_LINE_BYTES.saturating_sub(end - start) / 2;
+    let from = start.saturating_sub(lead).min(line.len() - MAX_GREP_LINE_BYTES);
+    let from = line.floor_char_boundary(from);
+    let to = line.floor_char_boundary(from + MAX_GREP_LINE_BYTES);
+
+    let prefix = if from > 0 { ELLIPSIS } else { "" };
+    let suffix = if to < line.len() { ELLIPSIS } else { "" };
+    let clipped = format!("{}{}{}", prefix, &line[from..to], suffix);
+    let shift = |offset: usize| prefix.len() + offset.clamp(from, to) - from;
+    (clipped, shift(start), shift(end))
+}
+
+/// A context line cut to `MAX_GREP_LINE_BYTES` from its start.
+fn clip_context_line(line: &str) -> String {
+    if line.len() <= MAX_GREP_LINE_BYTES {
+        return line.to_string();
+    }
+    format!("{}{}", &line[..line.floor_char_boundary(MAX_GREP_LINE_BYTES)], ELLIPSIS)
+}
+
+/// How `grep_workspace` finds a match within a line.
+enum LineMatcher {
+    Regex(Regex),
+    Literal(String),
+    /// Needle already lowercased.
+    LiteralIgnoreCase(String),
+}
+
+impl LineMatcher {
+    /// Byte range of the first match in `line`.
+    fn find(&self, line: &str) -> Option<(usize, usize)> {
+        match self {
+            Self::Regex(re) => re.find(line).map(|m| (m.start(), m.end())),
+            Self::Literal(needle) => line.find(needle.as_str()).map(|s| (s, s + needle.len())),
+            Self::LiteralIgnoreCase(needle) => find_ignore_case(line, needle),
+        }
+    }
+}
+
+/// Byte range in `line` of the first case-insensitive match of the
+/// lowercased `needle`. Lowercasing can change a char's byte length (e.g.
+/// `İ`), so offsets found in the lowercased line are mapped back to `line`.
+fn find_ignore_case(line: &str, needle: &str) -> Option<(usize, usize)> {
+    if line.is_ascii() {
+        return line
+            .to_ascii_lowercase()
+            .find(needle)
+            .map(|s| (s, s + needle.len()));
+    }
+
+    // Offset in `line` of the char each byte of `lowered` came from
+    let mut lowered = String::with_capacity(line.len());
+    let mut origin = Vec::with_capacity(line.len());
+    for (offset, c) in line.char_indices() {
+        for lower in c.to_lowercase() {
+            lowered.push(lower);
+            origin.extend(std::iter::repeat_n(offset, lower.len_utf8()));
+        }
+    }
+
+    let start = lowered.find(needle)?;
+    if needle.is_empty() {
+        return Some((origin[start], origin[start]));
+    }
+    // The match ends after the whole original char its last byte came from
+    let last_char = origin[start + needle.len() - 1];
+    let end = last_char + line[last_char..].chars().next().map_or(0, char::len_utf8);
+    Some((origin[start], end))
+}
+
+struct GrepFileOptions<'a> {
+    workspace_path: &'a str,
+    include_context: bool,
+    context_lines: usize,
+}
+
+/// Search one file line by line, stopping after `max_matches` matches.
+/// Unreadable and non-UTF-8 files yield no matches.
+fn grep_file(
+    path: &std::path::Path,
+    matcher: &LineMatcher,
+    max_matches: usize,
+    options: &GrepFileOptions<'_>,
+) -> Vec<GrepResult> {
+    let mut results = Vec::new();
+    let content = match std::fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(_) => return results,
+    };
+    let relative = path
+        .strip_prefix(options.workspace_path)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/");
+    let context_lines = options.context_lines;
+
+    let lines: Vec<&str> = content.lines().collect();
+    for (line_idx, line) in lines.iter().enumerate() {
+        let Some((match_start, match_end)) = matcher.find(line) else {
+            continue;
+        };
+
+        let (context_before, context_after) = if options.include_context {
+            let start = line_idx.saturating_sub(context_lines);
+            let end = (line_idx + 1 + context_lines).min(lines.len());
+            (
+                lines[start..line_idx].iter().map(|l| clip_context_line(l)).collect(),
+                lines[line_idx + 1..end].iter().map(|l| clip_context_line(l)).collect(),
+            )
+        } else {
+            (vec![], vec![])
+        };
+
+        let (line_content, match_start, match_end) = clip_line(line, match_start, match_end);
+        results.push(GrepResult {
+            path: path.to_string_lossy().to_string(),
+            relative_path: relative.clone(),
+            line_number: line_idx + 1,
+            line_content,
+            match_start,
+            match_end,
+            context_before,
+            context_after,
+        });
+        if results.len() >= max_matches {
+            break;
+        }
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grep_query(fields: serde_json::Value) -> GrepQuery {
+        serde_json::from_value(fields).unwrap()
+    }
+
+    /// `dirs` × `files` text files, each with "needle" on lines 1 and 3.
+    fn synthetic_tree(dirs: usize, files: usize) -> tempfile::TempDir {
+        let root = tempfile::tempdir().unwrap();
+        for d in 0..dirs {
+            let dir = root.path().join(format!("dir{:02}", d));
+            std::fs::create_dir(&dir).unwrap();
+            for f in 0..files {
+                std::fs::write(dir.join(format!("file{:02}.txt", f)), "needle one\nhay\nneedle two\n").unwrap();
+            }
+        }
+        root
+    }
+
+    fn locations(response: &GrepResponse) -> Vec<(String, usize)> {
+        response
+            .results
+            .iter()
+            .map(|r| (r.relative_path.clone(), r.line_number))
+            .collect()
+    }
+
+    #[test]
+    fn parallel_grep_returns_every_match_in_path_order() {
+        // More files than one chunk, so several chunks are stitched together
+        let tree = synthetic_tree(30, 20);
+        let root = tree.path().to_string_lossy();
+        let query = grep_query(serde_json::json!({ "pattern": "needle", "limit": 10_000 }));
+
+        let response = grep_workspace(&root, &query).unwrap();
+        assert_eq!(response.files_searched, 600);
+        assert_eq!(response.total_matches, 1200);
+        assert!(!response.truncated);
+
+        let mut expected = Vec::new();
+        for d in 0..30 {
+            for f in 0..20 {
+                let path = format!("dir{:02}/file{:02}.txt", d, f);
+                expected.push((path.clone(), 1));
+                expected.push((path, 3));
+            }
+        }
+        assert_eq!(locations(&response), expected);
+
+        // Same tree, same results, whatever order the workers finish in
+        for _ in 0..3 {
+            assert_eq!(locations(&grep_workspace(&root, &query).unwrap()), expected);
+        }
+    }
+
+    #[test]
+    fn limit_keeps_the_first_matches_in_path_order() {
+        let tree = synthetic_tree(30, 20);
+        let root = tree.path().to_string_lossy();
+        let all_query = grep_query(serde_json::json!({ "pattern": "needle", "limit": 10_000 }));
+        let all = grep_workspace(&root, &all_query).unwrap();
+
+        let capped_query = grep_query(serde_json::json!({ "pattern": "needle", "limit": 301 }));
+        let capped = grep_workspace(&root, &capped_query).unwrap();
+        assert_eq!(capped.total_matches, 301);
+        assert!(capped.truncated);
+        assert_eq!(locations(&capped), locations(&all)[..301]);
+    }
+
+    #[test]
+    fn max_matches_per_file_caps_each_file() {
+        let tree = synthetic_tree(2, 3);
+        let root = tree.path().to_string_lossy();
+        let query = grep_query(serde_json::json!({ "pattern": "needle", "max_matches_per_file": 1 }));
+
+        let response = grep_workspace(&root, &query).unwrap();
+        assert_eq!(response.total_matches, 6);
+        assert!(response.truncated);
+        assert!(response.results.iter().all(|r| r.line_number == 1));
+    }
+
+    #[test]
+    fn case_sensitivity_and_regex_options() {
+        let tree = synthetic_tree(1, 1);
+        let root = tree.path().to_string_lossy();
+
+        let sensitive = grep_query(serde_json::json!({ "pattern": "NEEDLE", "case_sensitive": true }));
+        assert_eq!(grep_workspace(&root, &sensitive).unwrap().total_matches, 0);
+        let insensitive = grep_query(serde_json::json!({ "pattern": "NEEDLE" }));
+        assert_eq!(grep_workspace(&root, &insensitive).unwrap().total_matches, 2);
+
+        let regex = grep_query(serde_json::json!({ "pattern": "needle (one|three)", "is_regex": true }));
+        let response = grep_workspace(&root, &regex).unwrap();
+        assert_eq!(locations(&response), [("dir00/file00.txt".to_string(), 1)]);
+
+        let invalid = grep_query(serde_json::json!({ "pattern": "(", "is_regex": true }));
+        assert!(matches!(grep_workspace(&root, &invalid), Err(AppError::BadRequest(_))));
+    }
+
+    /// `line` clipped around the first "needle", with the text its returned
+    /// match range covers.
+    fn clip_around_needle(line: &str) -> (String, String) {
+        let start = line.find("needle").unwrap();
+        let (clipped, from, to) = clip_line(line, start, start + "needle".len());
+        let matched = clipped[from..to].to_string();
+        (clipped, matched)
+    }
+
+    #[test]
+    fn long_line_match_near_the_start_is_cut_after_it() {
+        let line = format!("a needle {}", "x".repeat(100_000));
+
+        let (clipped, matched) = clip_around_needle(&line);
+        assert_eq!(matched, "needle");
+        assert!(clipped.starts_with("a needle"));
+        assert!(clipped.ends_with(ELLIPSIS));
+        assert_eq!(clipped.len(), MAX_GREP_LINE_BYTES + ELLIPSIS.len());
+    }
+
+    #[test]
+    fn long_line_match_in_the_middle_is_centred() {
+        let line = format!("{}needle{}", "x".repeat(50_000), "y".repeat(50_000));
+
+        let (clipped, matched) = clip_around_needle(&line);
+        assert_eq!(matched, "needle");
+        assert!(clipped.starts_with(&format!("{}x", ELLIPSIS)));
+        assert!(clipped.ends_with(&format!("y{}", ELLIPSIS)));
+        assert_eq!(clipped.len(), MAX_GREP_LINE_BYTES + 2 * ELLIPSIS.len());
+        let at = clipped.find("needle").unwrap();
+        let before = at - ELLIPSIS.len();
+        let after = clipped.len() - ELLIPSIS.len() - (at + "needle".len());
+        assert!(before.abs_diff(after) <= 1, "{} before, {} after", before, after);
+    }
+
+    #[test]
+    fn long_line_match_near_the_end_is_cut_before_it() {
+        let line = format!("{} needle!", "x".repeat(100_000));
+
+        let (clipped, matched) = clip_around_needle(&line);
+        assert_eq!(matched, "needle");
+        assert!(clipped.starts_with(ELLIPSIS));
+        assert!(clipped.ends_with("needle!"));
+        assert_eq!(clipped.len(), MAX_GREP_LINE_BYTES + ELLIPSIS.len());
+    }
+
+    #[test]
+    fn long_line_cuts_fall_on_char_boundaries() {
+        // Three-byte chars, so a byte-exact window would split one
+        let line = format!("{}needle{}", "語".repeat(20_000), "é".repeat(20_000));
+
+        let (clipped, matched) = clip_around_needle(&line);
+        assert_eq!(matched, "needle");
+        assert!(clipped.len() <= MAX_GREP_LINE_BYTES + 2 * ELLIPSIS.len());
+        assert_eq!(clip_context_line(&line).chars().last(), ELLIPSIS.chars().next());
+    }
+
+    #[test]
+    fn grep_caps_a_megabyte_single_line_file() {
+        let root = tempfile::tempdir().unwrap();
+        let line = format!("{}needle{}", "x".repeat(512 * 1024), "y".repeat(512 * 1024));
+        let contents = format!("before\n{}\nafter\n", line);
+        std::fs::write(root.path().join("bundle.min.txt"), contents).unwrap();
+        let query = grep_query(serde_json::json!({ "pattern": "needle", "include_context": true }));
+
+        let response = grep_workspace(&root.path().to_string_lossy(), &query).unwrap();
+        assert_eq!(response.total_matches, 1);
+        let result = &response.results[0];
+        assert_eq!(result.line_number, 2);
+        assert!(result.line_content.len() <= MAX_GREP_LINE_BYTES + 2 * ELLIPSIS.len());
+        assert_eq!(&result.line_content[result.match_start..result.match_end], "needle");
+        assert_eq!(result.context_before, ["before"]);
+        assert_eq!(result.context_after, ["after"]);
+    }
+
+    #[test]
+    fn find_ignore_case_maps_offsets_back_to_the_line() {
+        assert_eq!(find_ignore_case("😀 Hello", "hello"), Some((5, 10)));
+        assert_eq!(find_ignore_case("日本語テスト", "テスト"), Some((9, 18)));
+        assert_eq!(find_ignore_case("日本語テスト", "ない"), None);
+
+        // `İ` is two bytes but lowercases to three (`i` + U+0307)
+        assert_eq!(find_ignore_case("İstanbul", "stanbul"), Some((2, 9)));
+        assert_eq!(find_ignore_case("İstanbul", "i"), Some((0, 2)));
+        assert_eq!(find_ignore_case("İİ Foo", "foo"), Some((5, 8)));
+        let line = "İİ Foo";
+        let (start, end) = find_ignore_case(line, "foo").unwrap();
+        assert_eq!(&line[start..end], "Foo");
+    }
+
+    #[test]
+    fn snippet_window_cuts_on_char_boundaries() {
+        for filler in ["😀", "語", "İ"] {
+            let content = format!("{}Needle{}", filler.repeat(300), filler.repeat(300));
+
+            let (snippet, line) = generate_snippet(&content, "needle", 101);
+            assert!(snippet.contains("Needle"), "{}", filler);
+            assert!(snippet.len() <= 101, "{}", filler);
+            assert_eq!(line, Some(1));
+        }
+    }
+
+    #[test]
+    fn snippet_of_a_match_on_a_line_longer_than_the_window() {
+        let long_line = format!("{}target{}", "a".repeat(1000), "b".repeat(1000));
+        let content = format!("first line\nsecond line\n{}\nlast line\n", long_line);
+
+        let (snippet, line) = generate_snippet(&content, "TARGET", 200);
+        assert_eq!(line, Some(3));
+        assert!(snippet.contains("target"));
+        assert!(!snippet.contains("second line"));
+        assert!(!snippet.contains('\n'));
+        assert!(snippet.len() <= 200);
+    }
+
+    #[test]
+    fn snippet_line_number_counts_lines_before_a_multibyte_match() {
+        let content = "😀 intro\n日本語\nİİ then the Needle here\n";
+
+        let (snippet, line) = generate_snippet(content, "needle", 1000);
+        assert_eq!(line, Some(3));
+        assert_eq!(snippet, content.trim());
+    }
+
+    fn search_query(fields: serde_json::Value) -> SearchQuery {
+        serde_json::from_value(fields).unwrap()
+    }
+
+    /// Relative paths of the hits for `query` in the workspace indexed as
+    /// `ws` at `root`, best first.
+    fn ranked(manager: &IndexManager, root: &std::path::Path, query: serde_json::Value) -> Vec<String> {
+        let boosts = FieldBoosts::from_config(&crate::config::AppConfig::default());
+        search_workspace(manager, "ws", &root.to_string_lossy(), &search_query(query), boosts)
+            .unwrap()
+            .results
+            .into_iter()
+            .map(|r| r.relative_path)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn filename_match_outranks_content_only_matches() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = crate::indexer::tests::test_manager(&dir.path().join("indexes"));
+        let busy = "auth ".repeat(100);
+        let root = crate::indexer::tests::indexed_workspace(
+            &manager,
+            dir.path(),
+            &[
+                ("docs/notes.md", busy.as_str()),
+                ("src/auth.rs", "fn login() {}"),
+                ("src/session.rs", "// checks auth before every request"),
+            ],
+        )
+        .await;
+
+        let results = ranked(&manager, &root, serde_json::json!({ "query": "auth" }));
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0], "src/auth.rs");
+    }
+
+    #[tokio::test]
+    async fn identifier_styles_find_each_other() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = crate::indexer::tests::test_manager(&dir.path().join("indexes"));
+        let root = crate::indexer::tests::indexed_workspace(
+            &manager,
+            dir.path(),
+            &[
+                ("a.ts", "const name = getUserName(id);"),
+                ("b.cs", "var name = GetUserName(id);"),
+                ("c.py", "name = get_user_name(id)"),
+                ("d.sh", "echo $GET_USER_NAME"),
+                ("e.txt", "the user picks a name"),
+            ],
+        )
+        .await;
+        let identifiers = ["a.ts", "b.cs", "c.py", "d.sh"];
+
+        let queries = [
+            "getUserName",
+            "GetUserName",
+            "get_user_name",
+            "GET_USER_NAME",
+            "\"user name\"",
+        ];
+        for query in queries {
+            let mut results = ranked(&manager, &root, serde_json::json!({ "query": query }));
+            results.sort();
+            assert_eq!(results, identifiers, "{}", query);
+        }
+
+        // Loose words also match the plain-text file
+        let results = ranked(&manager, &root, serde_json::json!({ "query": "+user +name" }));
+        assert_eq!(results.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn equal_scores_are_ordered_by_path_on_every_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = crate::indexer::tests::test_manager(&dir.path().join("indexes"));
+        let names = ["m.txt", "c.txt", "x.txt", "a.txt", "k.txt", "f.txt"];
+        let files: Vec<(&str, &str)> = names.iter().map(|name| (*name, "shared words")).collect();
+        let root = crate::indexer::tests::indexed_workspace(&manager, dir.path(), &files).await;
+        // A re-added document lands after the others in the index
+        manager
+            .reindex_file("ws", "c.txt", &root.to_string_lossy(), "modify")
+            .await
+            .unwrap();
+
+        let mut expected: Vec<String> = names.iter().map(|name| name.to_string()).collect();
+        expected.sort();
+        for _ in 0..5 {
+            assert_eq!(ranked(&manager, &root, serde_json::json!({ "query": "shared" })), expected);
+        }
+
+        // Pages split the same order
+        let first = ranked(&manager, &root, serde_json::json!({ "query": "shared", "limit": 4 }));
+        let second = ranked(
+            &manager,
+            &root,
+            serde_json::json!({ "query": "shared", "limit": 4, "offset": 4 }),
+        );
+        assert_eq!([first, second].concat(), expected);
+    }
+
+    #[test]
+    fn sort_modes_fall_back_to_the_path() {
+        let result = |path: &str, score: f32, modified: u64| SearchResult {
+            path: format!("/ws/{}", path),
+            relative_path: path.to_string(),
+            filename: path.to_string(),
+            language: String::new(),
+            score,
+            relevance: None,
+            modified,
+            snippet: String::new(),
+            line_number: None,
+            context: None,
+            matched_terms: None,
+            last_commit: None,
+        };
+        let mut results = vec![
+            result("b.rs", 1.0, 10),
+            result("a.rs", 1.0, 10),
+            result("c.rs", 2.0, 5),
+        ];
+        let order = |results: &[SearchResult]| -> Vec<String> {
+            results.iter().map(|r| r.relative_path.clone()).collect()
+        };
+
+        results.sort_by(|a, b| SearchSort::Relevance.compare(a, b));
+        assert_eq!(order(&results), ["c.rs", "a.rs", "b.rs"]);
+        results.sort_by(|a, b| SearchSort::Recency.compare(a, b));
+        assert_eq!(order(&results), ["a.rs", "b.rs", "c.rs"]);
+    }
+}
@@ -1,6 +1,6 @@
 use axum::{
     extract::ws::{Message, WebSocket, WebSocketUpgrade},
-    extract::{Request, State},
+    extract::{Extension, Request, State},
     http::{HeaderValue, Method, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
@@ -8,15 +8,21 @@ use axum::{
     Router,
 };
 use futures_util::{SinkExt, StreamExt};
+use std::borrow::Cow;
 use std::sync::Arc;
 use tower_http::{
-    compression::CompressionLayer,
+    compression::{
+        predicate::{And, NotForContentType, Predicate},
+        CompressionLayer, CompressionLevel, DefaultPredicate,
+    },
     cors::CorsLayer,
     trace::TraceLayer,
 };
 
+use crate::config::{AppConfig, CompressionAlgorithm};
 use crate::routes;
 use crate::state::AppState;
+use crate::workspace::WorkspaceManager;
 
 pub fn create_app(state: AppState) -> Router {
     // Restrict CORS to localhost origins only — the Electron renderer
@@ -34,7 +40,8 @@ pub fn create_app(state: AppState) -> Router {
             Method::DELETE,
             Method::OPTIONS,
         ])
-        .allow_headers(tower_http::cors::Any);
+        .allow_headers(tower_http::cors::Any)
+        .expose_headers([axum::http::HeaderName::from_static(routes::files::FILE_SEARCH_SCOPE_HEADER)]);
 
     // Public routes — no auth required (health probes only)
     let public_routes = Router::new()
@@ -44,9 +51,18 @@ pub fn create_app(state: AppState) -> Router {
     let protected_routes = Router::new()
         // Graceful shutdown (requires auth to prevent unauthorized termination)
         .route("/shutdown", post(routes::health::shutdown_handler))
+        .route("/admin/rotate-token", post(routes::admin::rotate_token))
+        .route(
+            "/admin/log-level",
+            get(routes::admin::get_log_level).post(routes::admin::set_log_level),
+        )
+        // Feature detection (behind auth: exposes configured limits)
+        .route("/api/capabilities", get(routes::capabilities::capabilities))
         // Workspace management
+        .route("/api/languages", get(routes::languages::list_languages))
         .route("/api/workspaces", get(routes::workspace::list_workspaces))
         .route("/api/workspaces", post(routes::workspace::create_workspace))
+        .route("/api/workspaces/import", post(routes::workspace::import_workspace))
         .route(
             "/api/workspaces/{workspace_id}",
             get(routes::workspace::get_workspace),
@@ -59,11 +75,47 @@ pub fn create_app(state: AppState) -> Router {
             "/api/workspaces/{workspace_id}/activate",
             post(routes::workspace::activate_workspace),
         )
+        .route(
+            "/api/workspaces/{workspace_id}/deactivate",
+            post(routes::workspace::deactivate_workspace),
+        )
+        .route(
+            "/api/workspaces/{workspace_id}/relocate",
+            post(routes::workspace::relocate_workspace),
+        )
+        .route(
+            "/api/workspaces/{workspace_id}/warmup",
+            post(routes::search::warmup),
+        )
+        .route(
+            "/api/workspaces/{workspace_id}/export",
+            get(routes::workspace::export_workspace),
+        )
+        .route(
+            "/api/workspaces/{workspace_id}/settings",
+            post(routes::workspace::update_workspace_settings),
+        )
+        .route(
+            "/api/workspaces/{workspace_id}/watcher/pause",
+            post(routes::workspace::pause_watcher),
+        )
+        .route(
+            "/api/workspaces/{workspace_id}/watcher/resume",
+            post(routes::workspace::resume_watcher),
+        )
+        .route(
+            "/api/workspaces/{workspace_id}/watcher/restart",
+            post(routes::workspace::restart_watcher),
+        )
         // File explorer
         .route(
             "/api/workspaces/{workspace_id}/files",
             get(routes::files::list_files),
         )
+        .route(
+            "/api/workspaces/{workspace_id}/tree",
+            get(routes::files::get_tree),
+        )
         .route(
             "/api/workspaces/{workspace_id}/files/read",
             post(routes::files::read_file).get(routes::files::read_file_query),
@@ -96,10 +148,22 @@ pub fn create_app(state: AppState) -> Router {
             "/api/workspaces/{workspace_id}/files/stat",
             post(routes::files::stat_file),
         )
+        .route(
+            "/api/workspaces/{workspace_id}/files/hash",
+            post(routes::files::file_hash),
+        )
         .route(
             "/api/workspaces/{workspace_id}/files/search",
             post(routes::files::search_files),
         )
+        .route(
+            "/api/workspaces/{workspace_id}/files/quickopen",
+            get(routes::files::quick_open),
+        )
+        .route(
+            "/api/workspaces/{workspace_id}/blame",
+            get(routes::git::blame),
+        )
         .route(
             "/api/workspaces/{workspace_id}/files/mkdir",
             post(routes::files::create_directory),
@@ -109,10 +173,42 @@ pub fn create_app(state: AppState) -> Router {
             "/api/workspaces/{workspace_id}/index",
             post(routes::search::index_workspace),
         )
+        .route(
+            "/api/workspaces/{workspace_id}/index/sync",
+            post(routes::search::sync_index),
+        )
+        .route(
+            "/api/workspaces/{workspace_id}/stats",
+            get(routes::search::workspace_stats),
+        )
+        .route(
+            "/api/workspaces/{workspace_id}/markers",
+            get(routes::search::list_markers),
+        )
+        .route(
+            "/api/workspaces/{workspace_id}/index/export",
+            get(routes::search::export_index),
+        )
         .route(
             "/api/workspaces/{workspace_id}/index/status",
             get(routes::search::index_status),
         )
+        .route(
+            "/api/workspaces/{workspace_id}/index/errors",
+            get(routes::search::index_errors),
+        )
+        .route(
+            "/api/workspaces/{workspace_id}/index/explain",
+            get(routes::search::explain_index_path),
+        )
+        .route(
+            "/api/workspaces/{workspace_id}/index/plan",
+            get(routes::search::index_plan),
+        )
+        .route(
+            "/api/workspaces/{workspace_id}/index/verify",
+            post(routes::search::verify_index),
+        )
         .route(
             "/api/workspaces/{workspace_id}/search",
             post(routes::search::fulltext_search),
@@ -121,84 +217,193 @@ pub fn create_app(state: AppState) -> Router {
             "/api/workspaces/{workspace_id}/search/grep",
             post(routes::search::grep_search),
         )
+        .route(
+            "/api/workspaces/{workspace_id}/search/history",
+            get(routes::search::search_history).delete(routes::search::clear_search_history),
+        )
+        .route("/api/search/global", post(routes::search::global_search))
         // WebSocket for real-time events
         .route("/ws", get(ws_handler))
-        .layer(axum::middleware::from_fn(auth_middleware));
+        .layer(axum::middleware::from_fn_with_state(state.clone(), auth_middleware));
 
-    public_routes
+    let mut app = public_routes
         .merge(protected_routes)
-        .layer(TraceLayer::new_for_http())
-        .layer(CompressionLayer::new())
-        .layer(cors)
-        .with_state(state)
+        .layer(TraceLayer::new_for_http());
+    if let Some(compression) = compression_layer(&state.config) {
+        app = app.layer(compression);
+    }
+    app.layer(cors).with_state(state)
 }
 
-/// Middleware that validates `Authorization: Bearer <token>` against the
-/// `VYOTIQ_AUTH_TOKEN` environment variable.  If the env var is not set or
-/// empty, auth is skipped (development mode).
-async fn auth_middleware(req: Request, next: Next) -> Result<Response, (StatusCode, axum::Json<serde_json::Value>)> {
-    // Read expected token from env.  Cache via OnceLock so we only read once.
-    use std::sync::OnceLock;
-    static AUTH_TOKEN: OnceLock<Option<String>> = OnceLock::new();
-    let expected = AUTH_TOKEN.get_or_init(|| {
-        std::env::var("VYOTIQ_AUTH_TOKEN")
-            .ok()
-            .filter(|t| !t.is_empty())
-    });
+/// Response compression as configured, or `None` when disabled. The layer
+/// encodes bodies frame by frame, so streamed responses (NDJSON export) are
+/// flushed as they are produced rather than buffered. Index bundles are
+/// already gzipped and are passed through untouched.
+fn compression_layer(
+    config: &AppConfig,
+) -> Option<CompressionLayer<And<DefaultPredicate, NotForContentType>>> {
+    let algorithms = &config.compression_algorithms;
+    if algorithms.is_empty() {
+        return None;
+    }
+    let quality = config
+        .compression_level
+        .map_or(CompressionLevel::Default, CompressionLevel::Precise);
+    Some(
+        CompressionLayer::new()
+            .gzip(algorithms.contains(&CompressionAlgorithm::Gzip))
+            .zstd(algorithms.contains(&CompressionAlgorithm::Zstd))
+            .quality(quality)
+            .compress_when(
+                DefaultPredicate::new().and(NotForContentType::const_new("application/gzip")),
+            ),
+    )
+}
+
+/// Middleware that validates `Authorization: Bearer <token>` (or `?token=`)
+/// against the global token (`VYOTIQ_AUTH_TOKEN`, or the latest one set via
+/// `/admin/rotate-token`). Routes under
+/// `/api/workspaces/{id}/` of a workspace with its own token also accept that
+/// token, and require it or the global one even when no global token is set.
+/// `/admin/*` routes accept only the global token, or the bootstrap token
+/// while no global token is set, and require one of them as soon as any
+/// token is configured, so a workspace token can't be used to mint a global
+/// one. With no token configured at
+/// all, auth is skipped (development mode).
+///
+/// Requests that pass carry their `Credentials`, which routes spanning
+/// several workspaces use to leave out the ones the caller can't access.
+async fn auth_middleware(
+    State(state): State<AppState>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, (StatusCode, axum::Json<serde_json::Value>)> {
+    // Read on every request so a rotated token takes effect immediately
+    let expected = state.auth_token_hash.read().clone();
+    let credentials = Credentials::of(&req, expected.as_deref());
 
-    let expected_token = match expected {
-        Some(t) => t.as_str(),
-        None => return Ok(next.run(req).await), // No token configured — skip auth
+    let authorized = if req.uri().path().starts_with("/admin/") {
+        let any_token = expected.is_some()
+            || state.bootstrap_token_hash.is_some()
+            || state.workspace_manager.has_auth_tokens();
+        // The bootstrap token only sets the first global token
+        let bootstrap = state.bootstrap_token_hash.as_deref().filter(|_| expected.is_none());
+        if credentials.presents(bootstrap) {
+            req.extensions_mut().insert(BootstrapAuthorized);
+        }
+        !any_token || credentials.global || credentials.presents(bootstrap)
+    } else {
+        let workspace_token_hash = workspace_id_of(req.uri().path())
+            .and_then(|id| state.workspace_manager.auth_token_hash(&id));
+        // No token configured — skip auth
+        (expected.is_none() && workspace_token_hash.is_none())
+            || credentials.global
+            || credentials.presents(workspace_token_hash.as_deref())
     };
+    if !authorized {
+        tracing::warn!("Missing or invalid auth credentials — rejecting request");
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            axum::Json(serde_json::json!({"error": "Unauthorized", "status": 401})),
+        ));
+    }
+
+    req.extensions_mut().insert(credentials);
+    Ok(next.run(req).await)
+}
+
+/// Request extension set when the caller presented `VYOTIQ_BOOTSTRAP_TOKEN`
+/// on an `/admin/*` route while no global token was set.
+#[derive(Debug, Clone, Copy)]
+pub struct BootstrapAuthorized;
+
+/// The tokens a request presented, attached to it by `auth_middleware`.
+#[derive(Debug, Clone)]
+pub struct Credentials {
+    /// One of them is the global token.
+    global: bool,
+    /// SHA-256 of each presented token.
+    token_hashes: Vec<String>,
+}
+
+impl Credentials {
+    fn of(req: &Request, global_hash: Option<&str>) -> Self {
+        let token_hashes: Vec<String> = presented_tokens(req)
+            .into_iter()
+            .map(crate::workspace::hash_auth_token)
+            .collect();
+        let global = global_hash
+            .is_some_and(|hash| token_hashes.iter().any(|t| hashes_match(t, hash)));
+        Self { global, token_hashes }
+    }
+
+    /// Whether one of the tokens hashes to `hash`; false without a hash.
+    fn presents(&self, hash: Option<&str>) -> bool {
+        hash.is_some_and(|hash| self.token_hashes.iter().any(|t| hashes_match(t, hash)))
+    }
+
+    /// Whether the caller may reach workspace `id`: always when it has no
+    /// token of its own, otherwise with that token or the global one.
+    pub fn can_access(&self, workspaces: &WorkspaceManager, id: &str) -> bool {
+        self.global || workspaces.auth_token_hash(id).is_none_or(|hash| self.presents(Some(&hash)))
+    }
+}
 
-    // 1. Check Authorization: Bearer <token> header
-    // Use constant-time comparison to prevent timing side-channel attacks
+/// Constant-time comparison of two token hashes. Tokens are compared by
+/// their fixed-length SHA-256 rather than directly, since `ct_eq` on slices
+/// of different lengths returns early and would leak the token's length.
+fn hashes_match(a: &str, b: &str) -> bool {
     use subtle::ConstantTimeEq;
+    a.as_bytes().ct_eq(b.as_bytes()).into()
+}
+
+/// Tokens a request presents: an `Authorization: Bearer <token>` header, and
+/// `?token=<token>` query parameters (for WebSocket connections, since the
+/// browser WebSocket API does not support custom headers).
+fn presented_tokens(req: &Request) -> Vec<&str> {
+    let mut tokens = Vec::new();
 
     let auth_header = req
         .headers()
         .get("authorization")
         .and_then(|v| v.to_str().ok());
-
-    if let Some(header) = auth_header {
-        if header.len() > 7 && header[..7].eq_ignore_ascii_case("bearer ") {
-            let token = &header[7..];
-            if token.as_bytes().ct_eq(expected_token.as_bytes()).into() {
-                return Ok(next.run(req).await);
-            }
-        }
+    if let Some(header) = auth_header
+        && header.len() > 7
+        && header[..7].eq_ignore_ascii_case("bearer ")
+    {
+        tokens.push(&header[7..]);
     }
 
-    // 2. Check ?token=<token> query parameter (for WebSocket connections,
-    //    since the browser WebSocket API does not support custom headers).
     if let Some(query) = req.uri().query() {
-        for pair in query.split('&') {
-            if let Some(val) = pair.strip_prefix("token=") {
-                if val.as_bytes().ct_eq(expected_token.as_bytes()).into() {
-                    return Ok(next.run(req).await);
-                }
-            }
-        }
+        tokens.extend(query.split('&').filter_map(|pair| pair.strip_prefix("token=")));
     }
+    tokens
+}
 
-    tracing::warn!("Missing or invalid auth credentials — rejecting request");
-    Err((
-        StatusCode::UNAUTHORIZED,
-        axum::Json(serde_json::json!({"error": "Unauthorized", "status": 401})),
-    ))
+/// Workspace id of a `/api/workspaces/{id}/...` route, if the path is one.
+///
+/// Percent-decoded the same way the `Path` extractor decodes it, so an
+/// encoded id can't reach a workspace without its token being checked.
+fn workspace_id_of(path: &str) -> Option<Cow<'_, str>> {
+    let id = path.strip_prefix("/api/workspaces/")?.split('/').next()?;
+    (!id.is_empty()).then(|| percent_encoding::percent_decode_str(id).decode_utf8_lossy())
 }
 
 async fn ws_handler(
     ws: WebSocketUpgrade,
     State(state): State<AppState>,
+    Extension(credentials): Extension<Credentials>,
 ) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_socket(socket, state))
+    ws.on_upgrade(move |socket| handle_socket(socket, state, credentials))
 }
 
 /// Bidirectional WebSocket handler
 /// Server → Client: broadcasts ServerEvents as JSON (filtered by subscribed workspaces)
 /// Client → Server: accepts commands for real-time operations
-async fn handle_socket(socket: WebSocket, state: AppState) {
+///
+/// Events and commands of workspaces with their own token are dropped unless
+/// the connection presented that token or the global one.
+async fn handle_socket(socket: WebSocket, state: AppState, credentials: Credentials) {
     tracing::info!("WebSocket client connected");
     let mut rx = state.event_tx.subscribe();
     let (mut sender, mut receiver) = socket.split();
@@ -208,6 +413,9 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
     let subscribed: Arc<parking_lot::Mutex<std::collections::HashSet<String>>> =
         Arc::new(parking_lot::Mutex::new(std::collections::HashSet::new()));
     let subscribed_for_send = subscribed.clone();
+    let credentials = Arc::new(credentials);
+    let credentials_for_send = credentials.clone();
+    let workspaces_for_send = state.workspace_manager.clone();
 
     // Server → Client: forward broadcast events, filtered by subscription
     let mut send_task = tokio::spawn(async move {
@@ -215,21 +423,24 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
             match rx.recv().await {
                 Ok(event) => {
                     // Filter: only send events for subscribed workspaces
-                    // If no subscriptions yet, send all events (backward compat)
-                    {
+                    // If no subscriptions yet, send all events (backward compat).
+                    // Global events (no workspace) always go through.
+                    if let Some(ws_id) = event.workspace_id() {
+                        if !credentials_for_send.can_access(&workspaces_for_send, ws_id) {
+                            continue;
+                        }
                         let subs = subscribed_for_send.lock();
-                        if !subs.is_empty() && !subs.contains(event.workspace_id()) {
+                        if !subs.is_empty() && !subs.contains(ws_id) {
                             continue;
                         }
                     }
-                    if let Ok(json) = serde_json::to_string(&event) {
-                        if sender
+                    if let Ok(json) = serde_json::to_string(&event)
+                        && sender
                             .send(Message::Text(json.into()))
                             .await
                             .is_err()
-                        {
-                            break;
-                        }
+                    {
+                        break;
                     }
                 }
                 Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
@@ -262,16 +473,21 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
                             let file_path = cmd.get("path").and_then(|v| v.as_str()).unwrap_or("");
                             let change_type = cmd.get("change_type").and_then(|v| v.as_str()).unwrap_or("modify");
 
-                            if !ws_id.is_empty() && !file_path.is_empty() {
+                            if !credentials.can_access(&workspace_manager, ws_id) {
+                                tracing::warn!("WebSocket reindex_file rejected: no access to workspace {}", ws_id);
+                            } else if !ws_id.is_empty() && !file_path.is_empty() {
                                 // Validate the file path against the workspace to prevent path traversal
                                 if let Err(e) = workspace_manager.validate_path(ws_id, file_path) {
                                     tracing::warn!("WebSocket reindex_file path validation failed: {}", e);
                                 } else if let Ok(ws) = workspace_manager.get_workspace(ws_id) {
-                                    if let Err(e) = index_manager
+                                    match index_manager
                                         .reindex_file(ws_id, file_path, &ws.path, change_type)
                                         .await
                                     {
-                                        tracing::warn!("Incremental reindex failed: {}", e);
+                                        Ok(delta) => {
+                                            let _ = workspace_manager.adjust_workspace_stats(ws_id, delta);
+                                        }
+                                        Err(e) => tracing::warn!("Incremental reindex failed: {}", e),
                                     }
                                 }
                             }
@@ -292,16 +508,18 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
                         }
                         "trigger_index" => {
                             let ws_id = cmd.get("workspace_id").and_then(|v| v.as_str()).unwrap_or("").to_string();
-                            if !ws_id.is_empty() {
-                                if let Ok(ws) = workspace_manager.get_workspace(&ws_id) {
-                                    crate::routes::search::spawn_background_indexing(
-                                        ws_id,
-                                        ws.path.clone(),
-                                        index_manager.clone(),
-                                        workspace_manager.clone(),
-                                        event_tx.clone(),
-                                    );
-                                }
+                            if !credentials.can_access(&workspace_manager, &ws_id) {
+                                tracing::warn!("WebSocket trigger_index rejected: no access to workspace {}", ws_id);
+                            } else if !ws_id.is_empty()
+                                && let Ok(ws) = workspace_manager.get_workspace(&ws_id)
+                            {
+                                crate::routes::search::spawn_background_indexing(
+                                    ws_id,
+                                    ws.path.clone(),
+                                    index_manager.clone(),
+                                    workspace_manager.clone(),
+                                    event_tx.clone(),
+                                );
                             }
                         }
                         _ => {
@@ -324,3 +542,200 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
     }
     tracing::info!("WebSocket client disconnected");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use tower::ServiceExt;
+
+    async fn test_state(data_dir: &std::path::Path) -> AppState {
+        let config = AppConfig {
+            data_dir: data_dir.to_string_lossy().into_owned(),
+            ..AppConfig::default()
+        };
+        let (_, log_filter) = crate::logging::reloadable_filter();
+        let mut state = AppState::new(config, log_filter).await.unwrap();
+        *state.auth_token_hash.write() = None;
+        state.bootstrap_token_hash = None;
+        state
+    }
+
+    async fn rotate(state: &AppState, bearer: Option<&str>) -> StatusCode {
+        let mut req = Request::builder()
+            .method("POST")
+            .uri("/admin/rotate-token")
+            .header("content-type", "application/json");
+        if let Some(token) = bearer {
+            req = req.header("authorization", format!("Bearer {}", token));
+        }
+        let req = req.body(Body::from(r#"{"token":"minted"}"#)).unwrap();
+        create_app(state.clone()).oneshot(req).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn workspace_token_does_not_open_admin_routes() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let root = tempfile::tempdir().unwrap();
+        let state = test_state(data_dir.path()).await;
+        let ws = state
+            .workspace_manager
+            .create_workspace("ws".into(), root.path().to_string_lossy().into_owned(), false)
+            .unwrap();
+        state.workspace_manager.set_auth_token(&ws.id, Some("ws-secret")).unwrap();
+
+        assert_eq!(rotate(&state, None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(rotate(&state, Some("ws-secret")).await, StatusCode::UNAUTHORIZED);
+        assert!(state.auth_token_hash.read().is_none());
+
+        let req = Request::builder().uri("/admin/log-level").body(Body::empty()).unwrap();
+        let status = create_app(state.clone()).oneshot(req).await.unwrap().status();
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    async fn get_json(state: &AppState, uri: &str, bearer: Option<&str>) -> serde_json::Value {
+        let mut req = Request::builder().uri(uri);
+        if let Some(token) = bearer {
+            req = req.header("authorization", format!("Bearer {}", token));
+        }
+        let response = create_app(state.clone())
+            .oneshot(req.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn protected_workspace_hidden_without_its_token() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let open_root = tempfile::tempdir().unwrap();
+        let protected_root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(protected_root.path().join("src")).unwrap();
+        let state = test_state(data_dir.path()).await;
+        let manager = &state.workspace_manager;
+        manager
+            .create_workspace("open".into(), open_root.path().to_string_lossy().into_owned(), false)
+            .unwrap();
+        let protected = manager
+            .create_workspace("protected".into(), protected_root.path().to_string_lossy().into_owned(), false)
+            .unwrap();
+        manager.set_auth_token(&protected.id, Some("ws-secret")).unwrap();
+
+        let names = |listing: serde_json::Value| -> Vec<String> {
+            let mut names: Vec<String> = listing
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|ws| ws["name"].as_str().unwrap().to_string())
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names(get_json(&state, "/api/workspaces", None).await), ["open"]);
+        assert_eq!(
+            names(get_json(&state, "/api/workspaces", Some("ws-secret")).await),
+            ["open", "protected"]
+        );
+
+        // A workspace inside the protected root would expose its files
+        let body = serde_json::json!({
+            "name": "inner",
+            "path": protected_root.path().join("src").to_string_lossy(),
+        });
+        let req = Request::builder()
+            .method("POST")
+            .uri("/api/workspaces")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let status = create_app(state.clone()).oneshot(req).await.unwrap().status();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn first_global_token_requires_bootstrap_token() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let mut state = test_state(data_dir.path()).await;
+
+        // Development mode: the route is reachable, but there is nothing to rotate from
+        assert_eq!(rotate(&state, None).await, StatusCode::FORBIDDEN);
+        assert!(state.auth_token_hash.read().is_none());
+
+        state.bootstrap_token_hash = Some(crate::workspace::hash_auth_token("bootstrap"));
+        assert_eq!(rotate(&state, None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(rotate(&state, Some("bootstrap")).await, StatusCode::OK);
+        assert_eq!(
+            state.auth_token_hash.read().as_deref(),
+            Some(crate::workspace::hash_auth_token("minted").as_str())
+        );
+
+        // Once set, the global token itself can rotate, and the bootstrap
+        // token no longer can
+        assert_eq!(rotate(&state, Some("bootstrap")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(rotate(&state, Some("minted")).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn workspace_token_is_checked_through_the_bearer_header() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let root = tempfile::tempdir().unwrap();
+        let state = test_state(data_dir.path()).await;
+        let ws = state
+            .workspace_manager
+            .create_workspace("ws".into(), root.path().to_string_lossy().into_owned(), false)
+            .unwrap();
+        state.workspace_manager.set_auth_token(&ws.id, Some("ws-secret")).unwrap();
+
+        let status = |bearer: Option<&'static str>| {
+            let mut req = Request::builder().uri(format!("/api/workspaces/{}", ws.id));
+            if let Some(token) = bearer {
+                req = req.header("authorization", format!("Bearer {}", token));
+            }
+            let app = create_app(state.clone());
+            async move { app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap().status() }
+        };
+        assert_eq!(status(Some("ws-secret")).await, StatusCode::OK);
+        // Same length, different token
+        assert_eq!(status(Some("ws-secreT")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some("ws-secret-but-longer")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(None).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn percent_encoded_workspace_id_still_needs_its_token() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let root = tempfile::tempdir().unwrap();
+        let state = test_state(data_dir.path()).await;
+        let ws = state
+            .workspace_manager
+            .create_workspace("ws".into(), root.path().to_string_lossy().into_owned(), false)
+            .unwrap();
+        state.workspace_manager.set_auth_token(&ws.id, Some("ws-secret")).unwrap();
+
+        // The `Path` extractor decodes this back to the real id
+        let first = ws.id.as_bytes()[0];
+        let encoded = format!("/api/workspaces/%{:02X}{}", first, &ws.id[1..]);
+        let status = |bearer: Option<&'static str>| {
+            let mut req = Request::builder().uri(encoded.clone());
+            if let Some(token) = bearer {
+                req = req.header("authorization", format!("Bearer {}", token));
+            }
+            let app = create_app(state.clone());
+            async move { app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap().status() }
+        };
+        assert_eq!(status(None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some("ws-secret")).await, StatusCode::OK);
+    }
+
+    #[test]
+    fn hashes_match_compares_whole_hashes() {
+        let hash = crate::workspace::hash_auth_token("secret");
+        assert!(hashes_match(&hash, &crate::workspace::hash_auth_token("secret")));
+        assert!(!hashes_match(&hash, &crate::workspace::hash_auth_token("Secret")));
+        // A prefix or an extension of the hash never matches
+        assert!(!hashes_match(&hash, &hash[..hash.len() - 1]));
+        assert!(!hashes_match(&hash, &format!("{}0", hash)));
+        assert!(!hashes_match(&hash, ""));
+    }
+}
@@ -1,5 +1,7 @@
 use crate::config::AppConfig;
 use crate::error::AppResult;
+use crate::git::BlameCache;
+use crate::history::SearchHistoryManager;
 use crate::indexer::IndexManager;
 use crate::watcher::FileWatcherManager;
 use crate::workspace::WorkspaceManager;
@@ -14,33 +16,76 @@ pub enum ServerEvent {
     WorkspaceCreated { workspace_id: String, path: String },
     #[serde(rename = "workspace_removed")]
     WorkspaceRemoved { workspace_id: String },
+    /// The workspace's root directory is missing (deleted or unmounted);
+    /// watching and indexing are skipped until it reappears.
+    #[serde(rename = "workspace_unavailable")]
+    WorkspaceUnavailable { workspace_id: String, path: String },
+    /// The workspace's root moved; its index is being rebuilt at `to`.
+    #[serde(rename = "workspace_relocated")]
+    WorkspaceRelocated { workspace_id: String, from: String, to: String },
     #[serde(rename = "index_started")]
     IndexingStarted { workspace_id: String },
     #[serde(rename = "index_progress")]
     IndexingProgress { workspace_id: String, indexed: usize, total: usize },
+    /// `skipped_files` files could not be read and were left out; a sample
+    /// of them with the reason is in `skipped_sample`.
     #[serde(rename = "index_complete")]
-    IndexingCompleted { workspace_id: String, total_files: usize, duration_ms: u64 },
+    IndexingCompleted {
+        workspace_id: String,
+        total_files: usize,
+        duration_ms: u64,
+        skipped_files: usize,
+        skipped_sample: Vec<crate::indexer::SkippedFile>,
+    },
     #[serde(rename = "index_error")]
     IndexingError { workspace_id: String, error: String },
+    /// An indexing pass found more than `max_indexed_files` indexable files
+    /// and left the rest out (first files in path order are kept).
+    #[serde(rename = "index_truncated")]
+    IndexTruncated { workspace_id: String, max_indexed_files: usize },
     #[serde(rename = "file_changed")]
     FileChanged { workspace_id: String, path: String, change_type: String },
+    #[serde(rename = "file_moved")]
+    FileMoved { workspace_id: String, from: String, to: String },
     #[serde(rename = "search_ready")]
     SearchReady { workspace_id: String },
+    /// The file watcher reported a problem (e.g. the OS watch limit was hit)
+    /// and some changes may be missed. The watcher keeps running.
+    #[serde(rename = "watcher_error")]
+    WatcherError { workspace_id: String, reason: String },
+    /// The file watcher stopped delivering events entirely; the index goes
+    /// stale until watching is restarted.
+    #[serde(rename = "watcher_stopped")]
+    WatcherStopped { workspace_id: String, reason: String },
+    /// Global: the server received a shutdown signal and is about to stop.
+    #[serde(rename = "server_shutting_down")]
+    ServerShuttingDown,
 }
 
 impl ServerEvent {
-    /// Extract the workspace_id from any event variant.
-    /// Used by WebSocket handler to filter events per subscribed workspace.
-    pub fn workspace_id(&self) -> &str {
+    /// The workspace an event belongs to, or None for global events.
+    ///
+    /// Workspace-scoped events are only delivered to WebSocket clients that
+    /// subscribed to that workspace (or have no subscriptions at all); global
+    /// events reach every client regardless of subscriptions. The match is
+    /// exhaustive, so every new variant has to declare its scope here.
+    pub fn workspace_id(&self) -> Option<&str> {
         match self {
-            ServerEvent::WorkspaceCreated { workspace_id, .. } => workspace_id,
-            ServerEvent::WorkspaceRemoved { workspace_id } => workspace_id,
-            ServerEvent::IndexingStarted { workspace_id } => workspace_id,
-            ServerEvent::IndexingProgress { workspace_id, .. } => workspace_id,
-            ServerEvent::IndexingCompleted { workspace_id, .. } => workspace_id,
-            ServerEvent::IndexingError { workspace_id, .. } => workspace_id,
-            ServerEvent::FileChanged { workspace_id, .. } => workspace_id,
-            ServerEvent::SearchReady { workspace_id } => workspace_id,
+            ServerEvent::WorkspaceCreated { workspace_id, .. }
+            | ServerEvent::WorkspaceRemoved { workspace_id }
+            | ServerEvent::WorkspaceUnavailable { workspace_id, .. }
+            | ServerEvent::WorkspaceRelocated { workspace_id, .. }
+            | ServerEvent::IndexingStarted { workspace_id }
+            | ServerEvent::IndexingProgress { workspace_id, .. }
+            | ServerEvent::IndexingCompleted { workspace_id, .. }
+            | ServerEvent::IndexingError { workspace_id, .. }
+            | ServerEvent::IndexTruncated { workspace_id, .. }
+            | ServerEvent::FileChanged { workspace_id, .. }
+            | ServerEvent::FileMoved { workspace_id, .. }
+            | ServerEvent::SearchReady { workspace_id }
+            | ServerEvent::WatcherError { workspace_id, .. }
+            | ServerEvent::WatcherStopped { workspace_id, .. } => Some(workspace_id),
+            ServerEvent::ServerShuttingDown => None,
         }
     }
 }
@@ -51,14 +96,24 @@ pub struct AppState {
     pub workspace_manager: Arc<WorkspaceManager>,
     pub index_manager: Arc<IndexManager>,
     pub watcher_manager: Arc<FileWatcherManager>,
+    pub search_history: Arc<SearchHistoryManager>,
+    pub blame_cache: Arc<BlameCache>,
     pub event_tx: broadcast::Sender<ServerEvent>,
+    /// SHA-256 of the global auth token (None = auth disabled). Starts from
+    /// `VYOTIQ_AUTH_TOKEN`; `/admin/rotate-token` swaps it without a restart.
+    pub auth_token_hash: Arc<parking_lot::RwLock<Option<String>>>,
+    /// SHA-256 of `VYOTIQ_BOOTSTRAP_TOKEN`, a credential shared with the
+    /// process that launched the backend. It only authorizes `/admin/*`, and
+    /// is required to set a global token when none exists yet.
+    pub bootstrap_token_hash: Option<String>,
+    /// Active log filter; `/admin/log-level` reads and replaces it.
+    pub log_filter: crate::logging::LogFilterHandle,
 }
 
 impl AppState {
-    pub async fn new(config: AppConfig) -> AppResult<Self> {
-        // MEMORY FIX: Reduce broadcast channel from 1024 to 256 buffered events.
-        // Each event contains strings; 256 is plenty for real-time UI updates.
-        let (event_tx, _) = broadcast::channel(256);
+    pub async fn new(config: AppConfig, log_filter: crate::logging::LogFilterHandle) -> AppResult<Self> {
+        // broadcast::channel panics on a zero capacity
+        let (event_tx, _) = broadcast::channel(config.event_channel_capacity.max(1));
 
         let data_dir = std::path::PathBuf::from(&config.data_dir);
         tokio::fs::create_dir_all(&data_dir).await.map_err(|e| {
@@ -68,26 +123,39 @@ impl AppState {
         let workspace_manager = Arc::new(WorkspaceManager::new(
             data_dir.clone(),
             config.exclude_patterns.clone(),
+            config.multiple_active_workspaces,
         ));
-        let index_manager = Arc::new(IndexManager::new(
-            data_dir.join("indexes"),
-            config.max_file_size_bytes,
-            config.index_batch_size,
-            config.max_indexed_files,
-            config.exclude_patterns.clone(),
-        ));
+        let index_manager = Arc::new(IndexManager::new(data_dir.join("indexes"), &config));
+        for workspace in workspace_manager.list_workspaces() {
+            index_manager.set_max_file_size_override(&workspace.id, workspace.max_file_size_bytes);
+            index_manager.set_fold_accents(&workspace.id, workspace.fold_accents);
+        }
         let watcher_manager = Arc::new(FileWatcherManager::new(
-            config.watcher_debounce_ms,
+            &config,
             event_tx.clone(),
-            config.exclude_patterns.clone(),
+            workspace_manager.clone(),
         ));
+        let search_history = Arc::new(SearchHistoryManager::new(data_dir.join("search_history")));
+        let auth_token_hash = std::env::var("VYOTIQ_AUTH_TOKEN")
+            .ok()
+            .filter(|t| !t.is_empty())
+            .map(|t| crate::workspace::hash_auth_token(&t));
+        let bootstrap_token_hash = std::env::var("VYOTIQ_BOOTSTRAP_TOKEN")
+            .ok()
+            .filter(|t| !t.is_empty())
+            .map(|t| crate::workspace::hash_auth_token(&t));
 
         Ok(Self {
             config,
             workspace_manager,
             index_manager,
             watcher_manager,
+            search_history,
+            blame_cache: Arc::new(BlameCache::new()),
             event_tx,
+            auth_token_hash: Arc::new(parking_lot::RwLock::new(auth_token_hash)),
+            bootstrap_token_hash,
+            log_filter,
         })
     }
 }
@@ -0,0 +1,277 @@
+//! Identifier-aware tokenizer for code search.
+//!
+//! Splits text into words of letters, digits and underscores, then splits each
+//! word on underscores and case boundaries so `getUserName`, `GetUserName`,
+//! `get_user_name` and `GET_USER_NAME` all produce `get`, `user`, `name`.
+//! The whole identifier is kept too, lowercased with underscores removed, at the
+//! position of its first part. Query text goes through the same tokenizer, so
+//! exact identifiers, their parts, and phrases of parts all match.
+//!
+//! A second variant also folds accented letters to ASCII (`café` -> `cafe`)
+//! for workspaces that opt in (`Workspace::fold_accents`).
+
+use tantivy::tokenizer::{
+    AsciiFoldingFilter, RemoveLongFilter, TextAnalyzer, Token, TokenStream, Tokenizer,
+};
+use tantivy::Index;
+
+/// Name the tokenizer is registered under; referenced by `IndexSchema::build`.
+pub const CODE_TOKENIZER: &str = "code";
+
+/// The code tokenizer followed by ASCII folding of diacritics.
+pub const FOLDED_CODE_TOKENIZER: &str = "code_folded";
+
+/// Tokens longer than this (minified blobs, base64) are dropped, as with
+/// Tantivy's default tokenizer.
+const MAX_TOKEN_LEN: usize = 40;
+
+/// Register the code tokenizers on an index. Tokenizers are not persisted with
+/// the index, so this must run every time an index is created or opened.
+pub fn register(index: &Index) {
+    let analyzer = TextAnalyzer::builder(CodeTokenizer)
+        .filter(RemoveLongFilter::limit(MAX_TOKEN_LEN))
+        .build();
+    index.tokenizers().register(CODE_TOKENIZER, analyzer);
+    let folded = TextAnalyzer::builder(CodeTokenizer)
+        .filter(RemoveLongFilter::limit(MAX_TOKEN_LEN))
+        .filter(AsciiFoldingFilter)
+        .build();
+    index.tokenizers().register(FOLDED_CODE_TOKENIZER, folded);
+}
+
+#[derive(Clone, Default)]
+pub struct CodeTokenizer;
+
+impl Tokenizer for CodeTokenizer {
+    type TokenStream<'a> = CodeTokenStream<'a>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> CodeTokenStream<'a> {
+        CodeTokenStream {
+            text,
+            cursor: 0,
+            next_position: 0,
+            pending: Vec::new(),
+            token: Token::default(),
+        }
+    }
+}
+
+/// Walks one word at a time; the tokens of the current word are queued in
+/// `pending` (in reverse) so memory stays proportional to one identifier.
+pub struct CodeTokenStream<'a> {
+    text: &'a str,
+    cursor: usize,
+    next_position: usize,
+    pending: Vec<Token>,
+    token: Token,
+}
+
+impl CodeTokenStream<'_> {
+    /// Queue the tokens for the next word. Returns false at end of text.
+    fn fill_pending(&mut self) -> bool {
+        loop {
+            let rest = &self.text[self.cursor..];
+            let Some(start) = rest.find(is_word_char) else {
+                self.cursor = self.text.len();
+                return false;
+            };
+            let start = self.cursor + start;
+            let end = self.text[start..]
+                .find(|c: char| !is_word_char(c))
+                .map_or(self.text.len(), |len| start + len);
+            self.cursor = end;
+
+            let parts = split_identifier(&self.text[start..end]);
+            if parts.is_empty() {
+                continue; // only underscores
+            }
+
+            let position = self.next_position;
+            self.next_position += parts.len();
+
+            let mut tokens: Vec<Token> = Vec::with_capacity(parts.len() + 1);
+            if parts.len() > 1 {
+                let whole: String = self.text[start..end]
+                    .chars()
+                    .filter(|&c| c != '_')
+                    .flat_map(char::to_lowercase)
+                    .collect();
+                tokens.push(make_token(whole, start, end, position));
+            }
+            for (i, (from, to)) in parts.into_iter().enumerate() {
+                let text = self.text[start + from..start + to].to_lowercase();
+                tokens.push(make_token(text, start + from, start + to, position + i));
+            }
+
+            tokens.reverse();
+            self.pending = tokens;
+            return true;
+        }
+    }
+}
+
+impl TokenStream for CodeTokenStream<'_> {
+    fn advance(&mut self) -> bool {
+        if self.pending.is_empty() && !self.fill_pending() {
+            return false;
+        }
+        match self.pending.pop() {
+            Some(token) => {
+                self.token = token;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn make_token(text: String, offset_from: usize, offset_to: usize, position: usize) -> Token {
+    Token {
+        offset_from,
+        offset_to,
+        position,
+        text,
+        position_length: 1,
+    }
+}
+
+/// Byte ranges of the parts of an identifier. Splits on underscores, on a
+/// lowercase letter or digit followed by an uppercase letter (`getUser`,
+/// `v2Api`), and before the last capital of an acronym followed by lowercase
+/// (`HTTPServer` -> `HTTP`, `Server`). Letter-digit runs stay together (`utf8`).
+fn split_identifier(word: &str) -> Vec<(usize, usize)> {
+    let chars: Vec<(usize, char)> = word.char_indices().collect();
+    let mut parts = Vec::new();
+    let mut part_start: Option<usize> = None;
+
+    for (i, &(offset, c)) in chars.iter().enumerate() {
+        if c == '_' {
+            if let Some(start) = part_start.take() {
+                parts.push((start, offset));
+            }
+            continue;
+        }
+
+        if let Some(start) = part_start {
+            let prev = chars[i - 1].1;
+            let next = chars.get(i + 1).map(|&(_, n)| n);
+            let camel = (prev.is_lowercase() || prev.is_numeric()) && c.is_uppercase();
+            let acronym_end =
+                prev.is_uppercase() && c.is_uppercase() && next.is_some_and(char::is_lowercase);
+            if camel || acronym_end {
+                parts.push((start, offset));
+                part_start = Some(offset);
+            }
+        } else {
+            part_start = Some(offset);
+        }
+    }
+
+    if let Some(start) = part_start {
+        parts.push((start, word.len()));
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (text, position) of every token, in stream order.
+    fn tokens(text: &str) -> Vec<(String, usize)> {
+        let mut tokenizer = CodeTokenizer;
+        let mut stream = tokenizer.token_stream(text);
+        let mut out = Vec::new();
+        while stream.advance() {
+            let token = stream.token();
+            out.push((token.text.clone(), token.position));
+        }
+        out
+    }
+
+    fn texts(text: &str) -> Vec<String> {
+        tokens(text).into_iter().map(|(text, _)| text).collect()
+    }
+
+    #[test]
+    fn identifier_styles_share_their_parts() {
+        for ident in [
+            "getUserName",
+            "GetUserName",
+            "get_user_name",
+            "GET_USER_NAME",
+        ] {
+            assert_eq!(
+                texts(ident),
+                ["getusername", "get", "user", "name"],
+                "{ident}"
+            );
+        }
+    }
+
+    #[test]
+    fn whole_identifier_sits_at_its_first_part() {
+        assert_eq!(
+            tokens("let getUser = 1"),
+            [
+                ("let".to_string(), 0),
+                ("getuser".to_string(), 1),
+                ("get".to_string(), 1),
+                ("user".to_string(), 2),
+                ("1".to_string(), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn acronyms_digits_and_underscores() {
+        assert_eq!(texts("HTTPServer"), ["httpserver", "http", "server"]);
+        assert_eq!(texts("v2Api"), ["v2api", "v2", "api"]);
+        assert_eq!(texts("utf8"), ["utf8"]);
+        assert_eq!(texts("__init__"), ["init"]);
+        assert!(texts("___").is_empty());
+    }
+
+    #[test]
+    fn non_ascii_words_split_on_case_and_keep_offsets() {
+        assert_eq!(texts("caféAu_lait"), ["caféaulait", "café", "au", "lait"]);
+        assert_eq!(texts("ÜberSchön"), ["überschön", "über", "schön"]);
+        // Scripts without case stay one word
+        assert_eq!(texts("日本語 テスト"), ["日本語", "テスト"]);
+
+        let text = "x = naïveÉtat";
+        let mut tokenizer = CodeTokenizer;
+        let mut stream = tokenizer.token_stream(text);
+        let mut parts = Vec::new();
+        while stream.advance() {
+            let token = stream.token();
+            parts.push(&text[token.offset_from..token.offset_to]);
+        }
+        assert_eq!(parts, ["x", "naïveÉtat", "naïve", "État"]);
+    }
+
+    #[test]
+    fn folded_analyzer_strips_diacritics() {
+        let index = Index::create_in_ram(tantivy::schema::Schema::builder().build());
+        register(&index);
+        let mut analyzer = index.tokenizers().get(FOLDED_CODE_TOKENIZER).unwrap();
+        let mut stream = analyzer.token_stream("CaféÉtat");
+        let mut out = Vec::new();
+        while stream.advance() {
+            out.push(stream.token().text.clone());
+        }
+        assert_eq!(out, ["cafeetat", "cafe", "etat"]);
+    }
+}
@@ -1,13 +1,16 @@
+use crate::config::{AppConfig, WatcherMode};
 use crate::indexer::IndexManager;
 use crate::state::ServerEvent;
+use crate::workspace::WorkspaceManager;
 use dashmap::DashMap;
 use notify_debouncer_full::{
-    new_debouncer, DebounceEventResult, DebouncedEvent,
+    new_debouncer_opt, DebounceEventResult, DebouncedEvent, Debouncer, RecommendedCache,
 };
 use notify::RecursiveMode;
 use std::collections::HashMap;
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock, Weak};
 use std::time::{Duration, Instant};
 use parking_lot::Mutex;
 use tokio::sync::broadcast;
@@ -16,19 +19,75 @@ use tracing::{info, warn};
 /// Minimum interval between re-index operations for the same file (in ms).
 /// Prevents rapid saves from triggering redundant re-indexing.
 /// MEMORY FIX: Increased from 2s to 5s to reduce the frequency of
-/// IndexWriter allocations (each creates a new 3MB buffer).
+/// IndexWriter allocations (each creates a new 15MB buffer).
 const REINDEX_COOLDOWN_MS: u64 = 5000;
 
 pub struct FileWatcherManager {
     watchers: DashMap<String, WatcherHandle>,
     debounce_ms: u64,
+    /// Changed-file count above which a debounce batch is handled by one
+    /// incremental `index_workspace` pass rather than per-file reindexing.
+    batch_threshold: usize,
+    /// Which notify backend to use (native OS events or polling).
+    mode: WatcherMode,
+    poll_interval_ms: u64,
+    /// Shared with every workspace's `WatchContext`.
+    scope: Arc<WatchScope>,
     event_tx: broadcast::Sender<ServerEvent>,
+    /// Receives incremental file count/size changes so stored totals stay
+    /// accurate between full index passes.
+    workspace_manager: Arc<WorkspaceManager>,
+}
+
+struct WatcherHandle {
+    /// The only strong reference; `WatchContext::backend` is weak so the
+    /// debounce thread doesn't keep its own watcher alive.
+    _watcher: Arc<Mutex<WatcherBackend>>,
+    ctx: Arc<WatchContext>,
+}
+
+/// Keeps the debouncer (and its OS or polling watcher) alive; dropping it stops watching.
+/// The debounce/cooldown pipeline in `WatchContext` is identical for both backends.
+enum WatcherBackend {
+    Native(Debouncer<notify::RecommendedWatcher, RecommendedCache>),
+    Poll(Debouncer<notify::PollWatcher, RecommendedCache>),
+}
+
+impl WatcherBackend {
+    fn watch(&mut self, path: &Path, mode: RecursiveMode) -> Result<(), notify::Error> {
+        match self {
+            Self::Native(debouncer) => debouncer.watch(path, mode),
+            Self::Poll(debouncer) => debouncer.watch(path, mode),
+        }
+    }
+}
+
+/// Which top-level directories get a recursive watch when watching is
+/// limited (`AppConfig::watch_dirs`, `AppConfig::watch_skip_excluded_dirs`),
+/// and which changed paths are ignored.
+struct WatchScope {
+    /// Limits watching to these top-level directories; empty means all.
+    watch_dirs: Vec<String>,
+    /// Leave out top-level directories the indexer skips.
+    skip_excluded_dirs: bool,
     /// User-provided exclude patterns forwarded from app settings.
     user_exclude_patterns: Vec<String>,
 }
 
-struct WatcherHandle {
-    _watcher: notify_debouncer_full::Debouncer<notify::RecommendedWatcher, notify_debouncer_full::FileIdMap>,
+impl WatchScope {
+    /// Without limits the root is watched recursively as a whole.
+    fn is_limited(&self) -> bool {
+        !self.watch_dirs.is_empty() || self.skip_excluded_dirs
+    }
+
+    /// Whether the top-level directory `name` is watched.
+    fn includes(&self, name: &str) -> bool {
+        if !self.watch_dirs.is_empty() && !self.watch_dirs.iter().any(|d| d.trim_matches('/') == name) {
+            return false;
+        }
+        !(self.skip_excluded_dirs
+            && IndexManager::is_build_or_output_dir_with_patterns(Path::new(name), &self.user_exclude_patterns))
+    }
 }
 
 /// Per-file cooldown tracker to avoid redundant re-indexing
@@ -46,10 +105,10 @@ impl ReindexCooldownTracker {
     /// Returns true if the file should be re-indexed (cooldown expired)
     fn should_reindex(&mut self, path: &str) -> bool {
         let now = Instant::now();
-        if let Some(last) = self.last_reindex.get(path) {
-            if now.duration_since(*last) < Duration::from_millis(REINDEX_COOLDOWN_MS) {
-                return false;
-            }
+        if let Some(last) = self.last_reindex.get(path)
+            && now.duration_since(*last) < Duration::from_millis(REINDEX_COOLDOWN_MS)
+        {
+            return false;
         }
         self.last_reindex.insert(path.to_string(), now);
         true
@@ -63,12 +122,25 @@ impl ReindexCooldownTracker {
 }
 
 impl FileWatcherManager {
-    pub fn new(debounce_ms: u64, event_tx: broadcast::Sender<ServerEvent>, user_exclude_patterns: Vec<String>) -> Self {
+    /// Debounce, backend and scope settings come from `config`.
+    pub fn new(
+        config: &AppConfig,
+        event_tx: broadcast::Sender<ServerEvent>,
+        workspace_manager: Arc<WorkspaceManager>,
+    ) -> Self {
         Self {
             watchers: DashMap::new(),
-            debounce_ms,
+            debounce_ms: config.watcher_debounce_ms,
+            batch_threshold: config.watcher_batch_threshold,
+            mode: config.watcher_mode,
+            poll_interval_ms: config.watcher_poll_interval_ms,
+            scope: Arc::new(WatchScope {
+                watch_dirs: config.watch_dirs.clone(),
+                skip_excluded_dirs: config.watch_skip_excluded_dirs,
+                user_exclude_patterns: config.exclude_patterns.clone(),
+            }),
             event_tx,
-            user_exclude_patterns,
+            workspace_manager,
         }
     }
 
@@ -79,128 +151,680 @@ impl FileWatcherManager {
         path: &str,
         index_manager: Option<Arc<IndexManager>>,
     ) -> Result<(), notify::Error> {
-        if self.watchers.contains_key(workspace_id) {
+        if self.is_watching(workspace_id) {
             return Ok(()); // Already watching
         }
+        // A watcher that died is replaced rather than kept around
+        self.watchers.remove(workspace_id);
+
+        let ctx = Arc::new(WatchContext {
+            ws_id: workspace_id.to_string(),
+            ws_path: PathBuf::from(path),
+            ws_path_str: path.to_string(),
+            event_tx: self.event_tx.clone(),
+            idx_mgr: index_manager,
+            ws_mgr: self.workspace_manager.clone(),
+            batch_threshold: self.batch_threshold,
+            cooldown: Mutex::new(ReindexCooldownTracker::new()),
+            cleanup_counter: AtomicUsize::new(0),
+            // Create a tokio runtime handle for async reindex calls
+            rt_handle: tokio::runtime::Handle::try_current().ok(),
+            paused: AtomicBool::new(false),
+            stopped: AtomicBool::new(false),
+            scope: self.scope.clone(),
+            backend: OnceLock::new(),
+        });
 
-        let ws_id = workspace_id.to_string();
-        let event_tx = self.event_tx.clone();
-        let ws_path = PathBuf::from(path);
-        let ws_path_str = path.to_string();
-        let idx_mgr = index_manager;
-        let user_patterns = self.user_exclude_patterns.clone();
-        let cooldown = Arc::new(Mutex::new(ReindexCooldownTracker::new()));
-        let cleanup_counter = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let use_polling = match self.mode {
+            WatcherMode::Native => false,
+            WatcherMode::Poll => true,
+            WatcherMode::Auto => is_network_path(Path::new(path)),
+        };
 
-        // Create a tokio runtime handle for async reindex calls
-        let rt_handle = tokio::runtime::Handle::try_current().ok();
+        let backend = match self.build_backend(ctx.clone(), Path::new(path), use_polling) {
+            Ok(backend) => backend,
+            Err(e) => {
+                let _ = self.event_tx.send(ServerEvent::WatcherError {
+                    workspace_id: workspace_id.to_string(),
+                    reason: describe_watch_error(&e),
+                });
+                return Err(notify::Error::generic(&format!("Watch failed: {}", e)));
+            }
+        };
+
+        let backend = Arc::new(Mutex::new(backend));
+        let _ = ctx.backend.set(Arc::downgrade(&backend));
+        self.watchers.insert(
+            workspace_id.to_string(),
+            WatcherHandle { _watcher: backend, ctx },
+        );
 
-        // Use notify-debouncer-full for proper event deduplication
-        let mut debouncer = new_debouncer(
+        if use_polling {
+            info!(
+                "Started polling workspace {} at {} (interval: {}ms, debounce: {}ms)",
+                workspace_id, path, self.poll_interval_ms, self.debounce_ms
+            );
+        } else {
+            info!("Started watching workspace {} at {} (debounce: {}ms)", workspace_id, path, self.debounce_ms);
+        }
+        if self.scope.is_limited() {
+            info!(
+                "Watch scope for workspace {} limited (dirs: {:?}, skip excluded: {})",
+                workspace_id, self.scope.watch_dirs, self.scope.skip_excluded_dirs
+            );
+        }
+        Ok(())
+    }
+
+    fn build_backend(
+        &self,
+        ctx: Arc<WatchContext>,
+        watch_root: &Path,
+        use_polling: bool,
+    ) -> Result<WatcherBackend, notify::Error> {
+        let mut backend = if use_polling {
+            let config = notify::Config::default()
+                .with_poll_interval(Duration::from_millis(self.poll_interval_ms));
+            WatcherBackend::Poll(self.build_debouncer::<notify::PollWatcher>(ctx, config)?)
+        } else {
+            WatcherBackend::Native(
+                self.build_debouncer::<notify::RecommendedWatcher>(ctx, notify::Config::default())?,
+            )
+        };
+        for (path, mode) in self.watch_targets(watch_root) {
+            backend.watch(&path, mode)?;
+        }
+        Ok(backend)
+    }
+
+    /// Paths to register with notify. Without a scope this is just the root,
+    /// recursively. With `watch_dirs` or `skip_excluded_dirs` the root is
+    /// watched on its own (top-level files only) plus each selected top-level
+    /// directory recursively, so notify never walks into the rest. Selected
+    /// directories created later are added by `WatchContext::watch_new_top_level_dir`.
+    fn watch_targets(&self, watch_root: &Path) -> Vec<(PathBuf, RecursiveMode)> {
+        if !self.scope.is_limited() {
+            return vec![(watch_root.to_path_buf(), RecursiveMode::Recursive)];
+        }
+
+        let mut targets = vec![(watch_root.to_path_buf(), RecursiveMode::NonRecursive)];
+        let Ok(entries) = std::fs::read_dir(watch_root) else {
+            return targets;
+        };
+        for entry in entries.flatten() {
+            if !entry.file_type().is_ok_and(|t| t.is_dir()) {
+                continue;
+            }
+            if self.scope.includes(&entry.file_name().to_string_lossy()) {
+                targets.push((entry.path(), RecursiveMode::Recursive));
+            }
+        }
+        targets
+    }
+
+    /// Use notify-debouncer-full for proper event deduplication, on top of
+    /// whichever notify watcher backend `T` is.
+    fn build_debouncer<T: notify::Watcher>(
+        &self,
+        ctx: Arc<WatchContext>,
+        config: notify::Config,
+    ) -> Result<Debouncer<T, RecommendedCache>, notify::Error> {
+        new_debouncer_opt::<_, T, RecommendedCache>(
             Duration::from_millis(self.debounce_ms),
             None, // Use default tick rate
             move |result: DebounceEventResult| {
                 match result {
-                    Ok(events) => {
-                        // Clean up cooldown tracker periodically
-                        let count = cleanup_counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-                        if count % 50 == 0 {
-                            cooldown.lock().cleanup_stale();
-                        }
+                    Ok(events) => ctx.handle_events(&events),
+                    Err(errors) => ctx.handle_errors(&errors),
+                }
+            },
+            RecommendedCache::new(),
+            config,
+        )
+    }
 
-                        // Batch deduplicate: collect unique file paths with their final event type
-                        let mut file_events: HashMap<PathBuf, String> = HashMap::new();
-                        for event in &events {
-                            let change_type = classify_debounced_event(event);
-                            if change_type == "access" || change_type == "other" {
-                                continue;
-                            }
-
-                            for path in &event.paths {
-                                // Skip build/output directories (including user patterns)
-                                if IndexManager::is_build_or_output_dir_with_patterns(path, &user_patterns) {
-                                    continue;
-                                }
-                                // Last event type wins for each path
-                                file_events.insert(path.clone(), change_type.to_string());
-                            }
-                        }
+    /// Replace a stopped or missing watcher with a fresh one, then reconcile
+    /// an already-indexed workspace for changes missed in the meantime.
+    /// Does nothing when the workspace is already being watched. Returns
+    /// whether a new watcher was started.
+    pub fn restart_watching(
+        &self,
+        workspace_id: &str,
+        path: &str,
+        index_manager: Option<Arc<IndexManager>>,
+    ) -> Result<bool, notify::Error> {
+        if self.is_watching(workspace_id) {
+            return Ok(false);
+        }
+        self.stop_watching(workspace_id);
+        let indexed = index_manager
+            .as_ref()
+            .is_some_and(|im| im.get_index_status(workspace_id).is_ok_and(|s| s.indexed));
+        self.start_watching(workspace_id, path, index_manager)?;
+        if indexed
+            && let Some(handle) = self.watchers.get(workspace_id)
+        {
+            handle.ctx.spawn_batch_reindex();
+        }
+        Ok(true)
+    }
 
-                        // Process each unique file change
-                        for (path, change_type) in file_events {
-                            let relative = path
-                                .strip_prefix(&ws_path)
-                                .unwrap_or(&path)
-                                .to_string_lossy()
-                                .replace('\\', "/");
-
-                            // Check cooldown
-                            if !cooldown.lock().should_reindex(&relative) {
-                                continue;
-                            }
-
-                            let _ = event_tx.send(ServerEvent::FileChanged {
-                                workspace_id: ws_id.clone(),
-                                path: relative.clone(),
-                                change_type: change_type.clone(),
-                            });
-
-                            // Trigger incremental full-text re-indexing
-                            if let (Some(im), Some(handle)) = (&idx_mgr, &rt_handle) {
-                                let im = im.clone();
-                                let ws = ws_id.clone();
-                                let fp = relative.clone();
-                                let wp = ws_path_str.clone();
-                                let ct = change_type.clone();
-                                handle.spawn(async move {
-                                    if let Err(e) = im.reindex_file(&ws, &fp, &wp, &ct).await {
-                                        tracing::debug!("Incremental reindex skipped: {}", e);
-                                    }
-                                });
-                            }
-                        }
+    pub fn stop_watching(&self, workspace_id: &str) {
+        if self.watchers.remove(workspace_id).is_some() {
+            info!("Stopped watching workspace {}", workspace_id);
+        }
+    }
+
+    /// True while a watcher exists for the workspace and is still delivering
+    /// events (see `ServerEvent::WatcherStopped`).
+    pub fn is_watching(&self, workspace_id: &str) -> bool {
+        self.watchers
+            .get(workspace_id)
+            .is_some_and(|handle| !handle.ctx.stopped.load(Ordering::Acquire))
+    }
+
+    /// Temporarily drop all change events for a workspace without tearing down
+    /// the OS watcher (e.g. while a large build churns output files).
+    /// Returns false if the workspace is not being watched.
+    pub fn pause_watching(&self, workspace_id: &str) -> bool {
+        match self.watchers.get(workspace_id) {
+            Some(handle) => {
+                if !handle.ctx.paused.swap(true, Ordering::AcqRel) {
+                    info!("Paused watching workspace {}", workspace_id);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Resume a paused watcher. Events seen while paused were dropped, so a
+    /// single incremental `index_workspace` pass reconciles the index.
+    /// Returns false if the workspace is not being watched.
+    pub fn resume_watching(&self, workspace_id: &str) -> bool {
+        match self.watchers.get(workspace_id) {
+            Some(handle) => {
+                if handle.ctx.paused.swap(false, Ordering::AcqRel) {
+                    info!("Resumed watching workspace {}, reconciling index", workspace_id);
+                    handle.ctx.spawn_batch_reindex();
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_paused(&self, workspace_id: &str) -> bool {
+        self.watchers
+            .get(workspace_id)
+            .is_some_and(|handle| handle.ctx.paused.load(Ordering::Acquire))
+    }
+}
+
+/// Per-workspace state owned by the debounce callback.
+struct WatchContext {
+    ws_id: String,
+    ws_path: PathBuf,
+    ws_path_str: String,
+    event_tx: broadcast::Sender<ServerEvent>,
+    idx_mgr: Option<Arc<IndexManager>>,
+    ws_mgr: Arc<WorkspaceManager>,
+    batch_threshold: usize,
+    cooldown: Mutex<ReindexCooldownTracker>,
+    cleanup_counter: AtomicUsize,
+    rt_handle: Option<tokio::runtime::Handle>,
+    /// Set by `pause_watching`; events are discarded (not queued) while true.
+    paused: AtomicBool,
+    /// Set once the watcher can no longer deliver events; `start_watching`
+    /// replaces it.
+    stopped: AtomicBool,
+    /// When it is limited, the root is watched non-recursively, so selected
+    /// top-level directories created later need their own watch.
+    scope: Arc<WatchScope>,
+    /// The watcher delivering these events, for adding watches; set once it
+    /// is built.
+    backend: OnceLock<Weak<Mutex<WatcherBackend>>>,
+}
+
+impl WatchContext {
+    /// Backend errors from notify. Each batch is reported as one
+    /// `WatcherError`; if the root directory has gone the watcher is
+    /// marked stopped as well.
+    fn handle_errors(&self, errors: &[notify::Error]) {
+        for e in errors {
+            warn!("File watcher error for workspace {}: {:?}", self.ws_id, e);
+        }
+        if self.stopped.load(Ordering::Acquire) {
+            return;
+        }
+        let mut reasons: Vec<String> = errors.iter().map(describe_watch_error).collect();
+        reasons.dedup();
+        let _ = self.event_tx.send(ServerEvent::WatcherError {
+            workspace_id: self.ws_id.clone(),
+            reason: reasons.join("; "),
+        });
+        if !self.ws_path.is_dir() {
+            self.mark_stopped("workspace directory no longer exists");
+        }
+    }
+
+    fn mark_stopped(&self, reason: &str) {
+        if self.stopped.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        warn!("File watcher for workspace {} stopped: {}", self.ws_id, reason);
+        let _ = self.event_tx.send(ServerEvent::WatcherStopped {
+            workspace_id: self.ws_id.clone(),
+            reason: reason.to_string(),
+        });
+    }
+
+    fn handle_events(&self, events: &[DebouncedEvent]) {
+        if self.paused.load(Ordering::Acquire) || self.stopped.load(Ordering::Acquire) {
+            return;
+        }
+        // Deleting or unmounting the root leaves nothing to watch
+        if !self.ws_path.is_dir() {
+            self.mark_stopped("workspace directory no longer exists");
+            return;
+        }
+
+        if self.scope.is_limited() {
+            for event in events {
+                if let Some(dir) = created_path(event)
+                    && dir.parent() == Some(self.ws_path.as_path())
+                    && dir.is_dir()
+                {
+                    self.watch_new_top_level_dir(dir);
+                }
+            }
+        }
+
+        // Clean up cooldown tracker periodically
+        let count = self.cleanup_counter.fetch_add(1, Ordering::Relaxed);
+        if count.is_multiple_of(50) {
+            self.cooldown.lock().cleanup_stale();
+        }
+
+        // Batch deduplicate: collect unique file paths with their final event type.
+        // Renames that notify reports with both paths are kept aside as moves.
+        let mut file_events: HashMap<PathBuf, String> = HashMap::new();
+        let mut moves: Vec<(PathBuf, PathBuf)> = Vec::new();
+        for event in events {
+            if let Some((from, to)) = paired_rename(event) {
+                match (self.is_excluded(from), self.is_excluded(to)) {
+                    (false, false) => {
+                        file_events.remove(from);
+                        file_events.remove(to);
+                        moves.push((from.clone(), to.clone()));
                     }
-                    Err(errors) => {
-                        for e in errors {
-                            warn!("File watcher error: {:?}", e);
-                        }
+                    (false, true) => {
+                        file_events.insert(from.clone(), "remove".to_string());
                     }
+                    (true, false) => {
+                        file_events.insert(to.clone(), "create".to_string());
+                    }
+                    (true, true) => {}
                 }
-            },
-        )?;
+                continue;
+            }
 
-        debouncer
-            .watch(PathBuf::from(path).as_path(), RecursiveMode::Recursive)
-            .map_err(|e| notify::Error::generic(&format!("Watch failed: {}", e)))?;
+            let change_type = classify_debounced_event(event);
+            if change_type == "access" || change_type == "other" {
+                continue;
+            }
 
-        self.watchers.insert(
-            workspace_id.to_string(),
-            WatcherHandle { _watcher: debouncer },
-        );
+            for path in &event.paths {
+                // Skip build/output directories (including user patterns)
+                if self.is_excluded(path) {
+                    continue;
+                }
+                // Last event type wins for each path
+                file_events.insert(path.clone(), change_type.to_string());
+            }
+        }
 
-        info!("Started watching workspace {} at {} (debounce: {}ms)", workspace_id, path, self.debounce_ms);
-        Ok(())
+        // Burst (e.g. git checkout): one incremental pass with a single
+        // IndexWriter instead of a writer per file. index_workspace
+        // hash-dedups, so unchanged files cost only a hash.
+        let is_burst = file_events.len() + moves.len() > self.batch_threshold;
+        if is_burst {
+            info!(
+                "Watcher burst for workspace {}: {} files changed, running batch reindex",
+                self.ws_id,
+                file_events.len() + moves.len()
+            );
+            self.spawn_batch_reindex();
+        }
+
+        for (from, to) in moves {
+            self.handle_move(&from, &to, is_burst);
+        }
+
+        // Process each unique file change
+        for (path, change_type) in file_events {
+            let relative = self.relative(&path);
+
+            // Check cooldown (burst batches are already covered by the batch pass)
+            if !is_burst && !self.cooldown.lock().should_reindex(&relative) {
+                continue;
+            }
+
+            let _ = self.event_tx.send(ServerEvent::FileChanged {
+                workspace_id: self.ws_id.clone(),
+                path: relative.clone(),
+                change_type: change_type.clone(),
+            });
+
+            if is_burst {
+                continue;
+            }
+
+            // Trigger incremental full-text re-indexing
+            if let (Some(im), Some(handle)) = (&self.idx_mgr, &self.rt_handle) {
+                let im = im.clone();
+                let ws = self.ws_id.clone();
+                let fp = relative.clone();
+                let wp = self.ws_path_str.clone();
+                let ct = change_type.clone();
+                let wm = self.ws_mgr.clone();
+                handle.spawn(async move {
+                    // A removed path may have been a whole directory
+                    let result = if ct == "remove" {
+                        im.remove_path_prefix(&ws, &fp, &wp).await
+                    } else {
+                        im.reindex_file(&ws, &fp, &wp, &ct).await
+                    };
+                    match result {
+                        Ok(delta) => {
+                            let _ = wm.adjust_workspace_stats(&ws, delta);
+                        }
+                        Err(e) => tracing::debug!("Incremental reindex skipped: {}", e),
+                    }
+                });
+            }
+        }
     }
 
-    pub fn stop_watching(&self, workspace_id: &str) {
-        if self.watchers.remove(workspace_id).is_some() {
-            info!("Stopped watching workspace {}", workspace_id);
+    /// Emit `FileMoved` and move the indexed document to its new path, reusing the
+    /// stored content hash. Directory renames are reported as remove + create and
+    /// reconciled by one incremental pass, since the index holds one document
+    /// per file rather than per directory.
+    fn handle_move(&self, from: &Path, to: &Path, is_burst: bool) {
+        let from_rel = self.relative(from);
+        let to_rel = self.relative(to);
+
+        if to.is_dir() {
+            for (rel, change_type) in [(from_rel, "remove"), (to_rel, "create")] {
+                let _ = self.event_tx.send(ServerEvent::FileChanged {
+                    workspace_id: self.ws_id.clone(),
+                    path: rel,
+                    change_type: change_type.to_string(),
+                });
+            }
+            if !is_burst {
+                self.spawn_batch_reindex();
+            }
+            return;
+        }
+
+        let _ = self.event_tx.send(ServerEvent::FileMoved {
+            workspace_id: self.ws_id.clone(),
+            from: from_rel.clone(),
+            to: to_rel.clone(),
+        });
+
+        if is_burst {
+            return;
+        }
+
+        if let (Some(im), Some(handle)) = (&self.idx_mgr, &self.rt_handle) {
+            let im = im.clone();
+            let ws = self.ws_id.clone();
+            let wp = self.ws_path_str.clone();
+            let wm = self.ws_mgr.clone();
+            handle.spawn(async move {
+                match im.move_file(&ws, &from_rel, &to_rel, &wp).await {
+                    Ok(delta) => {
+                        let _ = wm.adjust_workspace_stats(&ws, delta);
+                    }
+                    Err(e) => tracing::debug!("Incremental move skipped: {}", e),
+                }
+            });
         }
     }
 
-    pub fn is_watching(&self, workspace_id: &str) -> bool {
-        self.watchers.contains_key(workspace_id)
+    /// Watch a directory created (or moved) directly under the root of a
+    /// limited watcher, if the scope selects it. Anything written into it
+    /// before the watch was added is picked up by one incremental pass.
+    fn watch_new_top_level_dir(&self, dir: &Path) {
+        let name = dir.file_name().unwrap_or_default().to_string_lossy();
+        if !self.scope.includes(&name) {
+            return;
+        }
+        let Some(backend) = self.backend.get().and_then(Weak::upgrade) else {
+            return;
+        };
+        if let Err(e) = backend.lock().watch(dir, RecursiveMode::Recursive) {
+            warn!("Failed to watch new directory {} in workspace {}: {:?}", name, self.ws_id, e);
+            let _ = self.event_tx.send(ServerEvent::WatcherError {
+                workspace_id: self.ws_id.clone(),
+                reason: describe_watch_error(&e),
+            });
+            return;
+        }
+        info!("Watching new top-level directory {} in workspace {}", name, self.ws_id);
+        if std::fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_some()) {
+            self.spawn_batch_reindex();
+        }
+    }
+
+    /// Run one incremental `index_workspace` pass in the background.
+    fn spawn_batch_reindex(&self) {
+        if let (Some(im), Some(handle)) = (&self.idx_mgr, &self.rt_handle) {
+            let im = im.clone();
+            let ws = self.ws_id.clone();
+            let wp = self.ws_path_str.clone();
+            let tx = self.event_tx.clone();
+            let wm = self.ws_mgr.clone();
+            handle.spawn(async move {
+                if let Err(e) = im.index_workspace(&ws, &wp, tx).await {
+                    warn!("Batch reindex from watcher failed for {}: {}", ws, e);
+                    im.record_index_error(&ws, &e.to_string());
+                } else if let Ok(status) = im.get_index_status(&ws) {
+                    let _ = wm.update_workspace_stats(
+                        &ws,
+                        status.indexed_count,
+                        status.total_size_bytes,
+                        true,
+                        status.last_index_duration_ms,
+                    );
+                }
+            });
+        }
+    }
+
+    fn is_excluded(&self, path: &Path) -> bool {
+        IndexManager::is_build_or_output_dir_with_patterns(path, &self.scope.user_exclude_patterns)
+    }
+
+    fn relative(&self, path: &Path) -> String {
+        path.strip_prefix(&self.ws_path)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+}
+
+/// Return `(from, to)` when notify reports a rename with both sides paired.
+/// One-sided renames (`RenameMode::From`/`To`) are ambiguous and handled as
+/// remove/create by `classify_debounced_event`.
+fn paired_rename(event: &DebouncedEvent) -> Option<(&PathBuf, &PathBuf)> {
+    use notify::event::{ModifyKind, RenameMode};
+    use notify::EventKind;
+    match (event.kind, event.paths.as_slice()) {
+        (EventKind::Modify(ModifyKind::Name(RenameMode::Both)), [from, to]) => Some((from, to)),
+        _ => None,
+    }
+}
+
+/// The path an event brings into existence: a created path, or the target
+/// of a rename.
+fn created_path(event: &DebouncedEvent) -> Option<&PathBuf> {
+    use notify::event::{ModifyKind, RenameMode};
+    use notify::EventKind;
+    match event.kind {
+        EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(RenameMode::To)) => event.paths.first(),
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => event.paths.last(),
+        _ => None,
     }
 }
 
 /// Classify a debounced event into a simple change type
 fn classify_debounced_event(event: &DebouncedEvent) -> &'static str {
     use notify::EventKind;
+    use notify::event::{ModifyKind, RenameMode};
     match event.kind {
         EventKind::Create(_) => "create",
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => "remove",
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => "create",
         EventKind::Modify(_) => "modify",
         EventKind::Remove(_) => "remove",
         EventKind::Access(_) => "access",
         _ => "other",
     }
 }
+
+/// Heuristic: is `path` on a network filesystem where inotify-style events are
+/// unreliable? Checks UNC paths on Windows and the mount table on Linux.
+fn is_network_path(path: &Path) -> bool {
+    const NETWORK_FS_TYPES: &[&str] = &[
+        "nfs", "nfs4", "cifs", "smbfs", "smb3", "9p", "afs", "ceph",
+        "glusterfs", "davfs", "fuse.sshfs", "sshfs", "fuse.rclone",
+    ];
+
+    let lossy = path.to_string_lossy();
+    if lossy.starts_with("\\\\") || lossy.starts_with("//") {
+        return true;
+    }
+
+    let Ok(mounts) = std::fs::read_to_string("/proc/mounts") else {
+        return false;
+    };
+    let canonical = dunce::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+
+    // Longest mount point that contains the path decides its filesystem type
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _device = fields.next()?;
+            let mount_point = fields.next()?.replace("\\040", " ");
+            let fs_type = fields.next()?;
+            canonical
+                .starts_with(&mount_point)
+                .then(|| (mount_point.len(), fs_type.to_string()))
+        })
+        .max_by_key(|(len, _)| *len)
+        .is_some_and(|(_, fs_type)| NETWORK_FS_TYPES.contains(&fs_type.as_str()))
+}
+
+/// User-facing description of a notify error.
+fn describe_watch_error(e: &notify::Error) -> String {
+    match e.kind {
+        notify::ErrorKind::MaxFilesWatch => {
+            "OS file watch limit reached (raise fs.inotify.max_user_watches on Linux); \
+             changes in some directories are not tracked"
+                .to_string()
+        }
+        _ => e.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A manager watching only `watch_dirs` (and skipping excluded dirs),
+    /// its event receiver, and the workspace manager's data directory.
+    fn scoped_manager(
+        watch_dirs: &[&str],
+    ) -> (FileWatcherManager, broadcast::Receiver<ServerEvent>, tempfile::TempDir) {
+        let data = tempfile::tempdir().unwrap();
+        let (event_tx, event_rx) = broadcast::channel(256);
+        let workspaces =
+            Arc::new(WorkspaceManager::new(data.path().to_path_buf(), Vec::new(), false));
+        let config = AppConfig {
+            watcher_debounce_ms: 50,
+            watcher_batch_threshold: 100,
+            watcher_mode: WatcherMode::Native,
+            watcher_poll_interval_ms: 1000,
+            watch_dirs: watch_dirs.iter().map(|d| d.to_string()).collect(),
+            watch_skip_excluded_dirs: true,
+            ..AppConfig::default()
+        };
+        let manager = FileWatcherManager::new(&config, event_tx, workspaces);
+        (manager, event_rx, data)
+    }
+
+    /// Paths of the `FileChanged` events received within `wait`, stopping
+    /// early once `until` is among them.
+    fn changed_paths(
+        rx: &mut broadcast::Receiver<ServerEvent>,
+        until: &str,
+        wait: Duration,
+    ) -> Vec<String> {
+        let deadline = Instant::now() + wait;
+        let mut paths = Vec::new();
+        while Instant::now() < deadline && !paths.iter().any(|p| p == until) {
+            match rx.try_recv() {
+                Ok(ServerEvent::FileChanged { path, .. }) => paths.push(path),
+                Ok(_) => {}
+                Err(_) => std::thread::sleep(Duration::from_millis(20)),
+            }
+        }
+        paths
+    }
+
+    #[test]
+    fn scope_selects_listed_and_non_excluded_dirs() {
+        let listed = WatchScope {
+            watch_dirs: vec!["src".into(), "/docs/".into()],
+            skip_excluded_dirs: false,
+            user_exclude_patterns: Vec::new(),
+        };
+        assert!(listed.is_limited());
+        assert!(listed.includes("src"));
+        assert!(listed.includes("docs"));
+        assert!(!listed.includes("tests"));
+
+        let skipping = WatchScope {
+            watch_dirs: Vec::new(),
+            skip_excluded_dirs: true,
+            user_exclude_patterns: Vec::new(),
+        };
+        assert!(skipping.includes("src"));
+        assert!(!skipping.includes("node_modules"));
+        assert!(!skipping.includes("target"));
+    }
+
+    #[test]
+    fn new_top_level_dir_is_watched_when_the_scope_selects_it() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().join("ws");
+        std::fs::create_dir_all(&root).unwrap();
+        let (manager, mut rx, _data) = scoped_manager(&["lib"]);
+        manager.start_watching("ws", &root.to_string_lossy(), None).unwrap();
+
+        std::fs::create_dir(root.join("other")).unwrap();
+        std::fs::create_dir(root.join("lib")).unwrap();
+        let paths = changed_paths(&mut rx, "lib", Duration::from_secs(5));
+        assert!(paths.iter().any(|p| p == "lib"), "{:?}", paths);
+
+        std::fs::write(root.join("other/skipped.rs"), "fn skipped() {}").unwrap();
+        std::fs::write(root.join("lib/new.rs"), "fn new() {}").unwrap();
+        let paths = changed_paths(&mut rx, "lib/new.rs", Duration::from_secs(5));
+        assert!(paths.iter().any(|p| p == "lib/new.rs"), "{:?}", paths);
+        assert!(!paths.iter().any(|p| p.starts_with("other/")), "{:?}", paths);
+    }
+}
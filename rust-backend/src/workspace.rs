@@ -2,15 +2,23 @@ use crate::error::{AppError, AppResult};
 use chrono::{DateTime, Utc};
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize, Serializer};
-use std::path::{Path, PathBuf};
+use crate::git::{GitFileStatus, GitStatusMap};
+use crate::indexer::{IndexManager, PathIndex, StatsDelta};
+use ignore::WalkBuilder;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Workspace {
     pub id: String,
     pub name: String,
-    /// Primary path field (also exposed as `root_path` in JSON for frontend compat)
-    #[serde(alias = "root_path")]
+    /// Primary path field (also exposed as `root_path` in JSON for frontend compat).
+    /// Not aliased on deserialize: persisted JSON carries both keys, and serde
+    /// rejects an alias that appears alongside its primary name as a duplicate.
     pub path: String,
     pub created_at: DateTime<Utc>,
     pub last_accessed: DateTime<Utc>,
@@ -18,6 +26,31 @@ pub struct Workspace {
     pub indexed: bool,
     pub total_files: usize,
     pub total_size_bytes: u64,
+    /// Permit reads through symlinks whose target lies outside the workspace root.
+    /// Raw `..` traversal is rejected regardless.
+    #[serde(default)]
+    pub allow_symlinks_outside: bool,
+    /// When the last full-text indexing pass completed.
+    #[serde(default)]
+    pub last_indexed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_index_duration_ms: Option<u64>,
+    /// Overrides the global `max_file_size_bytes` for indexing and reads.
+    #[serde(default)]
+    pub max_file_size_bytes: Option<usize>,
+    /// Fold accented letters in indexed content and queries, so `cafe` finds
+    /// `café`. Off by default; changing it rebuilds the full-text index.
+    #[serde(default)]
+    pub fold_accents: bool,
+    /// The root directory existed when last checked (activation, indexing).
+    /// While false, watching and indexing are skipped; the next activation
+    /// re-checks and flips it back once the path reappears.
+    #[serde(default = "default_available")]
+    pub available: bool,
+}
+
+fn default_available() -> bool {
+    true
 }
 
 impl Workspace {
@@ -34,7 +67,7 @@ impl serde::Serialize for Workspace {
         S: Serializer,
     {
         use serde::ser::SerializeStruct;
-        let mut s = serializer.serialize_struct("Workspace", 10)?;
+        let mut s = serializer.serialize_struct("Workspace", 16)?;
         s.serialize_field("id", &self.id)?;
         s.serialize_field("name", &self.name)?;
         s.serialize_field("path", &self.path)?;
@@ -45,6 +78,12 @@ impl serde::Serialize for Workspace {
         s.serialize_field("indexed", &self.indexed)?;
         s.serialize_field("total_files", &self.total_files)?;
         s.serialize_field("total_size_bytes", &self.total_size_bytes)?;
+        s.serialize_field("allow_symlinks_outside", &self.allow_symlinks_outside)?;
+        s.serialize_field("last_indexed_at", &self.last_indexed_at)?;
+        s.serialize_field("last_index_duration_ms", &self.last_index_duration_ms)?;
+        s.serialize_field("max_file_size_bytes", &self.max_file_size_bytes)?;
+        s.serialize_field("fold_accents", &self.fold_accents)?;
+        s.serialize_field("available", &self.available)?;
         s.end()
     }
 }
@@ -62,6 +101,187 @@ pub struct FileEntry {
     pub extension: Option<String>,
     pub children_count: Option<usize>,
     pub is_hidden: bool,
+    /// Only filled in when requested (`?git=true` on the listing routes) and
+    /// the workspace is inside a git work tree.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_status: Option<GitFileStatus>,
+}
+
+/// Build the `FileEntry` for `path` from its (not followed) metadata.
+fn file_entry(path: &Path, relative_path: String, metadata: &std::fs::Metadata) -> FileEntry {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let is_dir = metadata.is_dir();
+    FileEntry {
+        is_hidden: name.starts_with('.'),
+        name,
+        path: path.to_string_lossy().to_string(),
+        relative_path,
+        is_dir,
+        is_symlink: metadata.is_symlink(),
+        size: metadata.len(),
+        modified: metadata.modified().ok().map(DateTime::from),
+        created: metadata.created().ok().map(DateTime::from),
+        extension: path.extension().map(|e| e.to_string_lossy().to_string()),
+        children_count: if is_dir {
+            std::fs::read_dir(path).map(|rd| rd.count()).ok()
+        } else {
+            None
+        },
+        git_status: None,
+    }
+}
+
+/// `list_directory` built from the index's in-memory path list instead of
+/// the filesystem: the same recursion, depth and hidden-entry rules, sorted
+/// and filtered by `options`. Directories are those containing an indexed
+/// file (size 0, `children_count` counting indexed children); files carry the
+/// size and mtime recorded at indexing and no creation time.
+pub fn list_indexed(
+    workspace_path: &Path,
+    paths: &PathIndex,
+    relative_path: &str,
+    recursive: bool,
+    show_hidden: bool,
+    max_depth: usize,
+    options: ListOptions,
+) -> Vec<FileEntry> {
+    let dir = relative_path.trim_matches('/');
+    let dir = if dir == "." { "" } else { dir };
+    let prefix = if dir.is_empty() { String::new() } else { format!("{}/", dir) };
+    let max_depth = if recursive { max_depth } else { 0 };
+
+    // Directory -> names of its indexed children
+    let mut dirs: BTreeMap<&str, std::collections::BTreeSet<&str>> = BTreeMap::new();
+    let mut files = Vec::new();
+    for (path, info) in paths.range(prefix.clone()..) {
+        let Some(rest) = path.strip_prefix(&prefix) else { break };
+        let parts: Vec<&str> = rest.split('/').collect();
+        for (depth, part) in parts.iter().enumerate() {
+            if depth > max_depth || (!show_hidden && part.starts_with('.')) {
+                break;
+            }
+            let end = prefix.len() + parts[..=depth].iter().map(|p| p.len() + 1).sum::<usize>() - 1;
+            if depth + 1 == parts.len() {
+                files.push((&path[..end], *info));
+            } else {
+                dirs.entry(&path[..end]).or_default().insert(parts[depth + 1]);
+            }
+        }
+    }
+
+    let entry = |relative: &str, is_dir: bool| {
+        let name = relative.rsplit('/').next().unwrap_or(relative).to_string();
+        FileEntry {
+            path: workspace_path.join(relative).to_string_lossy().to_string(),
+            relative_path: relative.to_string(),
+            is_dir,
+            is_symlink: false,
+            size: 0,
+            modified: None,
+            created: None,
+            extension: if is_dir {
+                None
+            } else {
+                Path::new(&name).extension().map(|e| e.to_string_lossy().to_string())
+            },
+            children_count: None,
+            is_hidden: name.starts_with('.'),
+            git_status: None,
+            name,
+        }
+    };
+    let mut entries: Vec<FileEntry> = dirs
+        .into_iter()
+        .map(|(relative, children)| FileEntry {
+            children_count: Some(children.len()),
+            ..entry(relative, true)
+        })
+        .chain(files.into_iter().map(|(relative, info)| FileEntry {
+            size: info.size,
+            modified: DateTime::from_timestamp(info.modified as i64, 0),
+            ..entry(relative, false)
+        }))
+        .filter(|e| options.includes(e))
+        .collect();
+    entries.sort_by(|a, b| options.compare(a, b));
+    entries
+}
+
+/// Fill in `git_status` on entries listed from `relative_dir`, with one git
+/// status call for the whole directory. Leaves them untouched when the
+/// workspace is not a git work tree.
+pub fn attach_git_status(workspace_path: &Path, relative_dir: &str, entries: &mut [FileEntry]) {
+    let Some(statuses) = GitStatusMap::load(workspace_path, relative_dir) else {
+        return;
+    };
+    for entry in entries {
+        entry.git_status = Some(statuses.status_of(&entry.relative_path, entry.is_dir));
+    }
+}
+
+/// Ordering and filtering applied by `list_directory`. The default is
+/// directories first, then by name A-Z.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ListOptions {
+    pub sort_by: ListSortKey,
+    pub sort_dir: SortDirection,
+    pub entry_type: EntryTypeFilter,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ListSortKey {
+    #[default]
+    Name,
+    Size,
+    Modified,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryTypeFilter {
+    #[default]
+    All,
+    Files,
+    Dirs,
+}
+
+impl ListOptions {
+    /// Directories stay ahead of files whatever the key; `sort_dir` orders
+    /// within each group. Entries with equal keys fall back to name A-Z so
+    /// the order is deterministic.
+    fn compare(&self, a: &FileEntry, b: &FileEntry) -> std::cmp::Ordering {
+        let by_name = || a.name.to_lowercase().cmp(&b.name.to_lowercase());
+        let key = match self.sort_by {
+            ListSortKey::Name => by_name(),
+            ListSortKey::Size => a.size.cmp(&b.size),
+            ListSortKey::Modified => a.modified.cmp(&b.modified),
+        };
+        let key = match self.sort_dir {
+            SortDirection::Asc => key,
+            SortDirection::Desc => key.reverse(),
+        };
+        b.is_dir.cmp(&a.is_dir).then(key).then_with(by_name)
+    }
+
+    fn includes(&self, entry: &FileEntry) -> bool {
+        match self.entry_type {
+            EntryTypeFilter::All => true,
+            EntryTypeFilter::Files => !entry.is_dir,
+            EntryTypeFilter::Dirs => entry.is_dir,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -78,26 +298,185 @@ pub struct FileStats {
     pub extension: Option<String>,
 }
 
+/// Node of the `/tree` response. For directories, `size` and `file_count`
+/// are recursive totals; `children` is omitted below the requested depth.
+#[derive(Debug, Clone, Serialize)]
+pub struct TreeNode {
+    pub name: String,
+    pub relative_path: String,
+    pub is_dir: bool,
+    pub size: u64,
+    pub file_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub children: Option<Vec<TreeNode>>,
+}
+
+impl TreeNode {
+    /// Copy of this node with children cut off after `depth` levels.
+    fn truncated(&self, depth: usize) -> TreeNode {
+        TreeNode {
+            name: self.name.clone(),
+            relative_path: self.relative_path.clone(),
+            is_dir: self.is_dir,
+            size: self.size,
+            file_count: self.file_count,
+            children: match &self.children {
+                Some(children) if depth > 0 => Some(
+                    children.iter().map(|c| c.truncated(depth - 1)).collect(),
+                ),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Settings of one `list_directory` walk, the same at every level.
+struct ListWalk<'a> {
+    /// Entries' relative paths are relative to this.
+    base_path: &'a Path,
+    recursive: bool,
+    show_hidden: bool,
+    max_depth: usize,
+}
+
+/// Mutable tree used while walking; converted to `TreeNode` once complete.
+#[derive(Default)]
+struct TreeBuilder {
+    is_dir: bool,
+    size: u64,
+    children: BTreeMap<String, TreeBuilder>,
+}
+
+impl TreeBuilder {
+    fn insert(&mut self, components: &[String], is_dir: bool, size: u64) {
+        match components {
+            [] => {}
+            [last] => {
+                let node = self.children.entry(last.clone()).or_default();
+                node.is_dir = is_dir;
+                node.size = size;
+            }
+            [first, rest @ ..] => {
+                let node = self.children.entry(first.clone()).or_default();
+                node.is_dir = true;
+                node.insert(rest, is_dir, size);
+            }
+        }
+    }
+
+    fn into_node(self, name: String, relative_path: String) -> TreeNode {
+        if !self.is_dir {
+            return TreeNode {
+                name,
+                relative_path,
+                is_dir: false,
+                size: self.size,
+                file_count: 1,
+                children: None,
+            };
+        }
+
+        let mut children: Vec<TreeNode> = self
+            .children
+            .into_iter()
+            .map(|(child_name, child)| {
+                let child_path = if relative_path.is_empty() {
+                    child_name.clone()
+                } else {
+                    format!("{}/{}", relative_path, child_name)
+                };
+                child.into_node(child_name, child_path)
+            })
+            .collect();
+
+        // Same order as list_directory: dirs first, then alphabetical
+        children.sort_by(|a, b| {
+            b.is_dir
+                .cmp(&a.is_dir)
+                .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+        });
+
+        TreeNode {
+            name,
+            relative_path,
+            is_dir: true,
+            size: children.iter().map(|c| c.size).sum(),
+            file_count: children.iter().map(|c| c.file_count).sum(),
+            children: Some(children),
+        }
+    }
+}
+
+/// Deepest level walked when building a tree; also the cap on `max_depth`.
+pub const MAX_TREE_DEPTH: usize = 20;
+
+/// How long a walked tree is reused before the workspace is walked again.
+const TREE_CACHE_TTL: Duration = Duration::from_secs(5);
+
+/// How often pending workspace changes are written to `workspaces.json`.
+pub const PERSIST_INTERVAL: Duration = Duration::from_secs(2);
+
 pub struct WorkspaceManager {
     workspaces: DashMap<String, Workspace>,
     data_dir: PathBuf,
     /// User-provided exclude patterns forwarded from app settings.
     user_exclude_patterns: Vec<String>,
+    /// Full workspace trees from recent `/tree` requests, keyed by workspace id.
+    tree_cache: DashMap<String, (Instant, Arc<TreeNode>)>,
+    /// SHA-256 of each workspace's own auth token, persisted apart from
+    /// `workspaces.json` so it never appears in API responses.
+    auth_token_hashes: DashMap<String, String>,
+    /// Whether several workspaces may be active at once
+    /// (`AppConfig::multiple_active_workspaces`).
+    multiple_active: bool,
+    /// Held for a whole activation or deactivation so concurrent ones can't
+    /// interleave and, in single-active mode, leave two workspaces active.
+    activation_lock: parking_lot::Mutex<()>,
+    /// Set by every mutation; `flush` writes `workspaces.json` only when set,
+    /// so bursts of stat updates during indexing cost one write.
+    dirty: AtomicBool,
+    /// Serializes `flush`, whose writes share one temp file.
+    flush_lock: parking_lot::Mutex<()>,
+    /// Serializes `persist_auth_tokens` for the same reason.
+    auth_tokens_lock: parking_lot::Mutex<()>,
+}
+
+/// Hex SHA-256 of an auth token; only the hash of a workspace token is kept.
+pub fn hash_auth_token(token: &str) -> String {
+    use sha2::{Digest, Sha256};
+    format!("{:x}", Sha256::digest(token.as_bytes()))
 }
 
 impl WorkspaceManager {
-    pub fn new(data_dir: PathBuf, user_exclude_patterns: Vec<String>) -> Self {
+    pub fn new(data_dir: PathBuf, user_exclude_patterns: Vec<String>, multiple_active: bool) -> Self {
         let manager = Self {
             workspaces: DashMap::new(),
             data_dir,
             user_exclude_patterns,
+            tree_cache: DashMap::new(),
+            auth_token_hashes: DashMap::new(),
+            multiple_active,
+            activation_lock: parking_lot::Mutex::new(()),
+            dirty: AtomicBool::new(false),
+            flush_lock: parking_lot::Mutex::new(()),
+            auth_tokens_lock: parking_lot::Mutex::new(()),
         };
         // Load persisted workspaces on startup
-        if let Ok(content) = std::fs::read_to_string(manager.workspaces_file()) {
-            if let Ok(workspaces) = serde_json::from_str::<Vec<Workspace>>(&content) {
-                for ws in workspaces {
-                    manager.workspaces.insert(ws.id.clone(), ws);
-                }
+        if let Ok(content) = std::fs::read_to_string(manager.workspaces_file())
+            && let Ok(workspaces) = serde_json::from_str::<Vec<Workspace>>(&content)
+        {
+            for ws in workspaces {
+                manager.workspaces.insert(ws.id.clone(), ws);
+            }
+        }
+        if !multiple_active {
+            manager.keep_latest_active();
+        }
+        if let Ok(content) = std::fs::read_to_string(manager.auth_tokens_file())
+            && let Ok(hashes) = serde_json::from_str::<HashMap<String, String>>(&content)
+        {
+            for (id, hash) in hashes {
+                manager.auth_token_hashes.insert(id, hash);
             }
         }
         manager
@@ -107,6 +486,92 @@ impl WorkspaceManager {
         self.data_dir.join("workspaces.json")
     }
 
+    fn auth_tokens_file(&self) -> PathBuf {
+        self.data_dir.join("workspace_tokens.json")
+    }
+
+    fn persist_auth_tokens(&self) -> AppResult<()> {
+        // Snapshot under the lock, so the last write holds every change
+        let _guard = self.auth_tokens_lock.lock();
+        let hashes: HashMap<String, String> = self
+            .auth_token_hashes
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+        let json = serde_json::to_string_pretty(&hashes)?;
+        std::fs::create_dir_all(&self.data_dir)?;
+        let target = self.auth_tokens_file();
+        let tmp = target.with_extension("json.tmp");
+        std::fs::write(&tmp, &json)?;
+        std::fs::rename(&tmp, &target)?;
+        Ok(())
+    }
+
+    /// Set or clear (None) the token that grants access to this workspace's
+    /// routes. Only its hash is stored.
+    pub fn set_auth_token(&self, id: &str, token: Option<&str>) -> AppResult<()> {
+        self.get_workspace(id)?;
+        match token {
+            Some(token) => {
+                self.auth_token_hashes.insert(id.to_string(), hash_auth_token(token));
+            }
+            None => {
+                self.auth_token_hashes.remove(id);
+            }
+        }
+        self.persist_auth_tokens()
+    }
+
+    /// Hash of the workspace's own auth token, if one is set.
+    pub fn auth_token_hash(&self, id: &str) -> Option<String> {
+        self.auth_token_hashes.get(id).map(|hash| hash.value().clone())
+    }
+
+    /// Ids of workspaces (other than `except_id`) with their own auth token
+    /// whose root contains `path` or lies inside it. A workspace registered
+    /// at `path` would reach their files without their token.
+    pub fn protected_overlapping(&self, path: &str, except_id: Option<&str>) -> Vec<String> {
+        let Ok(canonical) = dunce::canonicalize(path) else {
+            return Vec::new();
+        };
+        self.auth_token_hashes
+            .iter()
+            .map(|entry| entry.key().clone())
+            .filter(|id| Some(id.as_str()) != except_id)
+            .filter(|id| {
+                self.workspaces.get(id).is_some_and(|ws| {
+                    let root = Path::new(&ws.path);
+                    is_within(&canonical, root) || is_within(root, &canonical)
+                })
+            })
+            .collect()
+    }
+
+    /// Whether any workspace has its own auth token.
+    pub fn has_auth_tokens(&self) -> bool {
+        !self.auth_token_hashes.is_empty()
+    }
+
+    /// Schedule a write of `workspaces.json`; see `flush`.
+    fn mark_dirty(&self) {
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    /// Write `workspaces.json` if anything changed since the last write.
+    /// Called every `PERSIST_INTERVAL` and on shutdown.
+    pub fn flush(&self) -> AppResult<()> {
+        let _guard = self.flush_lock.lock();
+        if !self.dirty.swap(false, Ordering::AcqRel) {
+            return Ok(());
+        }
+        // Changes made while writing set the flag again for the next flush
+        let result = self.persist();
+        if result.is_err() {
+            self.dirty.store(true, Ordering::Release);
+        }
+        result
+    }
+
     fn persist(&self) -> AppResult<()> {
         let workspaces: Vec<Workspace> = self
             .workspaces
@@ -123,19 +588,18 @@ impl WorkspaceManager {
         Ok(())
     }
 
-    pub fn create_workspace(&self, name: String, path: String) -> AppResult<Workspace> {
+    pub fn create_workspace(
+        &self,
+        name: String,
+        path: String,
+        allow_symlinks_outside: bool,
+    ) -> AppResult<Workspace> {
         let canonical = dunce::canonicalize(&path).map_err(|_| {
             AppError::FileNotFound(format!("Path does not exist: {}", path))
         })?;
 
-        // Check for duplicate path
-        for entry in self.workspaces.iter() {
-            if dunce::canonicalize(&entry.value().path)
-                .map(|p| p == canonical)
-                .unwrap_or(false)
-            {
-                return Err(AppError::WorkspaceAlreadyExists(path.clone()));
-            }
+        if self.path_in_use(&canonical, None) {
+            return Err(AppError::WorkspaceAlreadyExists(path.clone()));
         }
 
         let workspace = Workspace {
@@ -148,13 +612,50 @@ impl WorkspaceManager {
             indexed: false,
             total_files: 0,
             total_size_bytes: 0,
+            allow_symlinks_outside,
+            last_indexed_at: None,
+            last_index_duration_ms: None,
+            max_file_size_bytes: None,
+            fold_accents: false,
+            available: true,
         };
 
         self.workspaces.insert(workspace.id.clone(), workspace.clone());
-        self.persist()?;
+        self.mark_dirty();
         Ok(workspace)
     }
 
+    /// Whether a workspace other than `except_id` is rooted at `canonical`.
+    fn path_in_use(&self, canonical: &Path, except_id: Option<&str>) -> bool {
+        self.workspaces.iter().any(|entry| {
+            Some(entry.key().as_str()) != except_id
+                && dunce::canonicalize(&entry.value().path).is_ok_and(|p| p == canonical)
+        })
+    }
+
+    /// Point a workspace at a new root directory (the project folder moved).
+    /// Only the record changes; the caller restarts the watcher and refreshes
+    /// the index, whose relative-path keys carry over to the new root.
+    pub fn relocate_workspace(&self, id: &str, path: &str) -> AppResult<Workspace> {
+        let canonical = dunce::canonicalize(path).map_err(|_| {
+            AppError::FileNotFound(format!("Path does not exist: {}", path))
+        })?;
+        if self.path_in_use(&canonical, Some(id)) {
+            return Err(AppError::WorkspaceAlreadyExists(path.to_string()));
+        }
+
+        let mut ws = self
+            .workspaces
+            .get_mut(id)
+            .ok_or_else(|| AppError::WorkspaceNotFound(id.to_string()))?;
+        ws.path = canonical.to_string_lossy().to_string();
+        ws.available = true;
+        let result = ws.clone();
+        drop(ws);
+        self.mark_dirty();
+        Ok(result)
+    }
+
     pub fn get_workspace(&self, id: &str) -> AppResult<Workspace> {
         self.workspaces
             .get(id)
@@ -168,7 +669,7 @@ impl WorkspaceManager {
             .iter()
             .map(|entry| entry.value().clone())
             .collect();
-        workspaces.sort_by(|a, b| b.last_accessed.cmp(&a.last_accessed));
+        workspaces.sort_by_key(|w| std::cmp::Reverse(w.last_accessed));
         workspaces
     }
 
@@ -176,14 +677,41 @@ impl WorkspaceManager {
         self.workspaces
             .remove(id)
             .ok_or_else(|| AppError::WorkspaceNotFound(id.to_string()))?;
-        self.persist()?;
+        self.tree_cache.remove(id);
+        self.mark_dirty();
+        if self.auth_token_hashes.remove(id).is_some() {
+            self.persist_auth_tokens()?;
+        }
         Ok(())
     }
 
-    pub fn activate_workspace(&self, id: &str) -> AppResult<Workspace> {
-        // Deactivate all
+    /// Workspaces saved while multiple-active mode was on may have several
+    /// active; in single-active mode only the most recently accessed stays so.
+    fn keep_latest_active(&self) {
+        let latest = self
+            .workspaces
+            .iter()
+            .filter(|entry| entry.value().is_active)
+            .max_by_key(|entry| entry.value().last_accessed)
+            .map(|entry| entry.key().clone());
         for mut entry in self.workspaces.iter_mut() {
-            entry.value_mut().is_active = false;
+            if Some(entry.key()) != latest.as_ref() {
+                entry.value_mut().is_active = false;
+            }
+        }
+    }
+
+    /// Mark a workspace active. In single-active mode every other workspace
+    /// is deactivated in the same step.
+    pub fn activate_workspace(&self, id: &str) -> AppResult<Workspace> {
+        let _guard = self.activation_lock.lock();
+        if !self.workspaces.contains_key(id) {
+            return Err(AppError::WorkspaceNotFound(id.to_string()));
+        }
+        if !self.multiple_active {
+            for mut entry in self.workspaces.iter_mut() {
+                entry.value_mut().is_active = false;
+            }
         }
         // Activate selected
         let mut ws = self
@@ -194,7 +722,22 @@ impl WorkspaceManager {
         ws.last_accessed = Utc::now();
         let result = ws.clone();
         drop(ws);
-        self.persist()?;
+        self.mark_dirty();
+        Ok(result)
+    }
+
+    /// Mark a workspace inactive. Its watcher and index are left running, as
+    /// for any other inactive workspace.
+    pub fn deactivate_workspace(&self, id: &str) -> AppResult<Workspace> {
+        let _guard = self.activation_lock.lock();
+        let mut ws = self
+            .workspaces
+            .get_mut(id)
+            .ok_or_else(|| AppError::WorkspaceNotFound(id.to_string()))?;
+        ws.is_active = false;
+        let result = ws.clone();
+        drop(ws);
+        self.mark_dirty();
         Ok(result)
     }
 
@@ -204,6 +747,7 @@ impl WorkspaceManager {
         total_files: usize,
         total_size_bytes: u64,
         indexed: bool,
+        index_duration_ms: u64,
     ) -> AppResult<()> {
         let mut ws = self
             .workspaces
@@ -212,16 +756,119 @@ impl WorkspaceManager {
         ws.total_files = total_files;
         ws.total_size_bytes = total_size_bytes;
         ws.indexed = indexed;
+        if indexed {
+            ws.last_indexed_at = Some(Utc::now());
+            ws.last_index_duration_ms = Some(index_duration_ms);
+        }
+        drop(ws);
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Apply an incremental change (from the file watcher) to the stored
+    /// totals, keeping them accurate between full index passes.
+    pub fn adjust_workspace_stats(&self, id: &str, delta: StatsDelta) -> AppResult<()> {
+        if delta.is_zero() {
+            return Ok(());
+        }
+        let mut ws = self
+            .workspaces
+            .get_mut(id)
+            .ok_or_else(|| AppError::WorkspaceNotFound(id.to_string()))?;
+        ws.total_files = (ws.total_files as u64).saturating_add_signed(delta.files) as usize;
+        ws.total_size_bytes = ws.total_size_bytes.saturating_add_signed(delta.bytes);
         drop(ws);
-        self.persist()?;
+        self.mark_dirty();
         Ok(())
     }
 
+    pub fn set_allow_symlinks_outside(&self, id: &str, allow: bool) -> AppResult<Workspace> {
+        let mut ws = self
+            .workspaces
+            .get_mut(id)
+            .ok_or_else(|| AppError::WorkspaceNotFound(id.to_string()))?;
+        ws.allow_symlinks_outside = allow;
+        let result = ws.clone();
+        drop(ws);
+        self.mark_dirty();
+        Ok(result)
+    }
+
+    /// Set or clear (None) the workspace's file size limit override.
+    pub fn set_max_file_size(&self, id: &str, max_file_size_bytes: Option<usize>) -> AppResult<Workspace> {
+        let mut ws = self
+            .workspaces
+            .get_mut(id)
+            .ok_or_else(|| AppError::WorkspaceNotFound(id.to_string()))?;
+        ws.max_file_size_bytes = max_file_size_bytes;
+        let result = ws.clone();
+        drop(ws);
+        self.mark_dirty();
+        Ok(result)
+    }
+
+    /// Re-check whether the workspace's root directory exists and record the
+    /// result. The flag is true when availability changed with this check.
+    pub fn refresh_availability(&self, id: &str) -> AppResult<(Workspace, bool)> {
+        let mut ws = self
+            .workspaces
+            .get_mut(id)
+            .ok_or_else(|| AppError::WorkspaceNotFound(id.to_string()))?;
+        let available = Path::new(&ws.path).is_dir();
+        let changed = ws.available != available;
+        ws.available = available;
+        let result = ws.clone();
+        drop(ws);
+        if changed {
+            self.mark_dirty();
+        }
+        Ok((result, changed))
+    }
+
+    pub fn set_fold_accents(&self, id: &str, fold_accents: bool) -> AppResult<Workspace> {
+        let mut ws = self
+            .workspaces
+            .get_mut(id)
+            .ok_or_else(|| AppError::WorkspaceNotFound(id.to_string()))?;
+        ws.fold_accents = fold_accents;
+        let result = ws.clone();
+        drop(ws);
+        self.mark_dirty();
+        Ok(result)
+    }
+
+    /// Resolve a workspace-relative path for mutation. Symlinks are always
+    /// resolved and the target must lie inside the workspace root.
     pub fn validate_path(&self, workspace_id: &str, file_path: &str) -> AppResult<PathBuf> {
+        self.resolve_path(workspace_id, file_path, false)
+    }
+
+    /// Resolve a workspace-relative path for reading. Same as `validate_path`,
+    /// except that symlinks escaping the root are followed when the workspace
+    /// has `allow_symlinks_outside` set.
+    pub fn validate_read_path(&self, workspace_id: &str, file_path: &str) -> AppResult<PathBuf> {
+        self.resolve_path(workspace_id, file_path, true)
+    }
+
+    fn resolve_path(
+        &self,
+        workspace_id: &str,
+        file_path: &str,
+        follow_outside_symlinks: bool,
+    ) -> AppResult<PathBuf> {
         let ws = self.get_workspace(workspace_id)?;
         let ws_path = PathBuf::from(&ws.path);
         let full_path = ws_path.join(file_path);
 
+        // Lexical check first: `..` components must never climb out of the root,
+        // whatever the symlink policy is.
+        if !is_within(&full_path, &ws_path) {
+            return Err(AppError::PathNotAllowed(format!(
+                "Path '{}' is outside workspace",
+                file_path
+            )));
+        }
+
         let canonical = dunce::canonicalize(&full_path)
             .or_else(|_| {
                 // For files that don't exist yet, validate parent
@@ -239,11 +886,19 @@ impl WorkspaceManager {
         let ws_canonical = dunce::canonicalize(&ws_path)
             .map_err(|_| AppError::WorkspaceNotFound(workspace_id.to_string()))?;
 
-        if !canonical.starts_with(&ws_canonical) {
-            return Err(AppError::PathNotAllowed(format!(
-                "Path '{}' is outside workspace",
-                file_path
-            )));
+        if !is_within(&canonical, &ws_canonical) {
+            // Only a symlink can get here past the lexical check. Follow it when
+            // the workspace opted in, but not combined with `..`, which the OS
+            // resolves relative to the link target rather than the link itself.
+            let has_parent_dir = Path::new(file_path)
+                .components()
+                .any(|c| matches!(c, Component::ParentDir));
+            if !(follow_outside_symlinks && ws.allow_symlinks_outside && !has_parent_dir) {
+                return Err(AppError::PathNotAllowed(format!(
+                    "Path '{}' is outside workspace",
+                    file_path
+                )));
+            }
         }
 
         Ok(canonical)
@@ -257,13 +912,16 @@ impl WorkspaceManager {
         recursive: bool,
         show_hidden: bool,
         max_depth: usize,
+        options: ListOptions,
     ) -> AppResult<Vec<FileEntry>> {
         let ws = self.get_workspace(workspace_id)?;
         let base_path = PathBuf::from(&ws.path);
+        // Validated like file paths so `../repo-secrets` can't list a sibling
+        // directory that merely shares the workspace's name as a prefix
         let target_path = if relative_path.is_empty() || relative_path == "." {
             base_path.clone()
         } else {
-            base_path.join(relative_path)
+            self.validate_path(workspace_id, relative_path)?
         };
 
         if !target_path.exists() {
@@ -271,37 +929,110 @@ impl WorkspaceManager {
         }
 
         let mut entries = Vec::new();
-        self.collect_entries(
-            &base_path,
-            &target_path,
+        let walk = ListWalk {
+            base_path: &base_path,
             recursive,
             show_hidden,
             max_depth,
-            0,
-            &mut entries,
-        )?;
+        };
+        self.collect_entries(&walk, &target_path, 0, &mut entries)?;
 
-        // Sort: dirs first, then alphabetical
-        entries.sort_by(|a, b| {
-            b.is_dir
-                .cmp(&a.is_dir)
-                .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
-        });
+        entries.retain(|e| options.includes(e));
+        entries.sort_by(|a, b| options.compare(a, b));
 
         Ok(entries)
     }
 
+    /// Nested tree of `relative_path` with recursive sizes and file counts,
+    /// expanded `max_depth` levels deep. Follows the indexer's exclusion rules
+    /// (.gitignore, build/output directories, user patterns).
+    pub fn tree(
+        &self,
+        workspace_id: &str,
+        relative_path: &str,
+        max_depth: usize,
+    ) -> AppResult<TreeNode> {
+        let root = self.cached_tree(workspace_id)?;
+
+        let mut node = root.as_ref();
+        for part in relative_path.split(['/', '\\']).filter(|p| !p.is_empty() && *p != ".") {
+            node = node
+                .children
+                .as_ref()
+                .and_then(|children| children.iter().find(|c| c.name == part))
+                .ok_or_else(|| AppError::FileNotFound(relative_path.to_string()))?;
+        }
+
+        Ok(node.truncated(max_depth.min(MAX_TREE_DEPTH)))
+    }
+
+    fn cached_tree(&self, workspace_id: &str) -> AppResult<Arc<TreeNode>> {
+        if let Some(entry) = self.tree_cache.get(workspace_id) {
+            let (built_at, tree) = entry.value();
+            if built_at.elapsed() < TREE_CACHE_TTL {
+                return Ok(tree.clone());
+            }
+        }
+
+        let ws = self.get_workspace(workspace_id)?;
+        let base_path = PathBuf::from(&ws.path);
+        if !base_path.is_dir() {
+            return Err(AppError::FileNotFound(ws.path.clone()));
+        }
+
+        let user_patterns = self.user_exclude_patterns.clone();
+        let prune_base = base_path.clone();
+        let mut builder = TreeBuilder {
+            is_dir: true,
+            ..Default::default()
+        };
+
+        let walker = WalkBuilder::new(&base_path)
+            .hidden(false)
+            .git_ignore(true)
+            .git_global(true)
+            .git_exclude(true)
+            .max_depth(Some(MAX_TREE_DEPTH))
+            .filter_entry(move |entry| {
+                let relative = entry.path().strip_prefix(&prune_base).unwrap_or(entry.path());
+                !IndexManager::is_build_or_output_dir_with_patterns(relative, &user_patterns)
+            })
+            .build();
+
+        for entry in walker.filter_map(|e| e.ok()) {
+            let Ok(relative) = entry.path().strip_prefix(&base_path) else {
+                continue;
+            };
+            let components: Vec<String> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect();
+            if components.is_empty() {
+                continue;
+            }
+            let is_dir = entry.file_type().is_some_and(|ft| ft.is_dir());
+            let size = if is_dir {
+                0
+            } else {
+                entry.metadata().map(|m| m.len()).unwrap_or(0)
+            };
+            builder.insert(&components, is_dir, size);
+        }
+
+        let tree = Arc::new(builder.into_node(ws.name.clone(), String::new()));
+        self.tree_cache
+            .insert(workspace_id.to_string(), (Instant::now(), tree.clone()));
+        Ok(tree)
+    }
+
     fn collect_entries(
         &self,
-        base_path: &Path,
+        walk: &ListWalk<'_>,
         dir_path: &Path,
-        recursive: bool,
-        show_hidden: bool,
-        max_depth: usize,
         current_depth: usize,
         entries: &mut Vec<FileEntry>,
     ) -> AppResult<()> {
-        if current_depth > max_depth {
+        if current_depth > walk.max_depth {
             return Ok(());
         }
 
@@ -313,7 +1044,7 @@ impl WorkspaceManager {
             let name = entry.file_name().to_string_lossy().to_string();
 
             // Skip hidden files unless requested
-            if !show_hidden && name.starts_with('.') {
+            if !walk.show_hidden && name.starts_with('.') {
                 continue;
             }
 
@@ -324,44 +1055,18 @@ impl WorkspaceManager {
 
             let path = entry.path();
             let relative = path
-                .strip_prefix(base_path)
+                .strip_prefix(walk.base_path)
                 .unwrap_or(&path)
                 .to_string_lossy()
                 .replace('\\', "/");
 
             let is_dir = metadata.is_dir();
-            let children_count = if is_dir {
-                std::fs::read_dir(&path).map(|rd| rd.count()).ok()
-            } else {
-                None
-            };
-
-            let file_entry = FileEntry {
-                name: name.clone(),
-                path: path.to_string_lossy().to_string(),
-                relative_path: relative,
-                is_dir,
-                is_symlink: metadata.is_symlink(),
-                size: metadata.len(),
-                modified: metadata.modified().ok().map(DateTime::from),
-                created: metadata.created().ok().map(DateTime::from),
-                extension: path.extension().map(|e| e.to_string_lossy().to_string()),
-                children_count,
-                is_hidden: name.starts_with('.'),
-            };
+            let file_entry = file_entry(&path, relative, &metadata);
 
             entries.push(file_entry);
 
-            if recursive && is_dir {
-                self.collect_entries(
-                    base_path,
-                    &path,
-                    recursive,
-                    show_hidden,
-                    max_depth,
-                    current_depth + 1,
-                    entries,
-                )?;
+            if walk.recursive && is_dir {
+                self.collect_entries(walk, &path, current_depth + 1, entries)?;
             }
         }
 
@@ -375,7 +1080,7 @@ impl WorkspaceManager {
     }
 
     pub fn get_file_stats(&self, workspace_id: &str, relative_path: &str) -> AppResult<FileStats> {
-        let full_path = self.validate_path(workspace_id, relative_path)?;
+        let full_path = self.validate_read_path(workspace_id, relative_path)?;
         let metadata = std::fs::metadata(&full_path)?;
 
         Ok(FileStats {
@@ -394,3 +1099,255 @@ impl WorkspaceManager {
         })
     }
 }
+
+/// True if `path` is `root` or lies below it. Both sides are lexically
+/// normalized and compared component by component, so separators, trailing
+/// slashes and `.` segments don't matter and `C:\repo2` is not inside
+/// `C:\repo`. Components compare case-insensitively on macOS and Windows,
+/// whose filesystems are.
+fn is_within(path: &Path, root: &Path) -> bool {
+    let path = lexically_normalize(path);
+    let root = lexically_normalize(root);
+    let mut path_components = path.components();
+    root.components().all(|root_component| {
+        path_components.next().is_some_and(|component| {
+            if cfg!(any(target_os = "macos", windows)) {
+                component.as_os_str().eq_ignore_ascii_case(root_component.as_os_str())
+            } else {
+                component == root_component
+            }
+        })
+    })
+}
+
+/// Resolve `.` and `..` components without touching the filesystem.
+fn lexically_normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A workspace at `<tmp>/ws` (holding `src/a.txt`) next to a sibling
+    /// `<tmp>/ws2` and a file `<tmp>/secret.txt` outside it.
+    struct Fixture {
+        tmp: tempfile::TempDir,
+        manager: WorkspaceManager,
+        id: String,
+    }
+
+    impl Fixture {
+        fn new(allow_symlinks_outside: bool) -> Self {
+            let tmp = tempfile::tempdir().unwrap();
+            let root = tmp.path().join("ws");
+            std::fs::create_dir_all(root.join("src")).unwrap();
+            std::fs::write(root.join("src/a.txt"), "a").unwrap();
+            std::fs::create_dir_all(tmp.path().join("ws2")).unwrap();
+            std::fs::write(tmp.path().join("ws2/b.txt"), "b").unwrap();
+            std::fs::write(tmp.path().join("secret.txt"), "secret").unwrap();
+
+            let manager = WorkspaceManager::new(tmp.path().join("data"), Vec::new(), false);
+            let id = manager
+                .create_workspace("ws".into(), root.to_string_lossy().into_owned(), allow_symlinks_outside)
+                .unwrap()
+                .id;
+            Self { tmp, manager, id }
+        }
+
+        fn outside(&self) -> PathBuf {
+            dunce::canonicalize(self.tmp.path()).unwrap()
+        }
+
+        fn root(&self) -> PathBuf {
+            self.outside().join("ws")
+        }
+    }
+
+    fn is_not_allowed<T: std::fmt::Debug>(result: AppResult<T>) -> bool {
+        matches!(result, Err(AppError::PathNotAllowed(_)))
+    }
+
+    #[test]
+    fn parent_dir_components_cannot_leave_the_root() {
+        let fx = Fixture::new(false);
+        assert!(is_not_allowed(fx.manager.validate_path(&fx.id, "../secret.txt")));
+        assert!(is_not_allowed(fx.manager.validate_path(&fx.id, "src/../../secret.txt")));
+        assert!(is_not_allowed(fx.manager.validate_path(&fx.id, "../ws2/b.txt")));
+        assert_eq!(
+            fx.manager.validate_path(&fx.id, "src/../src/a.txt").unwrap(),
+            fx.root().join("src/a.txt")
+        );
+    }
+
+    #[test]
+    fn absolute_paths_outside_the_root_are_rejected() {
+        let fx = Fixture::new(false);
+        let secret = fx.outside().join("secret.txt");
+        assert!(is_not_allowed(fx.manager.validate_path(&fx.id, &secret.to_string_lossy())));
+        assert!(is_not_allowed(fx.manager.validate_read_path(&fx.id, &secret.to_string_lossy())));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_escaping_the_root_is_rejected() {
+        let fx = Fixture::new(false);
+        std::os::unix::fs::symlink(fx.outside(), fx.root().join("link")).unwrap();
+        assert!(is_not_allowed(fx.manager.validate_path(&fx.id, "link/secret.txt")));
+        assert!(is_not_allowed(fx.manager.validate_read_path(&fx.id, "link/secret.txt")));
+    }
+
+    #[test]
+    fn is_within_compares_whole_components() {
+        assert!(is_within(Path::new("/ws"), Path::new("/ws")));
+        assert!(is_within(Path::new("/ws/a/b.rs"), Path::new("/ws")));
+        assert!(!is_within(Path::new("/ws2"), Path::new("/ws")));
+        assert!(!is_within(Path::new("/ws2/a.rs"), Path::new("/ws")));
+        assert!(!is_within(Path::new("/w"), Path::new("/ws")));
+        assert!(!is_within(Path::new("/ws/../ws2/a.rs"), Path::new("/ws")));
+        assert!(is_within(Path::new("/ws/./a/../b.rs"), Path::new("/ws")));
+    }
+
+    #[cfg(any(target_os = "macos", windows))]
+    #[test]
+    fn is_within_ignores_case_on_case_insensitive_platforms() {
+        assert!(is_within(Path::new("/WS/a.rs"), Path::new("/ws")));
+        assert!(is_within(Path::new("/ws/a.rs"), Path::new("/Ws")));
+        assert!(!is_within(Path::new("/WS2/a.rs"), Path::new("/ws")));
+    }
+
+    #[cfg(not(any(target_os = "macos", windows)))]
+    #[test]
+    fn is_within_is_case_sensitive_elsewhere() {
+        assert!(!is_within(Path::new("/WS/a.rs"), Path::new("/ws")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn allow_symlinks_outside_only_opens_reads() {
+        let fx = Fixture::new(true);
+        std::os::unix::fs::symlink(fx.outside(), fx.root().join("link")).unwrap();
+        assert_eq!(
+            fx.manager.validate_read_path(&fx.id, "link/secret.txt").unwrap(),
+            fx.outside().join("secret.txt")
+        );
+        // Mutations never follow a link out of the root
+        assert!(is_not_allowed(fx.manager.validate_path(&fx.id, "link/secret.txt")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn allow_symlinks_outside_off_rejects_reads_through_links() {
+        let fx = Fixture::new(false);
+        std::os::unix::fs::symlink(fx.outside(), fx.root().join("link")).unwrap();
+        assert!(is_not_allowed(fx.manager.validate_read_path(&fx.id, "link/secret.txt")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn parent_dir_through_an_outside_symlink_is_rejected() {
+        let fx = Fixture::new(true);
+        std::os::unix::fs::symlink(fx.outside().join("ws2"), fx.root().join("link")).unwrap();
+        // Lexically inside the root, but the OS resolves `..` from the link
+        // target: `<tmp>/ws2/..` is `<tmp>`
+        assert!(is_not_allowed(fx.manager.validate_read_path(&fx.id, "link/../secret.txt")));
+        assert!(fx.manager.validate_read_path(&fx.id, "link/b.txt").is_ok());
+    }
+
+    /// A manager holding `count` workspaces, with their ids.
+    fn manager_with_workspaces(
+        tmp: &tempfile::TempDir,
+        count: usize,
+        multiple_active: bool,
+    ) -> (WorkspaceManager, Vec<String>) {
+        let manager = WorkspaceManager::new(tmp.path().join("data"), Vec::new(), multiple_active);
+        let ids = (0..count)
+            .map(|i| {
+                let root = tmp.path().join(format!("ws{}", i));
+                std::fs::create_dir_all(&root).unwrap();
+                manager
+                    .create_workspace(format!("ws{}", i), root.to_string_lossy().into_owned(), false)
+                    .unwrap()
+                    .id
+            })
+            .collect();
+        (manager, ids)
+    }
+
+    fn active_ids(manager: &WorkspaceManager) -> Vec<String> {
+        manager
+            .list_workspaces()
+            .into_iter()
+            .filter(|ws| ws.is_active)
+            .map(|ws| ws.id)
+            .collect()
+    }
+
+    #[test]
+    fn concurrent_activations_leave_exactly_one_active() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (manager, ids) = manager_with_workspaces(&tmp, 8, false);
+        let barrier = std::sync::Barrier::new(ids.len());
+        let done = AtomicBool::new(false);
+
+        let most_active_seen = std::thread::scope(|scope| {
+            // Whenever no activation is in progress, at most one is active
+            let observer = scope.spawn(|| {
+                let mut most = 0;
+                while !done.load(Ordering::Acquire) {
+                    let _guard = manager.activation_lock.lock();
+                    most = most.max(active_ids(&manager).len());
+                }
+                most
+            });
+            let workers: Vec<_> = (0..ids.len())
+                .map(|i| {
+                    let (manager, barrier, ids) = (&manager, &barrier, &ids);
+                    scope.spawn(move || {
+                        barrier.wait();
+                        // Each thread walks the workspaces from a different start
+                        for step in 0..10_000 {
+                            manager.activate_workspace(&ids[(i + step) % ids.len()]).unwrap();
+                        }
+                    })
+                })
+                .collect();
+            for worker in workers {
+                worker.join().unwrap();
+            }
+            done.store(true, Ordering::Release);
+            observer.join().unwrap()
+        });
+
+        assert!(most_active_seen <= 1, "{} active at once", most_active_seen);
+        assert_eq!(active_ids(&manager).len(), 1);
+    }
+
+    #[test]
+    fn multiple_active_mode_keeps_every_activation() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (manager, ids) = manager_with_workspaces(&tmp, 4, true);
+
+        std::thread::scope(|scope| {
+            for id in &ids {
+                let manager = &manager;
+                scope.spawn(move || manager.activate_workspace(id).unwrap());
+            }
+        });
+        let mut active = active_ids(&manager);
+        active.sort();
+        let mut expected = ids.clone();
+        expected.sort();
+        assert_eq!(active, expected);
+    }
+}
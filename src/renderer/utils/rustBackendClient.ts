@@ -738,7 +738,7 @@ class RustBackendClient {
   async searchFiles(
     workspaceId: string,
     query: string,
-    options: { file_types?: string[]; limit?: number } = {},
+    options: { file_types?: string[]; limit?: number; include_unindexed?: boolean } = {},
   ): Promise<RustFileEntry[]> {
     return request(`/api/workspaces/${workspaceId}/files/search`, {
       method: 'POST',